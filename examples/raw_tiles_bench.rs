@@ -0,0 +1,28 @@
+// Times a full parse of world.dat against World::parse_raw_tiles.
+//
+//     cargo run --release --example raw_tiles_bench [runs]
+
+use gtworld_r::prelude::*;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+fn main() {
+    let runs: u32 = std::env::args().nth(1).and_then(|runs| runs.parse().ok()).unwrap_or(200);
+    let data = std::fs::read("world.dat").unwrap();
+    let item_database = Arc::new(RwLock::new(gtitem_r::load_from_file("items.dat").unwrap()));
+
+    let start = Instant::now();
+    for _ in 0..runs {
+        let mut world = World::new(Arc::clone(&item_database));
+        world.parse(&data).unwrap();
+    }
+    let full = start.elapsed() / runs;
+
+    let start = Instant::now();
+    for _ in 0..runs {
+        World::parse_raw_tiles(&data, ParseOptions::default()).unwrap();
+    }
+    let raw = start.elapsed() / runs;
+
+    println!("world.dat, {} runs: full parse {:?}, parse_raw_tiles {:?}", runs, full, raw);
+}
@@ -0,0 +1,486 @@
+use crate::flags::LockSettings;
+use crate::tile::Tile;
+use crate::tile_extra::TileType;
+use crate::world::{World, BEDROCK_ID};
+use std::collections::{HashSet, VecDeque};
+
+// small, big, huge and builder's locks only cover the tiles they own
+const AREA_LOCK_IDS: [u16; 4] = [202, 204, 206, 4994];
+// how many tiles each of those claims at most, the lock tile included
+const AREA_LOCK_SIZES: [usize; 4] = [10, 48, 200, 200];
+
+// The lock that covers the whole world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldLockInfo {
+    pub x: u32,
+    pub y: u32,
+    pub item_id: u16,
+    pub owner_uid: u32,
+    pub is_public: bool,
+    pub minimum_level: u8,
+    pub access_uids: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ActorInfo {
+    pub user_id: u32,
+    pub level: u8,
+    pub friends_with_owner: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DenyReason {
+    LevelTooLow { required: u8 },
+    NotOnAccessList,
+    NotFriends,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AccessDecision {
+    Allowed,
+    Denied(DenyReason),
+    NotAnEntrance,
+}
+
+fn has_lock_access(lock: &Tile, user_id: u32) -> bool {
+    match &lock.tile_type {
+        TileType::Lock {
+            settings,
+            owner_uid,
+            access_uids,
+            ..
+        } => *owner_uid == user_id || access_uids.contains(&user_id) || is_open_to_public(lock, settings),
+        _ => false,
+    }
+}
+
+// Either the settings byte or the tile flag opens a lock, neither has been
+// seen set in a capture so both count.
+fn is_open_to_public(lock: &Tile, settings: &LockSettings) -> bool {
+    settings.is_open_to_public || lock.flags.is_open_to_public
+}
+
+impl World {
+    // Every lock tile that isn't an area lock: World, Diamond, Royal, Guild,
+    // the themed variants and so on. A sane world has at most one, more than
+    // one means the capture is corrupt.
+    pub fn world_lock_tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.iter().filter(|tile| {
+            matches!(tile.tile_type, TileType::Lock { .. })
+                && !AREA_LOCK_IDS.contains(&tile.foreground_item_id)
+        })
+    }
+
+    // The world's owner and access settings, None for an unowned world. With
+    // several world locks the first in reading order wins.
+    pub fn world_lock(&self) -> Option<WorldLockInfo> {
+        let lock = self.world_lock_tiles().next()?;
+        match &lock.tile_type {
+            TileType::Lock {
+                settings,
+                owner_uid,
+                access_uids,
+                minimum_level,
+                ..
+            } => Some(WorldLockInfo {
+                x: lock.x,
+                y: lock.y,
+                item_id: lock.foreground_item_id,
+                owner_uid: *owner_uid,
+                is_public: is_open_to_public(lock, settings),
+                minimum_level: *minimum_level,
+                access_uids: access_uids.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    // the lock that owns the tile, falling back to the world lock
+    pub fn governing_lock(&self, tile: &Tile) -> Option<&Tile> {
+        if tile.parent_block_index != 0 {
+            if let Some(parent) = self.tiles.get(tile.parent_block_index as usize) {
+                if matches!(parent.tile_type, TileType::Lock { .. }) {
+                    return Some(parent);
+                }
+            }
+        }
+
+        self.world_lock_tiles().next()
+    }
+
+    // The tiles the lock at (x, y) claims, in reading order, empty when there
+    // is no lock there. Tiles whose parent index points at the lock are taken
+    // as they are. Otherwise the area is approximated by a breadth first fill
+    // from the lock that stops at bedrock, other locks and tiles another lock
+    // claims through its parent index, and for area locks at the lock's size.
+    // The game's own rules (placement order, which tiles count as empty) aren't
+    // in the world data, so treat the result as a territory map, not as what
+    // the server would enforce.
+    pub fn owned_region(&self, x: u32, y: u32) -> Vec<(u32, u32)> {
+        let lock = match self.get_tile(x, y) {
+            Some(tile) if matches!(tile.tile_type, TileType::Lock { .. }) => tile,
+            _ => return Vec::new(),
+        };
+        let lock_index = (y * self.width + x) as usize;
+
+        let mut claimed: Vec<(u32, u32)> = self
+            .tiles
+            .iter()
+            // 0 means no parent, a lock at index 0 can't be pointed at
+            .filter(|tile| tile.parent_block_index != 0 && tile.parent_block_index as usize == lock_index)
+            .map(|tile| (tile.x, tile.y))
+            .collect();
+        if !claimed.is_empty() {
+            if !claimed.contains(&(x, y)) {
+                claimed.push((x, y));
+                claimed.sort_by_key(|&(x, y)| (y, x));
+            }
+            return claimed;
+        }
+
+        let limit = AREA_LOCK_IDS
+            .iter()
+            .position(|id| *id == lock.foreground_item_id)
+            .map_or(self.tiles.len(), |index| AREA_LOCK_SIZES[index]);
+        let blocked = |tile: &Tile| {
+            tile.foreground_item_id == BEDROCK_ID
+                || matches!(tile.tile_type, TileType::Lock { .. })
+                || (tile.parent_block_index != 0 && tile.parent_block_index as usize != lock_index)
+        };
+
+        let mut seen = HashSet::from([(x, y)]);
+        let mut queue = VecDeque::from([(x, y)]);
+        let mut region = Vec::new();
+        while let Some((x, y)) = queue.pop_front() {
+            if region.len() == limit {
+                break;
+            }
+            region.push((x, y));
+            let neighbours = [
+                (x.checked_sub(1), Some(y)),
+                (Some(x + 1), Some(y)),
+                (Some(x), y.checked_sub(1)),
+                (Some(x), Some(y + 1)),
+            ];
+            for (nx, ny) in neighbours {
+                let (Some(nx), Some(ny)) = (nx, ny) else {
+                    continue;
+                };
+                match self.get_tile(nx, ny) {
+                    Some(tile) if !blocked(tile) && seen.insert((nx, ny)) => queue.push_back((nx, ny)),
+                    _ => {}
+                }
+            }
+        }
+        region.sort_by_key(|&(x, y)| (y, x));
+        region
+    }
+
+    // Owner uid of the lock whose area (x, y) falls in, see owned_region. Area
+    // locks win over the world lock; None when no lock claims the tile.
+    pub fn owner_of(&self, x: u32, y: u32) -> Option<u32> {
+        let owner = |lock: &Tile| match lock.tile_type {
+            TileType::Lock { owner_uid, .. } => Some(owner_uid),
+            _ => None,
+        };
+        let tile = self.get_tile(x, y)?;
+        if let Some(lock) = self.governing_lock(tile) {
+            if AREA_LOCK_IDS.contains(&lock.foreground_item_id) {
+                return owner(lock);
+            }
+        }
+        for lock in self
+            .tiles
+            .iter()
+            .filter(|tile| AREA_LOCK_IDS.contains(&tile.foreground_item_id))
+        {
+            if self.owned_region(lock.x, lock.y).contains(&(x, y)) {
+                return owner(lock);
+            }
+        }
+        let world_lock = self.world_lock_tiles().next()?;
+        if self.owned_region(world_lock.x, world_lock.y).contains(&(x, y)) {
+            return owner(world_lock);
+        }
+        None
+    }
+
+    pub fn can_enter(&self, x: u32, y: u32, actor: &ActorInfo) -> AccessDecision {
+        let tile = match self.get_tile(x, y) {
+            Some(tile) => tile,
+            None => return AccessDecision::NotAnEntrance,
+        };
+
+        if !matches!(
+            tile.tile_type,
+            TileType::Door { .. } | TileType::VipEntrance { .. } | TileType::FriendsEntrance { .. }
+        ) {
+            return AccessDecision::NotAnEntrance;
+        }
+
+        if let Some(lock) = self.governing_lock(tile) {
+            if let TileType::Lock { minimum_level, .. } = lock.tile_type {
+                if actor.level < minimum_level && !has_lock_access(lock, actor.user_id) {
+                    return AccessDecision::Denied(DenyReason::LevelTooLow {
+                        required: minimum_level,
+                    });
+                }
+            }
+        }
+
+        match &tile.tile_type {
+            TileType::VipEntrance {
+                owner_uid,
+                access_uids,
+                ..
+            } => {
+                if tile.flags.is_open_to_public
+                    || *owner_uid == actor.user_id
+                    || access_uids.contains(&actor.user_id)
+                {
+                    AccessDecision::Allowed
+                } else {
+                    AccessDecision::Denied(DenyReason::NotOnAccessList)
+                }
+            }
+            TileType::FriendsEntrance { owner_user_id, .. } => {
+                if tile.flags.is_open_to_public
+                    || *owner_user_id == actor.user_id
+                    || actor.friends_with_owner
+                {
+                    AccessDecision::Allowed
+                } else {
+                    AccessDecision::Denied(DenyReason::NotFriends)
+                }
+            }
+            _ => AccessDecision::Allowed,
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_entrance_world(entrance: TileType, entrance_flags: u16, lock: Option<(u16, TileType)>) -> World {
+    use crate::flags::TileFlags;
+
+    let item_database = crate::test_item_database();
+    let mut world = World::new(item_database.clone());
+    world.width = 3;
+    world.height = 1;
+    world.tile_count = 3;
+
+    let mut tile = Tile::new(0, 0, 0, TileFlags::from_u16(0), 0, 0, 0, item_database.clone());
+    if let Some((lock_id, lock_type)) = lock {
+        tile.foreground_item_id = lock_id;
+        tile.tile_type = lock_type;
+    }
+    world.tiles.push(tile);
+
+    let mut tile = Tile::new(
+        3798,
+        0,
+        0,
+        TileFlags::from_u16(entrance_flags),
+        entrance_flags,
+        1,
+        0,
+        item_database.clone(),
+    );
+    tile.tile_type = entrance;
+    world.tiles.push(tile);
+    world
+        .tiles
+        .push(Tile::new(2, 0, 0, TileFlags::from_u16(0), 0, 2, 0, item_database));
+    world
+}
+
+#[cfg(test)]
+fn test_lock(public: bool, minimum_level: u8) -> TileType {
+    TileType::Lock {
+        settings: LockSettings {
+            is_open_to_public: public,
+            ..Default::default()
+        },
+        owner_uid: 1,
+        access_count: 1,
+        access_uids: vec![2],
+        minimum_level,
+        unknown_1: [0; 7],
+    }
+}
+
+#[test]
+fn test_can_enter_entrances() {
+    let vip = TileType::VipEntrance {
+        unknown_1: 0,
+        owner_uid: 10,
+        access_uids: vec![11],
+    };
+    let friends = TileType::FriendsEntrance {
+        owner_user_id: 10,
+        unknown_1: 0,
+        unknown_2: 0,
+    };
+    let door = TileType::Door {
+        text: String::new(),
+        unknown_1: 0,
+    };
+    let actor = |user_id, friends_with_owner| ActorInfo {
+        user_id,
+        level: 1,
+        friends_with_owner,
+    };
+
+    let cases = [
+        (vip.clone(), 0, actor(10, false), AccessDecision::Allowed),
+        (vip.clone(), 0, actor(11, false), AccessDecision::Allowed),
+        (vip.clone(), 0, actor(12, true), AccessDecision::Denied(DenyReason::NotOnAccessList)),
+        (vip, 0x80, actor(12, false), AccessDecision::Allowed),
+        (friends.clone(), 0, actor(10, false), AccessDecision::Allowed),
+        (friends.clone(), 0, actor(12, true), AccessDecision::Allowed),
+        (friends.clone(), 0, actor(12, false), AccessDecision::Denied(DenyReason::NotFriends)),
+        (friends, 0x80, actor(12, false), AccessDecision::Allowed),
+        (door, 0, actor(12, false), AccessDecision::Allowed),
+    ];
+    for (entrance, flags, actor, expected) in cases {
+        let world = test_entrance_world(entrance, flags, None);
+        assert_eq!(world.can_enter(1, 0, &actor), expected);
+    }
+
+    let world = test_entrance_world(TileType::Basic, 0, None);
+    assert_eq!(world.can_enter(1, 0, &actor(10, false)), AccessDecision::NotAnEntrance);
+    assert_eq!(world.can_enter(2, 0, &actor(10, false)), AccessDecision::NotAnEntrance);
+    assert_eq!(world.can_enter(5, 0, &actor(10, false)), AccessDecision::NotAnEntrance);
+}
+
+#[test]
+fn test_can_enter_minimum_level() {
+    let door = TileType::Door {
+        text: String::new(),
+        unknown_1: 0,
+    };
+    let actor = |user_id, level| ActorInfo {
+        user_id,
+        level,
+        friends_with_owner: false,
+    };
+    let too_low = AccessDecision::Denied(DenyReason::LevelTooLow { required: 20 });
+
+    let world = test_entrance_world(door.clone(), 0, Some((242, test_lock(false, 20))));
+    assert_eq!(world.can_enter(1, 0, &actor(5, 19)), too_low);
+    assert_eq!(world.can_enter(1, 0, &actor(5, 20)), AccessDecision::Allowed);
+    assert_eq!(world.can_enter(1, 0, &actor(1, 1)), AccessDecision::Allowed);
+    assert_eq!(world.can_enter(1, 0, &actor(2, 1)), AccessDecision::Allowed);
+
+    let world = test_entrance_world(door.clone(), 0, Some((242, test_lock(true, 20))));
+    assert_eq!(world.can_enter(1, 0, &actor(5, 1)), AccessDecision::Allowed);
+
+    // an area lock that doesn't own the door has no say
+    let world = test_entrance_world(door.clone(), 0, Some((202, test_lock(false, 20))));
+    assert_eq!(world.can_enter(1, 0, &actor(5, 1)), AccessDecision::Allowed);
+
+    // parent index 0 means "no parent", so move the area lock to the right of the door
+    let mut world = test_entrance_world(door, 0, Some((202, test_lock(false, 20))));
+    world.tiles.swap(0, 2);
+    world.tiles[0].x = 0;
+    world.tiles[2].x = 2;
+    assert_eq!(world.can_enter(1, 0, &actor(5, 1)), AccessDecision::Allowed);
+    world.tiles[1].parent_block_index = 2;
+    assert_eq!(world.can_enter(1, 0, &actor(5, 1)), too_low);
+
+    let vip = TileType::VipEntrance {
+        unknown_1: 0,
+        owner_uid: 10,
+        access_uids: vec![5],
+    };
+    let world = test_entrance_world(vip, 0, Some((242, test_lock(false, 20))));
+    assert_eq!(world.can_enter(1, 0, &actor(5, 1)), too_low);
+    assert_eq!(world.can_enter(1, 0, &actor(5, 30)), AccessDecision::Allowed);
+}
+
+#[test]
+fn test_world_lock() {
+    let door = TileType::Door {
+        text: String::new(),
+        unknown_1: 0,
+    };
+
+    // unowned, and an area lock doesn't own the world
+    assert_eq!(test_entrance_world(door.clone(), 0, None).world_lock(), None);
+    assert_eq!(test_entrance_world(door.clone(), 0, Some((202, test_lock(false, 20)))).world_lock(), None);
+
+    let mut world = test_entrance_world(door.clone(), 0, Some((242, test_lock(false, 20))));
+    assert_eq!(
+        world.world_lock(),
+        Some(WorldLockInfo {
+            x: 0,
+            y: 0,
+            item_id: 242,
+            owner_uid: 1,
+            is_public: false,
+            minimum_level: 20,
+            access_uids: vec![2],
+        })
+    );
+    assert_eq!(world.world_lock_tiles().count(), 1);
+
+    // a second world lock is kept out of world_lock but still listed
+    world.tiles[2].foreground_item_id = 4802;
+    world.tiles[2].tile_type = test_lock(true, 0);
+    assert_eq!(world.world_lock().unwrap().item_id, 242);
+    assert_eq!(world.world_lock_tiles().count(), 2);
+
+    let world = test_entrance_world(door.clone(), 0, Some((14296, test_lock(true, 0))));
+    assert!(world.world_lock().unwrap().is_public);
+    let mut world = test_entrance_world(door, 0, Some((242, test_lock(false, 0))));
+    world.tiles[0].flags.is_open_to_public = true;
+    assert!(world.world_lock().unwrap().is_public);
+}
+
+#[test]
+fn test_owned_region() {
+    use crate::builder::WorldBuilder;
+
+    // world lock on the left, a bedrock wall at x = 4, a small lock on the right
+    let mut world = WorldBuilder::new().size(8, 3).build(crate::test_item_database());
+    for y in 0..3 {
+        world.get_tile_mut(4, y).unwrap().foreground_item_id = BEDROCK_ID;
+    }
+    let mut place_lock = |x: u32, y: u32, item_id: u16, owner_uid: u32| {
+        let tile = world.get_tile_mut(x, y).unwrap();
+        tile.foreground_item_id = item_id;
+        tile.tile_type = test_lock(false, 0);
+        if let TileType::Lock { owner_uid: owner, .. } = &mut tile.tile_type {
+            *owner = owner_uid;
+        }
+    };
+    place_lock(0, 0, 242, 1);
+    place_lock(6, 1, 202, 2);
+
+    let left: Vec<(u32, u32)> = (0..3).flat_map(|y| (0..4).map(move |x| (x, y))).collect();
+    let right: Vec<(u32, u32)> = (0..3).flat_map(|y| (5..8).map(move |x| (x, y))).collect();
+    assert_eq!(world.owned_region(0, 0), left);
+    assert_eq!(world.owned_region(6, 1), right);
+    assert!(world.owned_region(1, 1).is_empty());
+    assert!(world.owned_region(9, 0).is_empty());
+    assert_eq!(world.owner_of(3, 2), Some(1));
+    assert_eq!(world.owner_of(5, 0), Some(2));
+    assert_eq!(world.owner_of(4, 0), None);
+
+    // without the wall the world lock reaches around the small lock, which
+    // still wins its own tiles, and a small lock stops at 10 tiles
+    let mut open = world.clone();
+    for y in 0..3 {
+        open.get_tile_mut(4, y).unwrap().foreground_item_id = 0;
+    }
+    assert_eq!(open.owned_region(0, 0).len(), 23);
+    assert_eq!(open.owned_region(6, 1).len(), 10);
+    assert_eq!(open.owner_of(7, 1), Some(2));
+    assert_eq!(open.owner_of(3, 1), Some(1));
+
+    // tiles linked to the lock through their parent index are taken as is
+    let lock_index = (world.width + 6) as u16;
+    world.get_tile_mut(1, 2).unwrap().parent_block_index = lock_index;
+    assert_eq!(world.owned_region(6, 1), vec![(6, 1), (1, 2)]);
+    assert_eq!(world.owner_of(1, 2), Some(2));
+    assert!(!world.owned_region(0, 0).contains(&(1, 2)));
+}
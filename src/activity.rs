@@ -0,0 +1,258 @@
+use crate::cancel::CancelToken;
+use crate::tile::{Rect, Tile};
+use crate::world::World;
+use std::borrow::Borrow;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActivityError {
+    NoSnapshots,
+    // snapshot `index` in the series doesn't match the size of the first one
+    DimensionMismatch {
+        index: usize,
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    Cancelled,
+}
+
+impl fmt::Display for ActivityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActivityError::NoSnapshots => write!(f, "no snapshots to analyze"),
+            ActivityError::DimensionMismatch {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "snapshot {} is {}x{}, expected {}x{}",
+                index, found.0, found.1, expected.0, expected.1
+            ),
+            ActivityError::Cancelled => write!(f, "the analysis was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for ActivityError {}
+
+// How often each tile changed between consecutive snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityMap {
+    pub width: u32,
+    pub height: u32,
+    pub snapshots: usize,
+    // row-major, one count per tile
+    pub changes: Vec<u32>,
+}
+
+// Snapshots are compared pairwise by Tile::content_hash, so only the hashes of
+// the previous snapshot are kept; pass an iterator that loads worlds lazily to
+// keep a long series out of memory.
+pub fn analyze_activity<I>(snapshots: I) -> Result<ActivityMap, ActivityError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<World>,
+{
+    analyze_activity_cancellable(snapshots, &CancelToken::new())
+}
+
+// Like analyze_activity, giving up with ActivityError::Cancelled once `cancel`
+// is set. The token is checked before each snapshot is compared.
+pub fn analyze_activity_cancellable<I>(snapshots: I, cancel: &CancelToken) -> Result<ActivityMap, ActivityError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<World>,
+{
+    let mut snapshots = snapshots.into_iter();
+    let first = snapshots.next().ok_or(ActivityError::NoSnapshots)?;
+    let first = first.borrow();
+    let mut previous: Vec<u64> = first.tiles.iter().map(Tile::content_hash).collect();
+    let mut activity = ActivityMap {
+        width: first.width,
+        height: first.height,
+        snapshots: 1,
+        changes: vec![0; previous.len()],
+    };
+
+    for (index, snapshot) in snapshots.enumerate() {
+        if cancel.is_cancelled() {
+            return Err(ActivityError::Cancelled);
+        }
+        let snapshot = snapshot.borrow();
+        if (snapshot.width, snapshot.height) != (activity.width, activity.height)
+            || snapshot.tiles.len() != previous.len()
+        {
+            return Err(ActivityError::DimensionMismatch {
+                index: index + 1,
+                expected: (activity.width, activity.height),
+                found: (snapshot.width, snapshot.height),
+            });
+        }
+
+        for ((tile, hash), count) in snapshot
+            .tiles
+            .iter()
+            .zip(previous.iter_mut())
+            .zip(activity.changes.iter_mut())
+        {
+            let current = tile.content_hash();
+            if current != *hash {
+                *count += 1;
+                *hash = current;
+            }
+        }
+        activity.snapshots += 1;
+    }
+    Ok(activity)
+}
+
+impl ActivityMap {
+    pub fn change_count(&self, x: u32, y: u32) -> Option<u32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.changes.get((y * self.width + x) as usize).copied()
+    }
+
+    // The n positions that changed most as (x, y, changes), busiest first and
+    // in reading order on ties. Tiles that never changed aren't listed.
+    pub fn hotspots(&self, n: usize) -> Vec<(u32, u32, u32)> {
+        let mut changed: Vec<(usize, u32)> = self
+            .changes
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, count)| *count > 0)
+            .collect();
+        changed.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        changed
+            .into_iter()
+            .take(n)
+            .map(|(index, count)| (index as u32 % self.width, index as u32 / self.width, count))
+            .collect()
+    }
+
+    // Share of possible changes that happened inside the rect, from 0.0 (no
+    // tile ever changed) to 1.0 (every tile changed between every pair of
+    // snapshots). Parts of the rect outside the world are ignored, positions
+    // past a short tile vector count as never changed.
+    pub fn region_score(&self, rect: Rect) -> f32 {
+        let x_end = rect.x.saturating_add(rect.width).min(self.width);
+        let y_end = rect.y.saturating_add(rect.height).min(self.height);
+        let transitions = self.snapshots.saturating_sub(1);
+        let mut total = 0u64;
+        let mut tiles = 0u64;
+        for y in rect.y..y_end {
+            for x in rect.x..x_end {
+                let index = y as usize * self.width as usize + x as usize;
+                total += self.changes.get(index).copied().unwrap_or(0) as u64;
+                tiles += 1;
+            }
+        }
+        if tiles == 0 || transitions == 0 {
+            return 0.0;
+        }
+        total as f32 / (tiles * transitions as u64) as f32
+    }
+
+    // Scores for a grid of region_size x region_size blocks, as (block rect,
+    // score) in reading order. Blocks on the right and bottom edges may be
+    // smaller.
+    pub fn region_scores(&self, region_size: u32) -> Vec<(Rect, f32)> {
+        let region_size = region_size.max(1);
+        let mut scores = Vec::new();
+        for y in (0..self.height).step_by(region_size as usize) {
+            for x in (0..self.width).step_by(region_size as usize) {
+                let rect = Rect::new(
+                    x,
+                    y,
+                    region_size.min(self.width - x),
+                    region_size.min(self.height - y),
+                );
+                scores.push((rect, self.region_score(rect)));
+            }
+        }
+        scores
+    }
+
+    // Change counts as a metric for heatmap::render_heatmap.
+    pub fn metric(&self) -> impl Fn(&Tile) -> Option<f32> + '_ {
+        move |tile| self.change_count(tile.x, tile.y).map(|count| count as f32)
+    }
+}
+
+#[test]
+fn test_analyze_activity() {
+    use crate::builder::WorldBuilder;
+
+    // (0, 0) flips every snapshot, (2, 1) changes once, (3, 1) is rewritten
+    // with the same content each time and must not count
+    let base = WorldBuilder::new().size(4, 2).build(crate::test_item_database());
+    let mut snapshots = Vec::new();
+    for step in 0..5u16 {
+        let mut world = base.clone();
+        world.tiles[0].foreground_item_id = if step % 2 == 0 { 2 } else { 0 };
+        if step >= 3 {
+            world.tiles[6].background_item_id = 14;
+        }
+        world.tiles[7].foreground_item_id = 554;
+        snapshots.push(world);
+    }
+
+    let activity = analyze_activity(&snapshots).unwrap();
+    assert_eq!(activity.snapshots, 5);
+    assert_eq!(activity.change_count(0, 0), Some(4));
+    assert_eq!(activity.change_count(2, 1), Some(1));
+    assert_eq!(activity.change_count(3, 1), Some(0));
+    assert_eq!(activity.change_count(4, 0), None);
+    assert_eq!(activity.hotspots(5), vec![(0, 0, 4), (2, 1, 1)]);
+    assert_eq!(activity.hotspots(1), vec![(0, 0, 4)]);
+
+    // 4 changes over 4 tiles and 4 transitions in the left half
+    assert_eq!(activity.region_score(Rect::new(0, 0, 2, 2)), 0.25);
+    assert_eq!(activity.region_score(Rect::new(0, 0, 1, 1)), 1.0);
+    let scores = activity.region_scores(3);
+    assert_eq!(scores.len(), 2);
+    assert_eq!(scores[1].0, Rect::new(3, 0, 1, 2));
+    assert_eq!(scores[1].1, 0.0);
+
+    // owned worlds stream through too
+    let streamed = analyze_activity(snapshots.clone()).unwrap();
+    assert_eq!(streamed, activity);
+
+    let tile = &snapshots[0].tiles[0];
+    assert_eq!(activity.metric()(tile), Some(4.0));
+
+    snapshots.push(WorldBuilder::new().size(4, 3).build(crate::test_item_database()));
+    assert_eq!(
+        analyze_activity(&snapshots),
+        Err(ActivityError::DimensionMismatch {
+            index: 5,
+            expected: (4, 2),
+            found: (4, 3)
+        })
+    );
+    assert_eq!(analyze_activity(Vec::<World>::new()), Err(ActivityError::NoSnapshots));
+
+    // cancelled while the third snapshot loads, it's never compared
+    let cancel = CancelToken::new();
+    let mut compared = 0;
+    let lazy = snapshots.iter().inspect(|_| {
+        compared += 1;
+        if compared == 3 {
+            cancel.cancel();
+        }
+    });
+    assert_eq!(analyze_activity_cancellable(lazy, &cancel), Err(ActivityError::Cancelled));
+    assert_eq!(compared, 3);
+
+    // a tile vector shorter than the world scores the missing tiles as unchanged
+    let mut short = base.clone();
+    short.tiles.truncate(3);
+    let mut changed = short.clone();
+    changed.tiles[0].foreground_item_id = 2;
+    let activity = analyze_activity([short, changed]).unwrap();
+    assert_eq!(activity.change_count(3, 1), None);
+    assert_eq!(activity.region_score(Rect::new(0, 0, 4, 2)), 1.0 / 8.0);
+}
@@ -0,0 +1,124 @@
+use crate::flags::TileFlags;
+use crate::tile::Tile;
+use crate::version;
+use crate::weather::WeatherType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::sync::{Arc, RwLock};
+
+// Builds a world of blank tiles. Tiles are never carried over by
+// World::to_builder, copy or re-add them on the built world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldBuilder {
+    pub version: u16,
+    pub flags: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub base_weather: WeatherType,
+    pub current_weather: WeatherType,
+}
+
+impl Default for WorldBuilder {
+    fn default() -> Self {
+        WorldBuilder {
+            version: version::CURRENT,
+            flags: 0,
+            name: "EXIT".to_string(),
+            width: 0,
+            height: 0,
+            base_weather: WeatherType::Default,
+            current_weather: WeatherType::Default,
+        }
+    }
+}
+
+impl WorldBuilder {
+    pub fn new() -> WorldBuilder {
+        WorldBuilder::default()
+    }
+
+    pub fn version(mut self, version: u16) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn weather(mut self, base_weather: WeatherType, current_weather: WeatherType) -> Self {
+        self.base_weather = base_weather;
+        self.current_weather = current_weather;
+        self
+    }
+
+    pub fn build(self, item_database: Arc<RwLock<ItemDatabase>>) -> World {
+        let mut world = World::new(Arc::clone(&item_database));
+        world.version = self.version;
+        world.flags = self.flags;
+        world.name = self.name;
+        world.width = self.width;
+        world.height = self.height;
+        world.tile_count = self.width * self.height;
+        world.base_weather = self.base_weather;
+        world.current_weather = self.current_weather;
+        for index in 0..world.tile_count {
+            world.tiles.push(Tile::new(
+                0,
+                0,
+                0,
+                TileFlags::default(),
+                0,
+                index % self.width,
+                index / self.width,
+                Arc::clone(&item_database),
+            ));
+        }
+        world
+    }
+}
+
+impl World {
+    pub fn to_builder(&self) -> WorldBuilder {
+        WorldBuilder {
+            version: self.version,
+            flags: self.flags,
+            name: self.name.clone(),
+            width: self.width,
+            height: self.height,
+            base_weather: self.base_weather.clone(),
+            current_weather: self.current_weather.clone(),
+        }
+    }
+}
+
+#[test]
+fn test_to_builder() {
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+
+    let copy = world.to_builder().name("PIWD2").build(Arc::clone(&item_database));
+    assert_eq!(copy.name, "PIWD2");
+    assert_eq!((copy.width, copy.height), (world.width, world.height));
+    assert_eq!(copy.tile_count, world.tile_count);
+    assert_eq!((copy.version, copy.flags), (world.version, world.flags));
+    assert_eq!(copy.base_weather, world.base_weather);
+    assert_eq!(copy.current_weather, world.current_weather);
+    assert!(copy.tiles.iter().all(|tile| tile.foreground_item_id == 0));
+    assert_eq!(copy.get_tile(99, 59).map(|tile| (tile.x, tile.y)), Some((99, 59)));
+    assert_eq!(copy.to_builder().name(&world.name), world.to_builder());
+}
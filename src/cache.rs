@@ -0,0 +1,168 @@
+use crate::world::{normalize_world_name, World};
+
+// Parsed worlds keyed by normalized name (see World::normalized_name), least recently used first out.
+// Holds at most `capacity` worlds and, when a memory limit is set, evicts until
+// the summed World::memory_usage total fits (the world just inserted always stays).
+#[derive(Debug, Clone)]
+pub struct WorldCache {
+    capacity: usize,
+    memory_limit: Option<usize>,
+    // oldest first, the most recently used world is last
+    entries: Vec<(String, World)>,
+}
+
+impl WorldCache {
+    pub fn new(capacity: usize) -> WorldCache {
+        WorldCache {
+            capacity: capacity.max(1),
+            memory_limit: None,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Stores the world under its name, replacing any world with the same name.
+    // Returns everything that was pushed out, the replaced world included.
+    pub fn insert_parsed(&mut self, world: World) -> Vec<World> {
+        let key = world.normalized_name();
+        let mut evicted: Vec<World> = self.evict(&key).into_iter().collect();
+        self.entries.push((key, world));
+
+        while self.entries.len() > self.capacity {
+            evicted.push(self.entries.remove(0).1);
+        }
+        if let Some(limit) = self.memory_limit {
+            while self.entries.len() > 1 && self.memory_usage() > limit {
+                evicted.push(self.entries.remove(0).1);
+            }
+        }
+        evicted
+    }
+
+    // Marks the world as the most recently used.
+    pub fn get(&mut self, name: &str) -> Option<&World> {
+        self.touch(name).map(|(_, world)| &*world)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut World> {
+        self.touch(name).map(|(_, world)| world)
+    }
+
+    // Looks a world up without changing the eviction order.
+    pub fn peek(&self, name: &str) -> Option<&World> {
+        let key = normalize_world_name(name);
+        self.entries
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, world)| world)
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.peek(name).is_some()
+    }
+
+    pub fn evict(&mut self, name: &str) -> Option<World> {
+        let key = normalize_world_name(name);
+        let index = self.entries.iter().position(|(name, _)| *name == key)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    // Normalized names, most recently used first.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().rev().map(|(name, _)| name.as_str())
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        self.entries.iter().map(|(_, world)| world.memory_usage().total).sum()
+    }
+
+    fn touch(&mut self, name: &str) -> Option<&mut (String, World)> {
+        let key = normalize_world_name(name);
+        let index = self.entries.iter().position(|(name, _)| *name == key)?;
+        let entry = self.entries.remove(index);
+        self.entries.push(entry);
+        self.entries.last_mut()
+    }
+}
+
+#[cfg(test)]
+fn test_named_world(name: &str) -> World {
+    crate::builder::WorldBuilder::new()
+        .name(name)
+        .size(10, 10)
+        .build(crate::test_item_database())
+}
+
+#[test]
+fn test_world_cache_lru() {
+    fn assert_send<T: Send + Sync>() {}
+    assert_send::<WorldCache>();
+
+    let mut cache = WorldCache::new(2);
+    assert!(cache.insert_parsed(test_named_world("START")).is_empty());
+    assert!(cache.insert_parsed(test_named_world("Buy")).is_empty());
+    assert_eq!(cache.get("start").map(|world| world.name.as_str()), Some("START"));
+
+    // BUY is now the least recently used
+    let evicted = cache.insert_parsed(test_named_world("wotd"));
+    assert_eq!(evicted.len(), 1);
+    assert_eq!(evicted[0].name, "Buy");
+    assert_eq!(cache.names().collect::<Vec<_>>(), vec!["WOTD", "START"]);
+
+    // peek leaves the order alone, so START goes next
+    assert!(cache.peek("START").is_some());
+    cache.get_mut("WOTD").unwrap().width = 11;
+    let evicted = cache.insert_parsed(test_named_world("Buy"));
+    assert_eq!(evicted[0].name, "START");
+
+    // same name in another case replaces the stored world
+    let evicted = cache.insert_parsed(test_named_world("BUY"));
+    assert_eq!(evicted.len(), 1);
+    assert_eq!(cache.len(), 2);
+    assert_eq!(cache.peek("buy").unwrap().name, "BUY");
+    assert_eq!(cache.peek("Wotd").unwrap().width, 11);
+
+    assert_eq!(cache.evict("wOtD").map(|world| world.width), Some(11));
+    assert!(cache.evict("wotd").is_none());
+    assert!(!cache.contains("wotd"));
+
+    // trailing padding doesn't make a different world
+    cache.insert_parsed(test_named_world("Buy\0"));
+    assert_eq!(cache.len(), 1);
+    assert!(cache.contains("buy "));
+}
+
+#[test]
+fn test_world_cache_memory_limit() {
+    let world_size = test_named_world("A").memory_usage().total;
+    let mut cache = WorldCache::new(10).with_memory_limit(world_size * 5 / 2);
+    for name in ["A", "B", "C", "D"] {
+        cache.insert_parsed(test_named_world(name));
+        assert!(cache.memory_usage() <= world_size * 5 / 2);
+    }
+    assert_eq!(cache.names().collect::<Vec<_>>(), vec!["D", "C"]);
+
+    // a single world over the limit is still kept
+    let mut cache = WorldCache::new(10).with_memory_limit(1);
+    cache.insert_parsed(test_named_world("A"));
+    cache.insert_parsed(test_named_world("B"));
+    assert_eq!(cache.names().collect::<Vec<_>>(), vec!["B"]);
+    let usage = cache.memory_usage();
+    assert!(usage >= world_size * 9 / 10 && usage <= world_size * 11 / 10);
+}
@@ -0,0 +1,55 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+// Shared flag for stopping a long operation from another thread. Clones share
+// the flag. Cancellation is best effort: operations only look at the flag at
+// coarse points (per row, per snapshot, every so many route steps), so one of
+// those units of work can still finish after cancel() is called.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn check(&self) -> Result<(), Cancelled> {
+        if self.is_cancelled() {
+            return Err(Cancelled);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the operation was cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+#[test]
+fn test_cancel_token() {
+    let token = CancelToken::new();
+    let shared = token.clone();
+    assert!(!shared.is_cancelled());
+    assert_eq!(shared.check(), Ok(()));
+    token.cancel();
+    assert!(shared.is_cancelled());
+    assert_eq!(shared.check(), Err(Cancelled));
+}
@@ -0,0 +1,227 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::tile::Tile;
+use std::fmt;
+
+// A decoded CBOR item, see Tile::cbor_value. Decoding is done by ciborium;
+// maps keep their entries in the order they were encoded.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CborValue {
+    // both unsigned and negative integers, CBOR's range fits in i128
+    Integer(i128),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    Map(Vec<(CborValue, CborValue)>),
+    Tag(u64, Box<CborValue>),
+    Bool(bool),
+    // undefined decodes to Null too
+    Null,
+    // half, single and double precision alike
+    Float(f64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CborError {
+    UnexpectedEnd,
+    // malformed CBOR at this offset, e.g. a reserved header or invalid UTF-8
+    Syntax { offset: usize },
+    // well-formed CBOR that doesn't decode to a value, e.g. an unassigned simple value
+    Semantic { offset: Option<usize>, message: String },
+    // arrays, maps and tags nested past MAX_DEPTH
+    TooDeep,
+    // bytes left over after the top level item
+    TrailingBytes { offset: usize },
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CborError::UnexpectedEnd => write!(f, "CBOR data ends inside an item"),
+            CborError::Syntax { offset } => write!(f, "malformed CBOR at offset {}", offset),
+            CborError::Semantic { offset: Some(offset), message } => {
+                write!(f, "undecodable CBOR at offset {}: {}", offset, message)
+            }
+            CborError::Semantic { offset: None, message } => write!(f, "undecodable CBOR: {}", message),
+            CborError::TooDeep => write!(f, "CBOR items nest deeper than {}", MAX_DEPTH),
+            CborError::TrailingBytes { offset } => write!(f, "bytes after the CBOR item at offset {}", offset),
+        }
+    }
+}
+
+impl std::error::Error for CborError {}
+
+impl From<ciborium::de::Error<std::io::Error>> for CborError {
+    fn from(err: ciborium::de::Error<std::io::Error>) -> CborError {
+        match err {
+            // a slice only fails to read when it runs out
+            ciborium::de::Error::Io(_) => CborError::UnexpectedEnd,
+            ciborium::de::Error::Syntax(offset) => CborError::Syntax { offset },
+            ciborium::de::Error::Semantic(offset, message) => CborError::Semantic { offset, message },
+            ciborium::de::Error::RecursionLimitExceeded => CborError::TooDeep,
+        }
+    }
+}
+
+// deeper nesting than any payload the game writes, shallow enough that a
+// forged one can't overflow the stack
+const MAX_DEPTH: usize = 64;
+
+impl CborValue {
+    // Decodes exactly one item spanning all of `data`.
+    pub fn decode(data: &[u8]) -> Result<CborValue, CborError> {
+        let mut reader = data;
+        let value: ciborium::Value = ciborium::de::from_reader_with_recursion_limit(&mut reader, MAX_DEPTH)?;
+        if !reader.is_empty() {
+            return Err(CborError::TrailingBytes {
+                offset: data.len() - reader.len(),
+            });
+        }
+        Ok(CborValue::from(value))
+    }
+
+    // The value of a map's entry with this text key, None for anything that
+    // isn't a map or has no such key.
+    pub fn get(&self, key: &str) -> Option<&CborValue> {
+        match self {
+            CborValue::Map(entries) => entries
+                .iter()
+                .find(|(entry_key, _)| matches!(entry_key, CborValue::Text(text) if text == key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            CborValue::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_text(&self) -> Option<&str> {
+        match self {
+            CborValue::Text(text) => Some(text),
+            _ => None,
+        }
+    }
+}
+
+// CborValue is ciborium's Value with plain integers, kept as this crate's
+// own type so its shape and serde support don't follow ciborium releases.
+impl From<ciborium::Value> for CborValue {
+    fn from(value: ciborium::Value) -> CborValue {
+        match value {
+            ciborium::Value::Integer(value) => CborValue::Integer(value.into()),
+            ciborium::Value::Bytes(bytes) => CborValue::Bytes(bytes),
+            ciborium::Value::Float(value) => CborValue::Float(value),
+            ciborium::Value::Text(text) => CborValue::Text(text),
+            ciborium::Value::Bool(value) => CborValue::Bool(value),
+            ciborium::Value::Null => CborValue::Null,
+            ciborium::Value::Tag(tag, value) => CborValue::Tag(tag, Box::new(CborValue::from(*value))),
+            ciborium::Value::Array(items) => CborValue::Array(items.into_iter().map(CborValue::from).collect()),
+            ciborium::Value::Map(entries) => CborValue::Map(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (CborValue::from(key), CborValue::from(value)))
+                    .collect(),
+            ),
+            // Value is non_exhaustive; every variant ciborium 0.2 decodes is above
+            _ => CborValue::Null,
+        }
+    }
+}
+
+impl Tile {
+    // The tile's CBOR payload decoded, None when it has none. Decoded on each
+    // call, cbor_data stays the source of truth and is what gets written back.
+    pub fn cbor_value(&self) -> Option<Result<CborValue, CborError>> {
+        self.cbor_data.as_deref().map(CborValue::decode)
+    }
+}
+
+#[test]
+fn test_cbor_value() {
+    use crate::world::World;
+
+    // examples from RFC 8949 appendix A
+    let hex = |hex: &str| -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|at| u8::from_str_radix(&hex[at..at + 2], 16).unwrap())
+            .collect()
+    };
+    let decode = |text: &str| CborValue::decode(&hex(text));
+    assert_eq!(decode("1903e8"), Ok(CborValue::Integer(1000)));
+    assert_eq!(
+        decode("3bffffffffffffffff"),
+        Ok(CborValue::Integer(-18446744073709551616))
+    );
+    assert_eq!(decode("f93c00"), Ok(CborValue::Float(1.0)));
+    assert_eq!(decode("f90001"), Ok(CborValue::Float(5.960464477539063e-8)));
+    assert_eq!(decode("fb3ff199999999999a"), Ok(CborValue::Float(1.1)));
+    assert_eq!(decode("f5"), Ok(CborValue::Bool(true)));
+    assert_eq!(decode("6449455446"), Ok(CborValue::Text("IETF".to_string())));
+    assert_eq!(decode("5f42010243030405ff"), Ok(CborValue::Bytes(vec![1, 2, 3, 4, 5])));
+    assert_eq!(
+        decode("9f018202039f0405ffff"),
+        Ok(CborValue::Array(vec![
+            CborValue::Integer(1),
+            CborValue::Array(vec![CborValue::Integer(2), CborValue::Integer(3)]),
+            CborValue::Array(vec![CborValue::Integer(4), CborValue::Integer(5)]),
+        ]))
+    );
+    assert_eq!(
+        decode("c074323031332d30332d32315432303a30343a30305a"),
+        Ok(CborValue::Tag(
+            0,
+            Box::new(CborValue::Text("2013-03-21T20:04:00Z".to_string()))
+        ))
+    );
+
+    // malformed input fails without panicking or allocating the claimed length
+    assert_eq!(decode("9b00ffffffffffffff"), Err(CborError::UnexpectedEnd));
+    assert_eq!(decode("1c"), Err(CborError::Syntax { offset: 0 }));
+    assert_eq!(decode("62c328"), Err(CborError::Syntax { offset: 0 }));
+    assert_eq!(decode("0101"), Err(CborError::TrailingBytes { offset: 1 }));
+    assert_eq!(decode(&"81".repeat(100)), Err(CborError::TooDeep));
+    assert_eq!(decode("5f01ff"), Err(CborError::Syntax { offset: 1 }));
+
+    // the payload of the tile at (69, 47) in world.dat
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let tile = world.get_tile(69, 47).unwrap();
+    let value = tile.cbor_value().unwrap().unwrap();
+    assert_eq!(value.get("selectedIllness").and_then(CborValue::as_integer), Some(20));
+    assert_eq!(value.get("wlCount"), Some(&CborValue::Integer(0)));
+    assert_eq!(value.get("missing"), None);
+    assert!(world.get_tile(0, 0).unwrap().cbor_value().is_none());
+
+    // a Party Projector, which the test items.dat is too old to know
+    let mut tiles = Vec::new();
+    for value in [15376u16, 0, 0, 0] {
+        tiles.extend(value.to_le_bytes());
+    }
+    let payload = hex("a26474797065016573706565640a");
+    tiles.extend((payload.len() as u32).to_le_bytes());
+    tiles.extend(&payload);
+    world.options.unknown_items = crate::options::UnknownItemPolicy::Placeholder;
+    world
+        .parse(&crate::test_world_bytes(crate::version::CURRENT, 1, 1, &tiles))
+        .unwrap();
+    let value = world.get_tile(0, 0).unwrap().cbor_value().unwrap().unwrap();
+    assert_eq!(
+        value,
+        CborValue::Map(vec![
+            (CborValue::Text("type".to_string()), CborValue::Integer(1)),
+            (CborValue::Text("speed".to_string()), CborValue::Integer(10)),
+        ])
+    );
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&value).unwrap();
+        assert_eq!(serde_json::from_value::<CborValue>(json).unwrap(), value);
+    }
+}
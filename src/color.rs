@@ -0,0 +1,32 @@
+use gtitem_r::structs::ItemDatabase;
+
+// Minimap colors, shared by the renderer and the viewer model so both show a
+// tile the same way.
+
+pub(crate) const SKY: [u8; 4] = [96, 215, 242, 255];
+pub(crate) const UNKNOWN_ITEM: [u8; 4] = [255, 0, 255, 255];
+
+// The item's own base_color is unused, its seed (id + 1) carries the color shown on the minimap.
+pub(crate) fn item_rgba(item_database: &ItemDatabase, item_id: u16) -> [u8; 4] {
+    match item_database.get_item(&(item_id as u32 + 1)) {
+        Some(item) => {
+            let colors = item.base_color;
+            let r = ((colors >> 24) & 0xFF) as u8;
+            let g = ((colors >> 16) & 0xFF) as u8;
+            let b = ((colors >> 8) & 0xFF) as u8;
+            [b, g, r, 255]
+        }
+        None => UNKNOWN_ITEM,
+    }
+}
+
+// Tiles with nothing on them show the sky.
+pub(crate) fn tile_rgba(item_database: &ItemDatabase, foreground: u16, background: u16) -> [u8; 4] {
+    if foreground != 0 {
+        item_rgba(item_database, foreground)
+    } else if background != 0 {
+        item_rgba(item_database, background)
+    } else {
+        SKY
+    }
+}
@@ -0,0 +1,406 @@
+use crate::tile_extra::TileType;
+use crate::world::{fnv1a, World, FNV_OFFSET_BASIS};
+use gtitem_r::structs::ItemDatabase;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+// Compatibility digests: a canonical, line per field dump of what the parser
+// decodes from a world, committed as a baseline so a change to decoded output
+// shows up as a field level diff in review. A digest file looks like
+//
+//   # gtworld-r compat digest 2
+//   version = 23
+//   tiles[49,44].tile_type = {"Door":{"text":"EXIT","unknown_1":0}}
+//
+// Structured values are written as single line JSON, so the digest doesn't
+// follow Debug output. Blank tiles are left out, and so is every tile field
+// still at its blank value, which keeps the digest of a 6000 tile world
+// reviewable.
+const DIGEST_HEADER: &str = "# gtworld-r compat digest 2";
+const DIGEST_EXTENSION: &str = "digest";
+
+// Set it to rewrite the committed baseline instead of checking against it:
+// GTWORLD_UPDATE_COMPAT=1 cargo test --features compat-test compat
+pub const UPDATE_ENV: &str = "GTWORLD_UPDATE_COMPAT";
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Digest {
+    // key and value per field, in the order the world holds them
+    pub fields: Vec<(String, String)>,
+}
+
+impl Digest {
+    fn push(&mut self, key: String, value: impl fmt::Display) {
+        self.fields.push((key, value.to_string()));
+    }
+
+    fn push_json(&mut self, key: String, value: &impl Serialize) {
+        // every digested type serializes to plain JSON, which can't fail
+        self.fields.push((key, serde_json::to_string(value).unwrap()));
+    }
+
+    pub fn parse(text: &str) -> Result<Digest, io::Error> {
+        let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+        let mut lines = text.lines();
+        if lines.next() != Some(DIGEST_HEADER) {
+            return Err(invalid("not a compat digest".to_string()));
+        }
+        let mut digest = Digest::default();
+        for line in lines.filter(|line| !line.is_empty()) {
+            let (key, value) = line
+                .split_once(" = ")
+                .ok_or_else(|| invalid(format!("malformed digest line: {}", line)))?;
+            digest.push(key.to_string(), value);
+        }
+        Ok(digest)
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", DIGEST_HEADER)?;
+        for (key, value) in &self.fields {
+            writeln!(f, "{} = {}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn digest_world(world: &World) -> Digest {
+    let mut digest = Digest::default();
+    digest.push("version".to_string(), world.version);
+    digest.push("flags".to_string(), format!("{:#010x}", world.flags));
+    digest.push_json("name".to_string(), &world.name);
+    digest.push("width".to_string(), world.width);
+    digest.push("height".to_string(), world.height);
+    digest.push("tile_count".to_string(), world.tile_count);
+
+    for tile in &world.tiles {
+        let key = |field: &str| format!("tiles[{},{}].{}", tile.x, tile.y, field);
+        if tile.foreground_item_id != 0 {
+            digest.push(key("foreground_item_id"), tile.foreground_item_id);
+        }
+        if tile.background_item_id != 0 {
+            digest.push(key("background_item_id"), tile.background_item_id);
+        }
+        if tile.parent_block_index != 0 {
+            digest.push(key("parent_block_index"), tile.parent_block_index);
+        }
+        if tile.flags_number != 0 {
+            digest.push(key("flags"), format!("{:#06x}", tile.flags_number));
+        }
+        if tile.tile_type != TileType::Basic {
+            digest.push_json(key("tile_type"), &tile.tile_type);
+        }
+        if let Some(cbor_data) = &tile.cbor_data {
+            let hash = fnv1a(FNV_OFFSET_BASIS, cbor_data);
+            digest.push(
+                key("cbor_data"),
+                format!("{} bytes, fnv {:016x}", cbor_data.len(), hash),
+            );
+        }
+    }
+
+    digest.push("dropped.items_count".to_string(), world.dropped.items_count);
+    digest.push(
+        "dropped.last_dropped_item_uid".to_string(),
+        world.dropped.last_dropped_item_uid,
+    );
+    for (index, item) in world.dropped.items.iter().enumerate() {
+        let key = |field: &str| format!("dropped.items[{}].{}", index, field);
+        digest.push(key("id"), item.id);
+        digest.push(key("position"), format!("{}, {}", item.x, item.y));
+        digest.push(key("count"), item.count);
+        digest.push(key("flags"), item.flags);
+        digest.push(key("uid"), item.uid);
+    }
+    digest.push_json("base_weather".to_string(), &world.base_weather);
+    digest.push_json("current_weather".to_string(), &world.current_weather);
+    for (index, warning) in world.warnings.iter().enumerate() {
+        digest.push_json(format!("warnings[{}]", index), warning);
+    }
+    for (index, loss) in world.losses.iter().enumerate() {
+        digest.push_json(format!("losses[{}]", index), loss);
+    }
+    digest
+}
+
+// A file that fails to parse still gets a digest, holding the error, so a
+// parser change that starts or stops rejecting a fixture is reported too.
+pub fn digest_file<P: AsRef<Path>>(path: P, item_database: Arc<RwLock<ItemDatabase>>) -> io::Result<Digest> {
+    let data = fs::read(path)?;
+    let mut world = World::new(item_database);
+    match world.parse(&data) {
+        Ok(()) => Ok(digest_world(&world)),
+        Err(err) => {
+            let mut digest = Digest::default();
+            digest.push("error".to_string(), err);
+            Ok(digest)
+        }
+    }
+}
+
+// Every file in `dir` is taken as a fixture world, keyed by its file name.
+pub fn digest_dir<P: AsRef<Path>>(
+    dir: P,
+    item_database: Arc<RwLock<ItemDatabase>>,
+) -> io::Result<BTreeMap<String, Digest>> {
+    let mut digests = BTreeMap::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_file() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            digests.insert(name, digest_file(entry.path(), Arc::clone(&item_database))?);
+        }
+    }
+    Ok(digests)
+}
+
+// Replaces the baseline in `dir`: one `<fixture>.digest` file per digest, and
+// digests of fixtures that are gone are removed.
+pub fn write_baseline<P: AsRef<Path>>(dir: P, digests: &BTreeMap<String, Digest>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir)?;
+    for name in read_baseline(dir)?.keys() {
+        if !digests.contains_key(name) {
+            fs::remove_file(dir.join(format!("{}.{}", name, DIGEST_EXTENSION)))?;
+        }
+    }
+    for (name, digest) in digests {
+        fs::write(dir.join(format!("{}.{}", name, DIGEST_EXTENSION)), digest.to_string())?;
+    }
+    Ok(())
+}
+
+// A missing directory is an empty baseline.
+pub fn read_baseline<P: AsRef<Path>>(dir: P) -> io::Result<BTreeMap<String, Digest>> {
+    let mut digests = BTreeMap::new();
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(digests),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|extension| extension == DIGEST_EXTENSION) {
+            let name = path.file_stem().unwrap().to_string_lossy().to_string();
+            digests.insert(name, Digest::parse(&fs::read_to_string(&path)?)?);
+        }
+    }
+    Ok(digests)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub key: String,
+    // None when the field is only on the other side
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileStatus {
+    Changed,
+    // a fixture with no baseline yet
+    Added,
+    // a baseline whose fixture is gone
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChanges {
+    pub name: String,
+    pub status: FileStatus,
+    // empty for added and removed files
+    pub changes: Vec<FieldChange>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompatReport {
+    pub files: Vec<FileChanges>,
+}
+
+impl CompatReport {
+    pub fn is_clean(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for file in &self.files {
+            match file.status {
+                FileStatus::Added => writeln!(f, "{}: no baseline", file.name)?,
+                FileStatus::Removed => writeln!(f, "{}: fixture removed", file.name)?,
+                FileStatus::Changed => {
+                    writeln!(f, "{}: {} fields changed", file.name, file.changes.len())?;
+                    for change in &file.changes {
+                        let side = |value: &Option<String>| value.clone().unwrap_or_else(|| "(absent)".to_string());
+                        writeln!(
+                            f,
+                            "  {}: {} -> {}",
+                            change.key,
+                            side(&change.before),
+                            side(&change.after)
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// Field by field differences from `baseline` to `current`. Changed fields are
+// listed in the current digest's order, then fields only the baseline has.
+pub fn compare(baseline: &BTreeMap<String, Digest>, current: &BTreeMap<String, Digest>) -> CompatReport {
+    let mut report = CompatReport::default();
+    for (name, digest) in current {
+        let Some(base) = baseline.get(name) else {
+            report.files.push(FileChanges {
+                name: name.clone(),
+                status: FileStatus::Added,
+                changes: Vec::new(),
+            });
+            continue;
+        };
+        let before: BTreeMap<&str, &str> = base.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let after: BTreeMap<&str, &str> = digest.fields.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+        let mut changes = Vec::new();
+        for (key, value) in &digest.fields {
+            match before.get(key.as_str()) {
+                Some(old) if old == value => {}
+                old => changes.push(FieldChange {
+                    key: key.clone(),
+                    before: old.map(|old| old.to_string()),
+                    after: Some(value.clone()),
+                }),
+            }
+        }
+        for (key, value) in &base.fields {
+            if !after.contains_key(key.as_str()) {
+                changes.push(FieldChange {
+                    key: key.clone(),
+                    before: Some(value.clone()),
+                    after: None,
+                });
+            }
+        }
+        if !changes.is_empty() {
+            report.files.push(FileChanges {
+                name: name.clone(),
+                status: FileStatus::Changed,
+                changes,
+            });
+        }
+    }
+    for name in baseline.keys().filter(|name| !current.contains_key(*name)) {
+        report.files.push(FileChanges {
+            name: name.clone(),
+            status: FileStatus::Removed,
+            changes: Vec::new(),
+        });
+    }
+    report
+}
+
+#[cfg(test)]
+const BASELINE_DIR: &str = "compat";
+// the root world.dat fixture is digested too, it isn't copied in here
+#[cfg(test)]
+const FIXTURES_DIR: &str = "compat/fixtures";
+
+// Every fixture must decode exactly as the committed baseline says. This is
+// only built with the compat-test feature, plain cargo test skips it; run
+// cargo test --features compat-test compat after a parser change.
+#[test]
+fn test_compat_baseline() {
+    let item_database = crate::test_item_database();
+    let mut current = digest_dir(FIXTURES_DIR, Arc::clone(&item_database)).unwrap();
+    current.insert("world.dat".to_string(), digest_file("world.dat", item_database).unwrap());
+    if std::env::var_os(UPDATE_ENV).is_some() {
+        write_baseline(BASELINE_DIR, &current).unwrap();
+    }
+    let report = compare(&read_baseline(BASELINE_DIR).unwrap(), &current);
+    assert!(
+        report.is_clean(),
+        "decoded output changed, rerun with {}=1 to accept it:\n{}",
+        UPDATE_ENV,
+        report
+    );
+}
+
+#[test]
+fn test_compare_digests() {
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    world.parse(&fs::read("world.dat").unwrap()).unwrap();
+    let digest = digest_world(&world);
+    assert_eq!(Digest::parse(&digest.to_string()).unwrap(), digest);
+    assert!(Digest::parse("version = 23").is_err());
+
+    world.get_tile_mut(49, 44).unwrap().tile_type = TileType::Door {
+        text: "HOME".to_string(),
+        unknown_1: 0,
+    };
+    world.get_tile_mut(0, 0).unwrap().foreground_item_id = 2;
+    world.dropped.items.pop();
+    let baseline = BTreeMap::from([
+        ("a.dat".to_string(), digest),
+        ("gone.dat".to_string(), Digest::default()),
+    ]);
+    let current = BTreeMap::from([
+        ("a.dat".to_string(), digest_world(&world)),
+        ("new.dat".to_string(), Digest::default()),
+    ]);
+    let report = compare(&baseline, &current);
+    assert!(!report.is_clean());
+    let statuses: Vec<_> = report
+        .files
+        .iter()
+        .map(|file| (file.name.as_str(), file.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("a.dat", FileStatus::Changed),
+            ("new.dat", FileStatus::Added),
+            ("gone.dat", FileStatus::Removed)
+        ]
+    );
+    let changes = &report.files[0].changes;
+    assert_eq!(
+        changes[0],
+        FieldChange {
+            key: "tiles[0,0].foreground_item_id".to_string(),
+            before: None,
+            after: Some("2".to_string()),
+        }
+    );
+    assert_eq!(changes[1].key, "tiles[49,44].tile_type");
+    assert!(changes
+        .iter()
+        .any(|change| change.key.starts_with("dropped.items[") && change.after.is_none()));
+    let text = report.to_string();
+    assert!(text.contains("a.dat: "));
+    let door_change = r#"  tiles[49,44].tile_type: {"Door":{"text":"EXIT","unknown_1":0}} -> {"Door":{"text":"HOME""#;
+    assert!(text.contains(door_change));
+    assert!(compare(&current, &current).is_clean());
+
+    // a fixture that doesn't parse digests to its error
+    let dir = std::env::temp_dir().join(format!("gtworld-compat-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("broken.dat"), [0; 4]).unwrap();
+    let digests = digest_dir(&dir, item_database).unwrap();
+    assert_eq!(digests["broken.dat"].fields[0].0, "error");
+    write_baseline(dir.join("baseline"), &digests).unwrap();
+    assert_eq!(read_baseline(dir.join("baseline")).unwrap(), digests);
+    write_baseline(dir.join("baseline"), &BTreeMap::new()).unwrap();
+    assert!(read_baseline(dir.join("baseline")).unwrap().is_empty());
+    fs::remove_dir_all(&dir).unwrap();
+}
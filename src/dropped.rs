@@ -0,0 +1,139 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::world::World;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Dropped {
+    pub items_count: u32,
+    pub last_dropped_item_uid: u32,
+    pub items: Vec<DroppedItem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DroppedItem {
+    pub id: u16,
+    pub x: f32,
+    pub y: f32,
+    pub count: u8,
+    pub flags: u8,
+    pub uid: u32,
+}
+
+// Captures can hold several items under one uid, from server bugs or a
+// capture taken mid-update. They're all kept; lookups by uid see the last one.
+impl Dropped {
+    // The last item with `uid`, the one a later drop under the same uid
+    // replaced.
+    pub fn by_uid(&self, uid: u32) -> Option<&DroppedItem> {
+        self.items.iter().rev().find(|item| item.uid == uid)
+    }
+
+    // Uids held by more than one item, in the order their second item appears.
+    pub fn duplicates(&self) -> Vec<u32> {
+        let mut seen = HashSet::new();
+        let mut duplicates = Vec::new();
+        for item in &self.items {
+            if !seen.insert(item.uid) && !duplicates.contains(&item.uid) {
+                duplicates.push(item.uid);
+            }
+        }
+        duplicates
+    }
+
+    // A uid no item has, after last_dropped_item_uid and every uid in use.
+    pub fn next_uid(&self) -> u32 {
+        let max = self.items.iter().map(|item| item.uid).max().unwrap_or(0);
+        self.last_dropped_item_uid.max(max).wrapping_add(1)
+    }
+
+    // Drops an item under a fresh uid and returns the uid. The counters are
+    // kept in step.
+    pub fn add(&mut self, id: u16, x: f32, y: f32, count: u8, flags: u8) -> u32 {
+        let mut uid = self.next_uid();
+        // only reachable once the uids wrapped around
+        while uid == 0 || self.items.iter().any(|item| item.uid == uid) {
+            uid = uid.wrapping_add(1);
+        }
+        self.items.push(DroppedItem {
+            id,
+            x,
+            y,
+            count,
+            flags,
+            uid,
+        });
+        self.items_count = self.items.len() as u32;
+        self.last_dropped_item_uid = uid;
+        uid
+    }
+}
+
+impl World {
+    pub fn dropped_of_item(&self, item_id: u16) -> impl Iterator<Item = &DroppedItem> {
+        self.dropped.items.iter().filter(move |item| item.id == item_id)
+    }
+}
+
+#[test]
+fn test_dropped_of_item() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let first = world.dropped.items[0].id;
+    let expected = world.dropped.items.iter().filter(|item| item.id == first).count();
+
+    assert!(expected > 0);
+    assert_eq!(world.dropped_of_item(first).count(), expected);
+    assert!(world.dropped_of_item(first).all(|item| item.id == first));
+    assert_eq!(world.dropped_of_item(15376).count(), 0);
+}
+
+#[test]
+fn test_duplicate_dropped_uids() {
+    use crate::builder::WorldBuilder;
+    use crate::error::ParseWarning;
+
+    let item = |id: u16, x: f32, uid: u32| DroppedItem {
+        id,
+        x,
+        y: 16.0,
+        count: 1,
+        flags: 0,
+        uid,
+    };
+    let mut world = WorldBuilder::new().size(4, 2).build(crate::test_item_database());
+    world.dropped.items = vec![item(2, 8.0, 7), item(10, 40.0, 3), item(14, 72.0, 7)];
+    world.dropped.items_count = 3;
+    world.dropped.last_dropped_item_uid = 7;
+    let data = world.serialize().unwrap();
+
+    let mut parsed = World::new(crate::test_item_database());
+    parsed.parse(&data).unwrap();
+    assert_eq!(parsed.warnings, vec![ParseWarning::DuplicateDroppedUids { uids: vec![7] }]);
+    assert_eq!(parsed.dropped.items.len(), 3);
+    assert_eq!(parsed.dropped.duplicates(), vec![7]);
+    assert_eq!(parsed.dropped.by_uid(7).unwrap().id, 14);
+    assert_eq!(parsed.dropped.by_uid(3).unwrap().id, 10);
+    assert_eq!(parsed.dropped.by_uid(4), None);
+    assert_eq!(parsed.serialize().unwrap(), data);
+
+    // fresh uids skip every uid in use, not just last_dropped_item_uid
+    parsed.dropped.last_dropped_item_uid = 1;
+    assert_eq!(parsed.dropped.add(2, 0.0, 0.0, 5, 0), 8);
+    assert_eq!(parsed.dropped.add(2, 0.0, 0.0, 5, 0), 9);
+    assert_eq!((parsed.dropped.items_count, parsed.dropped.last_dropped_item_uid), (5, 9));
+    assert_eq!(parsed.dropped.duplicates(), vec![7]);
+    parsed.dropped.items.push(item(2, 0.0, u32::MAX));
+    parsed.dropped.items.push(item(2, 0.0, 1));
+    assert_eq!(parsed.dropped.add(2, 0.0, 0.0, 5, 0), 2);
+
+    // collecting one of the pair is one collection, not zero
+    let mut after = world.clone();
+    after.dropped.items.remove(0);
+    let summary = world.diff_summary(&after);
+    assert_eq!((summary.items_dropped, summary.items_collected), (0, 1));
+    assert!(world.diff_summary(&world.clone()).is_empty());
+}
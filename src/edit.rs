@@ -0,0 +1,180 @@
+use crate::flags::TileFlags;
+use crate::tile::Rect;
+use crate::world::World;
+
+pub(crate) const PAINT_MASK: u16 = 0x2000 | 0x4000 | 0x8000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PaintColor {
+    // removes any paint
+    None,
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Aqua,
+    Purple,
+    Charcoal,
+}
+
+impl PaintColor {
+    // paint is stored as the red, green and blue tile flag bits
+    pub fn to_flags(&self) -> TileFlags {
+        let (painted_red, painted_green, painted_blue) = match self {
+            PaintColor::None => (false, false, false),
+            PaintColor::Red => (true, false, false),
+            PaintColor::Green => (false, true, false),
+            PaintColor::Blue => (false, false, true),
+            PaintColor::Yellow => (true, true, false),
+            PaintColor::Aqua => (false, true, true),
+            PaintColor::Purple => (true, false, true),
+            PaintColor::Charcoal => (true, true, true),
+        };
+        TileFlags {
+            painted_red,
+            painted_green,
+            painted_blue,
+            ..Default::default()
+        }
+    }
+}
+
+impl World {
+    // Removes `remove` then sets `add` on every tile inside the rect, keeping
+    // flags and flags_number in sync. Blank tiles (no foreground and no
+    // background) are left alone when skip_blank is set. Returns how many tiles
+    // actually changed.
+    pub fn set_flags_in_rect(&mut self, rect: Rect, add: TileFlags, remove: TileFlags, skip_blank: bool) -> usize {
+        let add = add.to_u16();
+        let remove = remove.to_u16();
+        self.update_flags_in_rect(rect, skip_blank, |flags| (flags & !remove) | add)
+    }
+
+    pub fn paint_rect(&mut self, rect: Rect, color: PaintColor, skip_blank: bool) -> usize {
+        let paint = color.to_flags().to_u16();
+        self.update_flags_in_rect(rect, skip_blank, |flags| (flags & !PAINT_MASK) | paint)
+    }
+
+    fn update_flags_in_rect(&mut self, rect: Rect, skip_blank: bool, update: impl Fn(u16) -> u16) -> usize {
+        let x_end = rect.x.saturating_add(rect.width).min(self.width);
+        let y_end = rect.y.saturating_add(rect.height).min(self.height);
+        let mut modified = 0;
+        for y in rect.y..y_end {
+            for x in rect.x..x_end {
+                let index = (y * self.width + x) as usize;
+                let tile = match self.tiles.get_mut(index) {
+                    Some(tile) => tile,
+                    None => continue,
+                };
+                if skip_blank && tile.foreground_item_id == 0 && tile.background_item_id == 0 {
+                    continue;
+                }
+
+                let flags = update(tile.flags_number);
+                if flags != tile.flags_number {
+                    tile.flags_number = flags;
+                    tile.flags = TileFlags::from_u16(flags);
+                    self.record_change(index);
+                    modified += 1;
+                }
+            }
+        }
+        modified
+    }
+}
+
+#[cfg(test)]
+fn test_random_world(seed: &mut u64, excluded_flags: u16) -> World {
+    use crate::tile::Tile;
+
+    let item_database = crate::test_item_database();
+    let mut world = World::new(item_database.clone());
+    world.width = 17;
+    world.height = 9;
+    for index in 0..world.width * world.height {
+        let flags = test_next(seed) as u16 & !excluded_flags;
+        let foreground = if test_next(seed).is_multiple_of(3) { 0 } else { 2 };
+        world.tiles.push(Tile::new(
+            foreground,
+            0,
+            0,
+            TileFlags::from_u16(flags),
+            flags,
+            index % world.width,
+            index / world.width,
+            item_database.clone(),
+        ));
+    }
+    world
+}
+
+#[cfg(test)]
+fn test_next(seed: &mut u64) -> u64 {
+    *seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+    *seed >> 33
+}
+
+#[test]
+fn test_set_flags_in_rect_round_trip() {
+    let mut seed = 7;
+    for _ in 0..50 {
+        let bit = 1u16 << (test_next(&mut seed) % 16);
+        let mut world = test_random_world(&mut seed, bit);
+        let original: Vec<u16> = world.tiles.iter().map(|tile| tile.flags_number).collect();
+        let rect = Rect::new(
+            (test_next(&mut seed) % 20) as u32,
+            (test_next(&mut seed) % 12) as u32,
+            (test_next(&mut seed) % 20) as u32,
+            (test_next(&mut seed) % 12) as u32,
+        );
+        let skip_blank = test_next(&mut seed).is_multiple_of(2);
+
+        let flag = TileFlags::from_u16(bit);
+        let added = world.set_flags_in_rect(rect, flag.clone(), TileFlags::default(), skip_blank);
+        for tile in &world.tiles {
+            assert_eq!(tile.flags, TileFlags::from_u16(tile.flags_number));
+            let touched = rect.contains(tile.x, tile.y) && !(skip_blank && tile.foreground_item_id == 0);
+            assert_eq!(tile.flags_number & bit != 0, touched);
+        }
+        let removed = world.set_flags_in_rect(rect, TileFlags::default(), flag, skip_blank);
+        assert_eq!(added, removed);
+
+        let restored: Vec<u16> = world.tiles.iter().map(|tile| tile.flags_number).collect();
+        assert_eq!(restored, original);
+    }
+}
+
+#[test]
+fn test_paint_rect() {
+    let mut seed = 3;
+    let mut world = test_random_world(&mut seed, 0);
+    let rect = Rect::new(2, 1, 4, 3);
+
+    world.paint_rect(rect, PaintColor::Purple, false);
+    for tile in &world.tiles {
+        if rect.contains(tile.x, tile.y) {
+            assert!(tile.flags.painted_red && !tile.flags.painted_green && tile.flags.painted_blue);
+        }
+    }
+    assert_eq!(world.paint_rect(rect, PaintColor::Purple, false), 0);
+
+    let outside: Vec<u16> = world
+        .tiles
+        .iter()
+        .filter(|tile| !rect.contains(tile.x, tile.y))
+        .map(|tile| tile.flags_number)
+        .collect();
+    assert_eq!(world.paint_rect(rect, PaintColor::None, false), 12);
+    assert!(world
+        .tiles
+        .iter()
+        .filter(|tile| rect.contains(tile.x, tile.y))
+        .all(|tile| tile.flags_number & PAINT_MASK == 0));
+    let after: Vec<u16> = world
+        .tiles
+        .iter()
+        .filter(|tile| !rect.contains(tile.x, tile.y))
+        .map(|tile| tile.flags_number)
+        .collect();
+    assert_eq!(after, outside);
+}
@@ -0,0 +1,285 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::fidelity::LossReason;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ParseWarning {
+    UnknownItem { item_id: u16, x: u32, y: u32 },
+    CborHeuristic { item_id: u16, x: u32, y: u32 },
+    // the lock's settings byte and its tile's open to public flag disagree,
+    // access checks treat the lock as public if either says so
+    LockPublicMismatch { x: u32, y: u32 },
+    // dropped item uids that appear more than once, each listed once in order
+    DuplicateDroppedUids { uids: Vec<u32> },
+    // ParseOptions::recover_truncated kept a cut off world, tiles from
+    // `tiles_parsed` on are padding
+    Truncated { tiles_parsed: u32, tile_count: u32 },
+    // ParseOptions::skip_bad_tiles replaced the record at byte `offset`, with
+    // the item ids it starts with, by a blank tile; `error` says what failed
+    SkippedTile {
+        x: u32,
+        y: u32,
+        offset: u64,
+        foreground_item_id: u16,
+        background_item_id: u16,
+        error: String,
+    },
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseWarning::UnknownItem { item_id, x, y } => {
+                write!(f, "item {} at ({}, {}) is not in the item database", item_id, x, y)
+            }
+            ParseWarning::CborHeuristic { item_id, x, y } => {
+                write!(f, "CBOR data for item {} at ({}, {}) was found by guessing", item_id, x, y)
+            }
+            ParseWarning::LockPublicMismatch { x, y } => {
+                write!(f, "lock at ({}, {}) disagrees with its tile's public flag", x, y)
+            }
+            ParseWarning::DuplicateDroppedUids { uids } => {
+                write!(f, "dropped item uids used more than once: {:?}", uids)
+            }
+            ParseWarning::Truncated { tiles_parsed, tile_count } => write!(
+                f,
+                "the data ends early, {} of {} tiles were read and the rest are blank",
+                tiles_parsed, tile_count
+            ),
+            ParseWarning::SkippedTile {
+                x,
+                y,
+                offset,
+                foreground_item_id,
+                background_item_id,
+                error,
+            } => write!(
+                f,
+                "tile at ({}, {}) with items {} and {} at byte {} was replaced by a blank tile: {}",
+                x, y, foreground_item_id, background_item_id, offset, error
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum WorldParseError {
+    InputTooSmall { len: usize, minimum: usize },
+    InvalidDimensions { width: u32, height: u32, tile_count: u32 },
+    UnknownItem { item_id: u16, x: u32, y: u32 },
+    // `offset` is where the type byte sits in the data, `item_id` the item
+    // the extra data belongs to
+    UnknownExtraType { extra_type: u8, item_id: u16, offset: u64, x: u32, y: u32 },
+    // the data ends inside a tile's extra data, whose type byte sits at
+    // `offset`
+    TruncatedExtraData { extra_type: u8, item_id: u16, offset: u64, x: u32, y: u32 },
+    // a length read from the data is past its cap in crate::limits
+    LengthLimit { field: &'static str, len: u64, max: usize, x: u32, y: u32 },
+    // the item database has no items, usually items.dat was never loaded
+    EmptyItemDatabase,
+    // older than ParseOptions::min_version
+    UnsupportedVersion { found: u16, minimum: u16 },
+    // a stream holds, or its header claims tiles for, more than
+    // limits::MAX_WORLD_LEN bytes
+    WorldTooLarge { max: usize },
+    // the header claims more tiles than width * height has room for
+    TileCountTooLarge { tile_count: u32, width: u32, height: u32 },
+    // the data ends inside `context` (the header, a tile, the dropped items
+    // ...), which starts at byte `offset`
+    UnexpectedEof { offset: u64, context: &'static str },
+    Io(std::io::Error),
+}
+
+impl fmt::Display for WorldParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorldParseError::InputTooSmall { len, minimum } => write!(
+                f,
+                "world data is {} bytes, at least {} are needed",
+                len, minimum
+            ),
+            WorldParseError::InvalidDimensions {
+                width,
+                height,
+                tile_count,
+            } => write!(
+                f,
+                "invalid world dimensions {}x{} for {} tiles",
+                width, height, tile_count
+            ),
+            WorldParseError::UnknownItem { item_id, x, y } => write!(
+                f,
+                "item {} at ({}, {}) is not in the item database",
+                item_id, x, y
+            ),
+            WorldParseError::UnknownExtraType {
+                extra_type,
+                item_id,
+                offset,
+                x,
+                y,
+            } => write!(
+                f,
+                "tile at ({}, {}) with item {} has unknown extra data type {} at byte {}",
+                x, y, item_id, extra_type, offset
+            ),
+            WorldParseError::TruncatedExtraData {
+                extra_type,
+                item_id,
+                offset,
+                x,
+                y,
+            } => write!(
+                f,
+                "world data ends inside the extra data (type {}) of the tile at ({}, {}) with item {}, \
+                 starting at byte {}",
+                extra_type, x, y, item_id, offset
+            ),
+            WorldParseError::EmptyItemDatabase => {
+                write!(f, "the item database is empty, load items.dat before parsing worlds")
+            }
+            WorldParseError::LengthLimit { field, len, max, x, y } => write!(
+                f,
+                "{} of the tile at ({}, {}) is {} long, the limit is {}",
+                field, x, y, len, max
+            ),
+            WorldParseError::WorldTooLarge { max } => write!(f, "the world is longer than the {} byte limit", max),
+            WorldParseError::UnsupportedVersion { found, minimum } => write!(
+                f,
+                "world version {} is older than the oldest accepted, {}",
+                found, minimum
+            ),
+            WorldParseError::TileCountTooLarge {
+                tile_count,
+                width,
+                height,
+            } => write!(
+                f,
+                "{} tiles don't fit in a {}x{} world",
+                tile_count, width, height
+            ),
+            WorldParseError::UnexpectedEof { offset, context } => {
+                write!(f, "world data ends inside the {} starting at byte {}", context, offset)
+            }
+            WorldParseError::Io(err) => write!(f, "failed to read world data: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for WorldParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WorldParseError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WorldParseError {
+    fn from(err: std::io::Error) -> Self {
+        WorldParseError::Io(err)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SerializeError {
+    Lossy(Vec<LossReason>),
+    // a string or list longer than its length field holds, `tile` is None
+    // for the world name and the tile and dropped item counts
+    TooLong {
+        field: &'static str,
+        len: usize,
+        tile: Option<(u32, u32)>,
+    },
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializeError::Lossy(losses) => {
+                write!(f, "world would not serialize byte-identically: ")?;
+                for (index, loss) in losses.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}", loss)?;
+                }
+                Ok(())
+            }
+            SerializeError::TooLong {
+                field,
+                len,
+                tile: Some((x, y)),
+            } => write!(f, "{} of the tile at ({}, {}) is {} long, too long to encode", field, x, y, len),
+            SerializeError::TooLong { field, len, tile: None } => {
+                write!(f, "{} of the world is {} long, too long to encode", field, len)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+#[test]
+fn test_structured_parse_errors() {
+    use crate::world::{World, MIN_HEADER_SIZE};
+    use crate::{test_world_bytes, version};
+    use std::sync::Arc;
+
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+
+    // any version parses unless a floor is asked for
+    let old = test_world_bytes(14, 1, 1, &[0; 8]);
+    world.parse(&old).unwrap();
+    assert_eq!(World::read_header(&old[..]).unwrap().version, 14);
+    world.options.min_version = Some(15);
+    let err = world.parse(&old).unwrap_err();
+    assert!(matches!(err, WorldParseError::UnsupportedVersion { found: 14, minimum: 15 }));
+    assert_eq!(err.to_string(), "world version 14 is older than the oldest accepted, 15");
+    assert!(world.is_error);
+    world.options.min_version = None;
+
+    // the tile count sits right before the 5 padding bytes
+    let mut crowded = test_world_bytes(version::CURRENT, 2, 1, &[0; 16]);
+    crowded[20..24].copy_from_slice(&3u32.to_le_bytes());
+    let err = world.parse(&crowded).unwrap_err();
+    assert!(matches!(
+        err,
+        WorldParseError::TileCountTooLarge {
+            tile_count: 3,
+            width: 2,
+            height: 1
+        }
+    ));
+    assert_eq!(err.to_string(), "3 tiles don't fit in a 2x1 world");
+    assert!(matches!(World::read_header(&crowded[..]), Err(WorldParseError::TileCountTooLarge { .. })));
+
+    // cut inside each section, the error names it and where it starts
+    let data = std::fs::read("world.dat").unwrap();
+    let layout = World::new(Arc::clone(&item_database)).parse_with_layout(&data).unwrap();
+    let (tiles, dropped, weather) = (
+        layout.tiles.unwrap(),
+        layout.dropped.unwrap(),
+        layout.weather.unwrap(),
+    );
+    let cuts = [
+        (MIN_HEADER_SIZE + 1, 0, "header"),
+        (tiles.start + 3, tiles.start, "tile"),
+        (tiles.end + 5, tiles.end, "padding after the tiles"),
+        (dropped.start + 30, dropped.start, "dropped items"),
+        (weather.start + 1, weather.start, "weather"),
+    ];
+    for (cut, start, section) in cuts {
+        match world.parse(&data[..cut]) {
+            Err(WorldParseError::UnexpectedEof { offset, context }) => {
+                assert_eq!((offset, context), (start as u64, section), "cut at {}", cut)
+            }
+            other => panic!("cut at {}: {:?}", cut, other),
+        }
+        assert!(world.is_error);
+    }
+}
@@ -0,0 +1,174 @@
+use crate::dropped::DroppedItem;
+use crate::tile::Tile;
+use crate::weather::WeatherType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::fmt;
+use std::sync::Arc;
+
+// One change to a live world, as a server event stream reports it.
+#[derive(Debug, Clone)]
+pub enum WorldEvent {
+    // the whole tile at (x, y) as it is now; its own x and y are ignored
+    TileChanged { x: u32, y: u32, tile: Tile },
+    ItemDropped(DroppedItem),
+    // the uid of the dropped item that was picked up
+    ItemCollected(u32),
+    WeatherChanged { base: WeatherType, current: WeatherType },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum EventError {
+    TileOutOfBounds { x: u32, y: u32 },
+    UnknownItem { item_id: u16, x: u32, y: u32 },
+    DuplicateDroppedUid(u32),
+    UnknownDroppedUid(u32),
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventError::TileOutOfBounds { x, y } => write!(f, "tile ({}, {}) is outside the world", x, y),
+            EventError::UnknownItem { item_id, x, y } => write!(
+                f,
+                "item {} at ({}, {}) is not in the item database",
+                item_id, x, y
+            ),
+            EventError::DuplicateDroppedUid(uid) => write!(f, "a dropped item with uid {} already exists", uid),
+            EventError::UnknownDroppedUid(uid) => write!(f, "no dropped item has uid {}", uid),
+        }
+    }
+}
+
+impl std::error::Error for EventError {}
+
+impl World {
+    // Applies one event to the world. A rejected event leaves the world as it
+    // was. Item ids are checked against `item_database`; dropped item
+    // positions are in pixels and aren't checked.
+    pub fn apply_event(&mut self, event: WorldEvent, item_database: &ItemDatabase) -> Result<(), EventError> {
+        match event {
+            WorldEvent::TileChanged { x, y, mut tile } => {
+                if self.get_tile(x, y).is_none() {
+                    return Err(EventError::TileOutOfBounds { x, y });
+                }
+                for item_id in [tile.foreground_item_id, tile.background_item_id] {
+                    if item_id != 0 && item_database.get_item(&(item_id as u32)).is_none() {
+                        return Err(EventError::UnknownItem { item_id, x, y });
+                    }
+                }
+                tile.x = x;
+                tile.y = y;
+                tile.item_database = Arc::clone(&self.item_database);
+                *self.get_tile_mut(x, y).unwrap() = tile;
+                self.record_change((y * self.width + x) as usize);
+            }
+            WorldEvent::ItemDropped(item) => {
+                if self.dropped.items.iter().any(|dropped| dropped.uid == item.uid) {
+                    return Err(EventError::DuplicateDroppedUid(item.uid));
+                }
+                if item_database.get_item(&(item.id as u32)).is_none() {
+                    return Err(EventError::UnknownItem {
+                        item_id: item.id,
+                        x: (item.x / 32.0) as u32,
+                        y: (item.y / 32.0) as u32,
+                    });
+                }
+                self.dropped.last_dropped_item_uid = self.dropped.last_dropped_item_uid.max(item.uid);
+                self.dropped.items.push(item);
+                self.dropped.items_count = self.dropped.items.len() as u32;
+            }
+            WorldEvent::ItemCollected(uid) => {
+                // with duplicate uids it's the last one, the one by_uid sees
+                let index = self
+                    .dropped
+                    .items
+                    .iter()
+                    .rposition(|item| item.uid == uid)
+                    .ok_or(EventError::UnknownDroppedUid(uid))?;
+                self.dropped.items.remove(index);
+                self.dropped.items_count = self.dropped.items.len() as u32;
+            }
+            WorldEvent::WeatherChanged { base, current } => self.set_weather(base, current),
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_apply_event() {
+    use crate::builder::WorldBuilder;
+    use crate::flags::TileFlags;
+    use crate::tile_extra::TileType;
+
+    let item_database = crate::test_item_database();
+    let database = item_database.read().unwrap();
+    let mut world = WorldBuilder::new().size(3, 2).build(Arc::clone(&item_database));
+    let mut sign = Tile::new(20, 14, 0, TileFlags::from_u16(0x01), 0x01, 9, 9, Arc::clone(&item_database));
+    sign.tile_type = TileType::Sign {
+        text: "hello".to_string(),
+    };
+    let dropped = |uid: u32| DroppedItem {
+        id: 2,
+        x: 40.0,
+        y: 8.0,
+        count: 5,
+        flags: 0,
+        uid,
+    };
+
+    let events = vec![
+        WorldEvent::TileChanged {
+            x: 1,
+            y: 0,
+            tile: sign.clone(),
+        },
+        WorldEvent::ItemDropped(dropped(7)),
+        WorldEvent::ItemDropped(dropped(8)),
+        WorldEvent::ItemCollected(7),
+        WorldEvent::WeatherChanged {
+            base: WeatherType::Sunny,
+            current: WeatherType::Night,
+        },
+    ];
+    for event in events {
+        world.apply_event(event, &database).unwrap();
+    }
+
+    let tile = world.get_tile(1, 0).unwrap();
+    assert!(tile.same_content(&sign));
+    assert_eq!((tile.x, tile.y), (1, 0));
+    assert_eq!(world.dropped.items, vec![dropped(8)]);
+    assert_eq!((world.dropped.items_count, world.dropped.last_dropped_item_uid), (1, 8));
+    assert_eq!(
+        (world.base_weather.clone(), world.current_weather.clone()),
+        (WeatherType::Sunny, WeatherType::Night)
+    );
+
+    // rejected events change nothing
+    let before = world.serialize().unwrap();
+    let out_of_bounds = WorldEvent::TileChanged {
+        x: 3,
+        y: 0,
+        tile: sign.clone(),
+    };
+    assert_eq!(
+        world.apply_event(out_of_bounds, &database).unwrap_err(),
+        EventError::TileOutOfBounds { x: 3, y: 0 }
+    );
+    sign.foreground_item_id = u16::MAX;
+    let unknown = WorldEvent::TileChanged { x: 0, y: 0, tile: sign };
+    assert!(matches!(
+        world.apply_event(unknown, &database),
+        Err(EventError::UnknownItem { x: 0, y: 0, .. })
+    ));
+    assert_eq!(
+        world.apply_event(WorldEvent::ItemDropped(dropped(8)), &database).unwrap_err(),
+        EventError::DuplicateDroppedUid(8)
+    );
+    assert_eq!(
+        world.apply_event(WorldEvent::ItemCollected(7), &database).unwrap_err(),
+        EventError::UnknownDroppedUid(7)
+    );
+    assert_eq!(world.serialize().unwrap(), before);
+}
@@ -0,0 +1,348 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::tile::Tile;
+use crate::tile_extra::TileType;
+use crate::topology::Topology;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::fmt;
+use std::str::FromStr;
+
+// One number in a feature vector. Each has a label (its Display form, e.g.
+// "tile_kind:Seed" or "item:242") which FromStr reads back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Feature {
+    // tiles whose TileType::name() is this, "Basic" being tiles without extra
+    // data. FromStr only takes names in TileType::NAMES; one built by hand
+    // that matches no variant counts nothing
+    TileKindCount(String),
+    // tiles with this item in the foreground or the background, a tile with
+    // it in both counts once
+    ItemCount(u16),
+    // foregrounds whose item has this action_type, the category items.dat
+    // files it under
+    ActionTypeCount(u8),
+    // share of tiles with a foreground, or with a background
+    ForegroundRatio,
+    BackgroundRatio,
+    Width,
+    Height,
+    // the Topology fields of the same name
+    AvgSurface,
+    SkyRatio,
+    DeepestAirPocket,
+    BedrockRows,
+    // dropped item stacks, and the items in them summed
+    DroppedStacks,
+    DroppedItemTotal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FeatureError {
+    UnknownFeature { label: String },
+}
+
+impl fmt::Display for FeatureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FeatureError::UnknownFeature { label } => write!(f, "unknown feature {:?}", label),
+        }
+    }
+}
+
+impl std::error::Error for FeatureError {}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Feature::TileKindCount(kind) => write!(f, "tile_kind:{}", kind),
+            Feature::ItemCount(item_id) => write!(f, "item:{}", item_id),
+            Feature::ActionTypeCount(action_type) => write!(f, "action_type:{}", action_type),
+            Feature::ForegroundRatio => write!(f, "foreground_ratio"),
+            Feature::BackgroundRatio => write!(f, "background_ratio"),
+            Feature::Width => write!(f, "width"),
+            Feature::Height => write!(f, "height"),
+            Feature::AvgSurface => write!(f, "avg_surface"),
+            Feature::SkyRatio => write!(f, "sky_ratio"),
+            Feature::DeepestAirPocket => write!(f, "deepest_air_pocket"),
+            Feature::BedrockRows => write!(f, "bedrock_rows"),
+            Feature::DroppedStacks => write!(f, "dropped_stacks"),
+            Feature::DroppedItemTotal => write!(f, "dropped_item_total"),
+        }
+    }
+}
+
+impl FromStr for Feature {
+    type Err = FeatureError;
+
+    fn from_str(label: &str) -> Result<Feature, FeatureError> {
+        let unknown = || FeatureError::UnknownFeature {
+            label: label.to_string(),
+        };
+        let feature = match label.split_once(':') {
+            Some(("tile_kind", kind)) if TileType::NAMES.contains(&kind) => Feature::TileKindCount(kind.to_string()),
+            Some(("item", item_id)) => Feature::ItemCount(item_id.parse().map_err(|_| unknown())?),
+            Some(("action_type", action_type)) => Feature::ActionTypeCount(action_type.parse().map_err(|_| unknown())?),
+            Some(_) => return Err(unknown()),
+            None => match label {
+                "foreground_ratio" => Feature::ForegroundRatio,
+                "background_ratio" => Feature::BackgroundRatio,
+                "width" => Feature::Width,
+                "height" => Feature::Height,
+                "avg_surface" => Feature::AvgSurface,
+                "sky_ratio" => Feature::SkyRatio,
+                "deepest_air_pocket" => Feature::DeepestAirPocket,
+                "bedrock_rows" => Feature::BedrockRows,
+                "dropped_stacks" => Feature::DroppedStacks,
+                "dropped_item_total" => Feature::DroppedItemTotal,
+                _ => return Err(unknown()),
+            },
+        };
+        Ok(feature)
+    }
+}
+
+// Which features World::feature_vector extracts, in order. The vector has
+// one entry per feature, in the same order, so two vectors from one spec line
+// up whatever the worlds or crate versions were. The text form is one label
+// per line, for pipelines to store next to what they extracted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FeatureSpec {
+    pub features: Vec<Feature>,
+}
+
+impl FeatureSpec {
+    pub fn new(features: Vec<Feature>) -> FeatureSpec {
+        FeatureSpec { features }
+    }
+
+    // Frozen: this layout never changes, a different one becomes default_v2.
+    //   0..12   tiles of kind Door, Sign, Lock, Seed, DisplayBlock,
+    //           VendingMachine, Mannequin, StorageBlock, WeatherMachine,
+    //           DataBedrock, Portrait, GivingTree
+    //   12..19  tiles with Dirt (2), Lava (4), Bedrock (8), Rock (10), Cave
+    //           Background (14), Small Lock (202), World Lock (242)
+    //   19..21  foreground and background ratio
+    //   21..23  width and height
+    //   23..27  avg surface, sky ratio, deepest air pocket, bedrock rows
+    //   27..29  dropped stacks and dropped item total
+    pub fn default_v1() -> FeatureSpec {
+        let kinds = [
+            "Door",
+            "Sign",
+            "Lock",
+            "Seed",
+            "DisplayBlock",
+            "VendingMachine",
+            "Mannequin",
+            "StorageBlock",
+            "WeatherMachine",
+            "DataBedrock",
+            "Portrait",
+            "GivingTree",
+        ];
+        let mut features: Vec<Feature> = kinds
+            .into_iter()
+            .map(|kind| Feature::TileKindCount(kind.to_string()))
+            .collect();
+        features.extend([2, 4, 8, 10, 14, 202, 242].map(Feature::ItemCount));
+        features.extend([
+            Feature::ForegroundRatio,
+            Feature::BackgroundRatio,
+            Feature::Width,
+            Feature::Height,
+            Feature::AvgSurface,
+            Feature::SkyRatio,
+            Feature::DeepestAirPocket,
+            Feature::BedrockRows,
+            Feature::DroppedStacks,
+            Feature::DroppedItemTotal,
+        ]);
+        FeatureSpec { features }
+    }
+
+    pub fn len(&self) -> usize {
+        self.features.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+    }
+
+    // Column names for the vector's entries.
+    pub fn labels(&self) -> Vec<String> {
+        self.features.iter().map(Feature::to_string).collect()
+    }
+}
+
+impl fmt::Display for FeatureSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for feature in &self.features {
+            writeln!(f, "{}", feature)?;
+        }
+        Ok(())
+    }
+}
+
+// Blank lines are skipped.
+impl FromStr for FeatureSpec {
+    type Err = FeatureError;
+
+    fn from_str(text: &str) -> Result<FeatureSpec, FeatureError> {
+        let features = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+        Ok(FeatureSpec { features })
+    }
+}
+
+impl World {
+    // One value per feature of `spec`, in its order. Counts and sizes are
+    // exact up to 2^24, ratios are 0 for a world without tiles.
+    pub fn feature_vector(&self, spec: &FeatureSpec, item_database: &ItemDatabase) -> Vec<f32> {
+        let needs_topology = |feature: &Feature| {
+            matches!(
+                feature,
+                Feature::AvgSurface | Feature::SkyRatio | Feature::DeepestAirPocket | Feature::BedrockRows
+            )
+        };
+        let topology = spec.features.iter().any(needs_topology).then(|| self.topology());
+        spec.features
+            .iter()
+            .map(|feature| self.feature_value(feature, topology.as_ref(), item_database))
+            .collect()
+    }
+
+    fn feature_value(&self, feature: &Feature, topology: Option<&Topology>, item_database: &ItemDatabase) -> f32 {
+        let count = |matches: &dyn Fn(&Tile) -> bool| self.tiles.iter().filter(|tile| matches(tile)).count();
+        let ratio = |tiles: usize| {
+            if self.tiles.is_empty() {
+                0.0
+            } else {
+                tiles as f32 / self.tiles.len() as f32
+            }
+        };
+        let topology = || topology.expect("topology is computed for the features that need it");
+        match feature {
+            Feature::TileKindCount(kind) => count(&|tile| tile.tile_type.name() == kind) as f32,
+            Feature::ItemCount(item_id) => {
+                count(&|tile| tile.foreground_item_id == *item_id || tile.background_item_id == *item_id) as f32
+            }
+            Feature::ActionTypeCount(action_type) => count(&|tile| {
+                tile.foreground_item_id != 0
+                    && item_database
+                        .get_item(&(tile.foreground_item_id as u32))
+                        .is_some_and(|item| item.action_type == *action_type)
+            }) as f32,
+            Feature::ForegroundRatio => ratio(count(&|tile| tile.foreground_item_id != 0)),
+            Feature::BackgroundRatio => ratio(count(&|tile| tile.background_item_id != 0)),
+            Feature::Width => self.width as f32,
+            Feature::Height => self.height as f32,
+            Feature::AvgSurface => topology().avg_surface,
+            Feature::SkyRatio => topology().sky_ratio,
+            Feature::DeepestAirPocket => topology().deepest_air_pocket as f32,
+            Feature::BedrockRows => topology().bedrock_rows as f32,
+            Feature::DroppedStacks => self.dropped.items.len() as f32,
+            Feature::DroppedItemTotal => self.dropped.items.iter().map(|item| item.count as f32).sum(),
+        }
+    }
+}
+
+#[test]
+fn test_feature_vector() {
+    use crate::builder::WorldBuilder;
+    use crate::tile_extra::TileType;
+
+    let item_database = crate::test_item_database();
+    let database = item_database.read().unwrap();
+    // the frozen layout, label by label
+    let spec = FeatureSpec::default_v1();
+    assert_eq!(spec.len(), 29);
+    assert_eq!(
+        spec.to_string(),
+        "tile_kind:Door\ntile_kind:Sign\ntile_kind:Lock\ntile_kind:Seed\ntile_kind:DisplayBlock\n\
+         tile_kind:VendingMachine\ntile_kind:Mannequin\ntile_kind:StorageBlock\ntile_kind:WeatherMachine\n\
+         tile_kind:DataBedrock\ntile_kind:Portrait\ntile_kind:GivingTree\nitem:2\nitem:4\nitem:8\nitem:10\n\
+         item:14\nitem:202\nitem:242\nforeground_ratio\nbackground_ratio\nwidth\nheight\navg_surface\n\
+         sky_ratio\ndeepest_air_pocket\nbedrock_rows\ndropped_stacks\ndropped_item_total\n"
+    );
+    assert_eq!(spec.to_string().parse::<FeatureSpec>().unwrap(), spec);
+    assert_eq!(
+        "item:2\nstars".parse::<FeatureSpec>(),
+        Err(FeatureError::UnknownFeature {
+            label: "stars".to_string()
+        })
+    );
+    assert!("item:70000".parse::<Feature>().is_err());
+    // a misspelled tile kind would count nothing forever, so it's refused
+    assert_eq!(
+        "tile_kind:Lokc".parse::<Feature>(),
+        Err(FeatureError::UnknownFeature {
+            label: "tile_kind:Lokc".to_string()
+        })
+    );
+    assert!("tile_kind:".parse::<Feature>().is_err());
+    for name in TileType::NAMES {
+        let label = format!("tile_kind:{}", name);
+        assert_eq!(label.parse::<Feature>().unwrap().to_string(), label);
+    }
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&spec).unwrap();
+        assert_eq!(serde_json::from_str::<FeatureSpec>(&json).unwrap(), spec);
+    }
+
+    // 4x2: a dirt row over a bedrock row, a sign on the dirt, cave background
+    // behind two tiles and 5 + 3 dropped items
+    let mut world = WorldBuilder::new()
+        .size(4, 2)
+        .build(std::sync::Arc::clone(&item_database));
+    for x in 0..4 {
+        world.edit_tile(x, 0).unwrap().foreground_item_id = 2;
+        world.edit_tile(x, 1).unwrap().foreground_item_id = 8;
+    }
+    {
+        let mut sign = world.edit_tile(1, 0).unwrap();
+        sign.foreground_item_id = 20;
+        sign.tile_type = TileType::Sign { text: "hi".to_string() };
+    }
+    world.edit_tile(2, 0).unwrap().background_item_id = 14;
+    world.edit_tile(3, 1).unwrap().background_item_id = 14;
+    world.dropped.add(2, 0.0, 0.0, 5, 0);
+    world.dropped.add(10, 32.0, 0.0, 3, 0);
+
+    let vector = world.feature_vector(&spec, &database);
+    assert_eq!(vector.len(), spec.len());
+    let value = |label: &str| vector[spec.labels().iter().position(|other| other == label).unwrap()];
+    assert_eq!(value("tile_kind:Sign"), 1.0);
+    assert_eq!(value("tile_kind:Door"), 0.0);
+    assert_eq!(value("item:2"), 3.0);
+    assert_eq!(value("item:8"), 4.0);
+    assert_eq!(value("item:14"), 2.0);
+    assert_eq!(value("foreground_ratio"), 1.0);
+    assert_eq!(value("background_ratio"), 0.25);
+    assert_eq!((value("width"), value("height")), (4.0, 2.0));
+    // a sign doesn't block movement, its column's surface is the bedrock
+    assert_eq!((value("sky_ratio"), value("bedrock_rows")), (1.0 / 8.0, 1.0));
+    assert_eq!((value("dropped_stacks"), value("dropped_item_total")), (2.0, 8.0));
+
+    // a spec of its own, and the same length for any world
+    let spec = FeatureSpec::new(vec![
+        Feature::ActionTypeCount(database.get_item(&20).unwrap().action_type),
+        Feature::ItemCount(20),
+        Feature::TileKindCount("NoSuchKind".to_string()),
+    ]);
+    assert_eq!(world.feature_vector(&spec, &database), vec![1.0, 1.0, 0.0]);
+    let mut parsed = crate::world::World::new(std::sync::Arc::clone(&item_database));
+    parsed.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    assert_eq!(parsed.feature_vector(&spec, &database).len(), 3);
+    let vector = parsed.feature_vector(&FeatureSpec::default_v1(), &database);
+    assert_eq!(vector.len(), 29);
+    assert_eq!((vector[0], vector[3], vector[4]), (27.0, 6.0, 46.0));
+}
@@ -0,0 +1,153 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::world::World;
+use std::fmt;
+
+// A place where parse kept less than it read, so serialize can't reproduce
+// the input byte for byte.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LossReason {
+    // the world name isn't valid UTF-8 and was decoded lossily
+    InvalidName,
+    // the 5 bytes after the tile count weren't zero
+    HeaderPadding,
+    // the tile re-encodes differently: skipped bytes inside its extra data
+    // weren't zero, a string wasn't valid UTF-8, or the parent index slot
+    // held a value
+    TileRecord { x: u32, y: u32, extra_type: Option<u8> },
+    // an unknown item id was replaced under UnknownItemPolicy::Remap
+    RemappedItem { item_id: u16, x: u32, y: u32 },
+    // the 12 bytes between the tiles and the dropped items weren't zero
+    PostTilePadding,
+    // the unknown u16 between the two weather ids wasn't zero
+    WeatherPadding,
+    // bytes after the current weather, which are never kept
+    TrailingBytes { len: usize },
+    // the input ended at `len` and ParseOptions::recover_truncated made up
+    // the rest
+    Truncated { len: usize },
+    // ParseOptions::skip_bad_tiles dropped the `len` bytes of a tile record
+    SkippedTile { x: u32, y: u32, len: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Fidelity {
+    Exact,
+    Lossy(Vec<LossReason>),
+}
+
+impl fmt::Display for LossReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LossReason::InvalidName => write!(f, "world name is not valid UTF-8"),
+            LossReason::HeaderPadding => write!(f, "header padding is not zero"),
+            LossReason::TileRecord { x, y, extra_type } => match extra_type {
+                Some(extra_type) => write!(
+                    f,
+                    "tile at ({}, {}) with extra type {} does not re-encode identically",
+                    x, y, extra_type
+                ),
+                None => write!(f, "tile at ({}, {}) does not re-encode identically", x, y),
+            },
+            LossReason::RemappedItem { item_id, x, y } => {
+                write!(f, "unknown item {} at ({}, {}) was remapped", item_id, x, y)
+            }
+            LossReason::PostTilePadding => write!(f, "padding after the tiles is not zero"),
+            LossReason::WeatherPadding => write!(f, "weather padding is not zero"),
+            LossReason::TrailingBytes { len } => write!(f, "{} trailing bytes were dropped", len),
+            LossReason::Truncated { len } => write!(f, "the data ends early, after {} bytes", len),
+            LossReason::SkippedTile { x, y, len } => {
+                write!(f, "tile at ({}, {}) could not be read, its {} bytes were dropped", x, y, len)
+            }
+        }
+    }
+}
+
+impl World {
+    // Whether serialize reproduces the data this world was parsed from. It
+    // describes the last parse only; edits made afterwards aren't tracked.
+    pub fn fidelity(&self) -> Fidelity {
+        if self.losses.is_empty() {
+            Fidelity::Exact
+        } else {
+            Fidelity::Lossy(self.losses.clone())
+        }
+    }
+}
+
+// How many bytes past the weather parse dropped, the part of a capture that
+// serialize can't give back.
+#[cfg(test)]
+pub(crate) fn trailing_len(world: &World) -> usize {
+    world
+        .losses
+        .iter()
+        .find_map(|loss| match loss {
+            LossReason::TrailingBytes { len } => Some(*len),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_fidelity_exact() {
+    use crate::options::{CborDetection, Endian, SerializeOptions};
+    use crate::writer;
+
+    for endianness in [Endian::Little, Endian::Big] {
+        let mut world = writer::test_sample_world();
+        world.options.endianness = endianness;
+        world.options.cbor_detection = CborDetection::ExplicitList([14].into_iter().collect());
+        let data = world.serialize().unwrap();
+
+        let mut parsed = World::new(crate::test_item_database());
+        parsed.options = world.options.clone();
+        parsed.parse(&data).unwrap();
+        assert_eq!(parsed.fidelity(), Fidelity::Exact);
+        let options = SerializeOptions { require_exact: true };
+        assert_eq!(parsed.serialize_with(&options).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_fidelity_lossy() {
+    use crate::error::SerializeError;
+    use crate::options::{SerializeOptions, UnknownItemPolicy};
+
+    // the capture has non-zero padding and a 7 byte trailer, its DataBedrock
+    // payload is kept since it's decoded
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let losses = vec![
+        LossReason::HeaderPadding,
+        LossReason::PostTilePadding,
+        LossReason::TrailingBytes { len: 7 },
+    ];
+    assert_eq!(world.fidelity(), Fidelity::Lossy(losses.clone()));
+    assert_eq!(trailing_len(&world), 7);
+    let options = SerializeOptions { require_exact: true };
+    assert_eq!(world.serialize_with(&options), Err(SerializeError::Lossy(losses)));
+    assert!(world.serialize_with(&SerializeOptions::default()).is_ok());
+
+    // an item id past the end of items.dat, remapped to dirt
+    let tiles = [0xFF, 0x7F, 0, 0, 0, 0, 0, 0];
+    let mut world = World::new(crate::test_item_database());
+    world.options.unknown_items = UnknownItemPolicy::Remap(2);
+    world
+        .parse(&crate::test_world_bytes(crate::version::CURRENT, 1, 1, &tiles))
+        .unwrap();
+    assert_eq!(
+        world.fidelity(),
+        Fidelity::Lossy(vec![LossReason::RemappedItem {
+            item_id: 0x7FFF,
+            x: 0,
+            y: 0
+        }])
+    );
+
+    world.reset();
+    assert_eq!(world.fidelity(), Fidelity::Exact);
+}
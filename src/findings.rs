@@ -0,0 +1,542 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::flags::TileFlags;
+use crate::tile::TileIndex;
+use crate::tile_extra::TileType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::fmt;
+use std::fmt::Write;
+
+// Ordered, so the worst finding is the max.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Finding {
+    pub severity: Severity,
+    // short kebab-case id, stable across releases so tools can match on it
+    pub code: String,
+    pub message: String,
+    // the tile it's about, None for world-wide findings
+    pub position: Option<(u32, u32)>,
+}
+
+// What one or more validation passes found, in the order they found it.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Findings {
+    pub findings: Vec<Finding>,
+}
+
+impl Findings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, severity: Severity, code: &str, message: String, position: Option<(u32, u32)>) {
+        self.findings.push(Finding {
+            severity,
+            code: code.to_string(),
+            message,
+            position,
+        });
+    }
+
+    pub fn merge(&mut self, other: Findings) {
+        self.findings.extend(other.findings);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.findings.len()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Finding> {
+        self.findings.iter()
+    }
+
+    pub fn with_code<'a>(&'a self, code: &'a str) -> impl Iterator<Item = &'a Finding> {
+        self.findings.iter().filter(move |finding| finding.code == code)
+    }
+
+    pub fn at_least(&self, severity: Severity) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |finding| finding.severity >= severity)
+    }
+
+    // None when nothing was found. A command line tool can map it to its exit
+    // code.
+    pub fn max_severity(&self) -> Option<Severity> {
+        self.findings.iter().map(|finding| finding.severity).max()
+    }
+}
+
+// One finding per line, in aligned columns: severity, code, position, message.
+impl fmt::Display for Findings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let position = |finding: &Finding| match finding.position {
+            Some((x, y)) => format!("({}, {})", x, y),
+            None => "-".to_string(),
+        };
+        let code_width = self
+            .findings
+            .iter()
+            .map(|finding| finding.code.len())
+            .max()
+            .unwrap_or(0);
+        let position_width = self
+            .findings
+            .iter()
+            .map(|finding| position(finding).len())
+            .max()
+            .unwrap_or(0);
+        for finding in &self.findings {
+            writeln!(
+                f,
+                "{:<7}  {:<code_width$}  {:<position_width$}  {}",
+                finding.severity.to_string(),
+                finding.code,
+                position(finding),
+                finding.message,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl World {
+    // Checks the world as it is in memory, usually after it was edited or
+    // built by hand, for what would make it serialize into something the game
+    // (or this parser) reads differently:
+    //   - tile-count, tile-position: the tile list doesn't match the size
+    //   - parent-index: parent indexes past the end or at a non-lock tile
+    //   - parent-range: parent indexes in a world with more tiles than a
+    //     parent index can reach
+    //   - flags-number, extra-flag: flags out of sync with flags_number or
+    //     with tile_type
+    //   - access-count, items-count: stored counters that disagree with their
+    //     lists
+    //   - lock-public: a lock's settings byte and tile flag disagree on
+    //     whether it's open to the public
+    //   - world-locks: more than one world lock
+    //   - dropped-bounds: dropped items outside the world
+    //   - unknown-item: ids `item_database` doesn't know
+    pub fn validate_all(&self, item_database: &ItemDatabase) -> Findings {
+        let mut findings = Findings::new();
+
+        let area = self.width as u64 * self.height as u64;
+        if self.tiles.len() as u64 != area || self.tile_count as u64 != area {
+            findings.push(
+                Severity::Error,
+                "tile-count",
+                format!(
+                    "{} tiles and a tile_count of {} for a {}x{} world",
+                    self.tiles.len(),
+                    self.tile_count,
+                    self.width,
+                    self.height
+                ),
+                None,
+            );
+        }
+
+        for (index, tile) in self.tiles.iter().enumerate() {
+            let position = Some((tile.x, tile.y));
+            let (width, index) = (self.width as u64, index as u64);
+            if width > 0 && (tile.x as u64, tile.y as u64) != (index % width, index / width) {
+                findings.push(
+                    Severity::Error,
+                    "tile-position",
+                    format!("tile {} is at ({}, {})", index, tile.x, tile.y),
+                    position,
+                );
+            }
+
+            if tile.parent_block_index != 0 {
+                match self.tiles.get(tile.parent_block_index as usize) {
+                    None => findings.push(
+                        Severity::Error,
+                        "parent-index",
+                        format!("parent index {} is past the last tile", tile.parent_block_index),
+                        position,
+                    ),
+                    Some(parent) if !matches!(parent.tile_type, TileType::Lock { .. }) => findings.push(
+                        Severity::Warning,
+                        "parent-index",
+                        format!("parent index {} isn't a lock", tile.parent_block_index),
+                        position,
+                    ),
+                    Some(_) => {}
+                }
+            }
+
+            if tile.flags != TileFlags::from_u16(tile.flags_number) {
+                findings.push(
+                    Severity::Error,
+                    "flags-number",
+                    format!("flags don't match flags_number {:#06x}", tile.flags_number),
+                    position,
+                );
+            }
+            let has_extra_data = tile.tile_type != TileType::Basic;
+            if tile.flags.has_extra_data != has_extra_data {
+                findings.push(
+                    Severity::Error,
+                    "extra-flag",
+                    format!(
+                        "has_extra_data is {} but the extra type is {:?}",
+                        tile.flags.has_extra_data,
+                        tile.tile_type.extra_type_id()
+                    ),
+                    position,
+                );
+            }
+
+            if let TileType::Lock {
+                settings,
+                access_count,
+                access_uids,
+                ..
+            } = &tile.tile_type
+            {
+                if settings.is_open_to_public != tile.flags.is_open_to_public {
+                    findings.push(
+                        Severity::Warning,
+                        "lock-public",
+                        format!(
+                            "settings say open to public is {}, the tile flag says {}",
+                            settings.is_open_to_public, tile.flags.is_open_to_public
+                        ),
+                        position,
+                    );
+                }
+                if *access_count as usize != access_uids.len() {
+                    findings.push(
+                        Severity::Warning,
+                        "access-count",
+                        format!("access_count is {} for {} uids", access_count, access_uids.len()),
+                        position,
+                    );
+                }
+            }
+
+            for item_id in [tile.foreground_item_id, tile.background_item_id] {
+                if item_id != 0 && item_database.get_item(&(item_id as u32)).is_none() {
+                    findings.push(
+                        Severity::Error,
+                        "unknown-item",
+                        format!("item {} isn't in the item database", item_id),
+                        position,
+                    );
+                }
+            }
+        }
+
+        // past TileIndex::MAX a stored parent can't tell which tile it meant
+        if self.tiles.len() > TileIndex::MAX.to_usize() + 1 {
+            let parents = self.tiles.iter().filter(|tile| tile.parent_block_index != 0).count();
+            if parents > 0 {
+                findings.push(
+                    Severity::Error,
+                    "parent-range",
+                    format!(
+                        "{} tiles have a parent in a world of {} tiles, indexes past {} can't be stored",
+                        parents,
+                        self.tiles.len(),
+                        TileIndex::MAX.get()
+                    ),
+                    None,
+                );
+            }
+        }
+
+        let world_locks: Vec<(u32, u32)> = self.world_lock_tiles().map(|tile| (tile.x, tile.y)).collect();
+        if world_locks.len() > 1 {
+            findings.push(
+                Severity::Warning,
+                "world-locks",
+                format!("{} world locks at {:?}, the first is used", world_locks.len(), world_locks),
+                None,
+            );
+        }
+
+        if self.dropped.items_count as usize != self.dropped.items.len() {
+            findings.push(
+                Severity::Warning,
+                "items-count",
+                format!(
+                    "items_count is {} for {} dropped items",
+                    self.dropped.items_count,
+                    self.dropped.items.len()
+                ),
+                None,
+            );
+        }
+        // dropped items are placed in pixels, 32 to a tile
+        let (max_x, max_y) = (self.width as f32 * 32.0, self.height as f32 * 32.0);
+        for item in &self.dropped.items {
+            if !(0.0..max_x).contains(&item.x) || !(0.0..max_y).contains(&item.y) {
+                findings.push(
+                    Severity::Warning,
+                    "dropped-bounds",
+                    format!(
+                        "dropped item {} (uid {}) at ({}, {}) is outside the world",
+                        item.id, item.uid, item.x, item.y
+                    ),
+                    None,
+                );
+            }
+        }
+
+        findings
+    }
+
+    // A health report for people, the one call to make on a world that
+    // misbehaves: size, then one line per area, each "ok" or its findings
+    // from validate_all, then what the last parse warned about or couldn't
+    // keep, and a verdict. The wording may change between releases; tools
+    // should use validate_all instead.
+    pub fn report(&self, item_database: &ItemDatabase) -> String {
+        // findings listed per area before the rest are summed up
+        const SHOWN: usize = 5;
+
+        let findings = self.validate_all(item_database);
+        let mut out = String::new();
+        writeln!(out, "world {} (version {}, {:?} endian)", self.name, self.version, self.options.endianness).unwrap();
+        writeln!(
+            out,
+            "size: {}x{}, {} tiles, {} with extra data",
+            self.width,
+            self.height,
+            self.tiles.len(),
+            self.tiles.iter().filter(|tile| tile.tile_type != TileType::Basic).count()
+        )
+        .unwrap();
+
+        let areas: [(&str, &[&str]); 6] = [
+            ("tile count", &["tile-count"]),
+            ("coordinates", &["tile-position", "parent-index", "parent-range"]),
+            ("flags", &["flags-number", "extra-flag"]),
+            ("items", &["unknown-item"]),
+            ("locks", &["access-count", "lock-public", "world-locks"]),
+            ("dropped", &["items-count", "dropped-bounds"]),
+        ];
+        for (area, codes) in areas {
+            let found: Vec<&Finding> = findings
+                .iter()
+                .filter(|finding| codes.contains(&finding.code.as_str()))
+                .collect();
+            if found.is_empty() {
+                writeln!(out, "{}: ok", area).unwrap();
+                continue;
+            }
+            writeln!(out, "{}: {} problem(s)", area, found.len()).unwrap();
+            for finding in found.iter().take(SHOWN) {
+                match finding.position {
+                    Some((x, y)) => writeln!(out, "  {} ({}, {}): {}", finding.severity, x, y, finding.message),
+                    None => writeln!(out, "  {}: {}", finding.severity, finding.message),
+                }
+                .unwrap();
+            }
+            if found.len() > SHOWN {
+                writeln!(out, "  ... and {} more", found.len() - SHOWN).unwrap();
+            }
+        }
+
+        let duplicates = self.dropped.duplicates();
+        writeln!(
+            out,
+            "dropped items: {}, last uid {}, {}",
+            self.dropped.items.len(),
+            self.dropped.last_dropped_item_uid,
+            if duplicates.is_empty() {
+                "no duplicate uids".to_string()
+            } else {
+                format!("duplicate uids {:?}", duplicates)
+            }
+        )
+        .unwrap();
+
+        writeln!(out, "parse warnings: {}", self.warnings.len()).unwrap();
+        for warning in self.warnings.iter().take(SHOWN) {
+            writeln!(out, "  {}", warning).unwrap();
+        }
+        if self.warnings.len() > SHOWN {
+            writeln!(out, "  ... and {} more", self.warnings.len() - SHOWN).unwrap();
+        }
+        if self.losses.is_empty() {
+            writeln!(out, "fidelity: exact").unwrap();
+        } else {
+            writeln!(out, "fidelity: lossy, {} difference(s)", self.losses.len()).unwrap();
+            for loss in self.losses.iter().take(SHOWN) {
+                writeln!(out, "  {}", loss).unwrap();
+            }
+            if self.losses.len() > SHOWN {
+                writeln!(out, "  ... and {} more", self.losses.len() - SHOWN).unwrap();
+            }
+        }
+
+        let errors = findings.at_least(Severity::Error).count();
+        let warnings = findings.len() - errors;
+        if findings.is_empty() && duplicates.is_empty() {
+            writeln!(out, "verdict: healthy").unwrap();
+        } else {
+            writeln!(out, "verdict: {} error(s), {} warning(s)", errors, warnings).unwrap();
+        }
+        out
+    }
+}
+
+#[test]
+fn test_validate_all() {
+    use crate::builder::WorldBuilder;
+    use crate::dropped::DroppedItem;
+
+    let item_database = crate::test_item_database();
+    let item_database = item_database.read().unwrap();
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let findings = world.validate_all(&item_database);
+    assert!(findings.is_empty(), "{}", findings);
+    assert_eq!(findings.max_severity(), None);
+
+    let mut world = WorldBuilder::new().size(4, 4).build(crate::test_item_database());
+    world.tiles[1].tile_type = TileType::Lock {
+        settings: Default::default(),
+        owner_uid: 1,
+        access_count: 3,
+        access_uids: vec![2],
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    world.tiles[1].flags = TileFlags::from_u16(0x01);
+    world.tiles[1].flags_number = 0x01;
+    // a second world lock, public by its settings only
+    world.tiles[8].tile_type = TileType::Lock {
+        settings: crate::flags::LockSettings::from_u8(0x01),
+        owner_uid: 1,
+        access_count: 0,
+        access_uids: Vec::new(),
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    world.tiles[8].flags = TileFlags::from_u16(0x01);
+    world.tiles[8].flags_number = 0x01;
+    world.tiles[2].parent_block_index = 100;
+    world.tiles[3].flags.is_wet = true;
+    world.tiles[4].tile_type = TileType::Sign { text: String::new() };
+    world.tiles[5].foreground_item_id = u16::MAX;
+    world.tiles[6].x = 3;
+    world.dropped.items.push(DroppedItem {
+        id: 2,
+        x: 200.0,
+        y: 16.0,
+        count: 1,
+        flags: 0,
+        uid: 1,
+    });
+    world.tiles.pop();
+
+    let findings = world.validate_all(&item_database);
+    let codes = [
+        "tile-count",
+        "tile-position",
+        "parent-index",
+        "flags-number",
+        "extra-flag",
+        "access-count",
+        "lock-public",
+        "world-locks",
+        "items-count",
+        "dropped-bounds",
+        "unknown-item",
+    ];
+    for code in codes {
+        assert_eq!(findings.with_code(code).count(), 1, "{}\n{}", code, findings);
+    }
+    assert_eq!(findings.len(), codes.len());
+    assert_eq!(findings.max_severity(), Some(Severity::Error));
+    assert_eq!(findings.at_least(Severity::Error).count(), 6);
+    assert_eq!(
+        findings.with_code("parent-index").next().unwrap().position,
+        Some((2, 0))
+    );
+
+    let table = findings.to_string();
+    assert_eq!(table.lines().count(), codes.len());
+    assert!(table.contains("warning  access-count    (1, 0)  access_count is 3 for 1 uids"));
+
+    // parents in a world too large for them to reach every tile
+    let mut large = WorldBuilder::new().size(350, 200).build(crate::test_item_database());
+    assert!(large.validate_all(&item_database).is_empty());
+    large.tiles[1].parent_block_index = 2;
+    assert_eq!(large.validate_all(&item_database).with_code("parent-range").count(), 1);
+
+    // an area past u32::MAX is compared without wrapping
+    let mut huge = World::new(crate::test_item_database());
+    (huge.width, huge.height) = (1 << 16, 1 << 16);
+    assert_eq!(huge.validate_all(&item_database).with_code("tile-count").count(), 1);
+
+    let mut merged = Findings::new();
+    merged.push(Severity::Info, "note", "merged".to_string(), None);
+    merged.merge(findings);
+    assert_eq!(merged.len(), codes.len() + 1);
+    assert_eq!(merged.findings[0].code, "note");
+}
+
+#[test]
+fn test_report() {
+    use crate::builder::WorldBuilder;
+
+    let item_database = crate::test_item_database();
+    let item_database = item_database.read().unwrap();
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let report = world.report(&item_database);
+    assert!(report.starts_with("world PIWD (version 23, Little endian)\nsize: 100x60, 6000 tiles, "));
+    let expected = [
+        "tile count: ok",
+        "coordinates: ok",
+        "flags: ok",
+        "fidelity: lossy, 3 difference(s)",
+        "verdict: healthy",
+    ];
+    for line in expected {
+        assert!(report.lines().any(|l| l == line), "{}\n{}", line, report);
+    }
+
+    let mut world = WorldBuilder::new().size(4, 4).build(crate::test_item_database());
+    for tile in &mut world.tiles[..7] {
+        tile.x = 3;
+    }
+    world.tiles[8].flags.is_wet = true;
+    world.tiles.pop();
+    let report = world.report(&item_database);
+    assert!(report.contains("\ntile count: 1 problem(s)\n  error: 15 tiles and a tile_count of 16 for a 4x4 world"));
+    assert!(report.contains("\ncoordinates: 6 problem(s)\n  error (3, 0): tile 0 is at (3, 0)\n"));
+    assert!(report.contains("\n  ... and 1 more\nflags: 1 problem(s)\n"));
+    assert!(report.contains("\nitems: ok\nlocks: ok\ndropped: ok\n"));
+    assert!(report.ends_with("verdict: 8 error(s), 0 warning(s)\n"), "{}", report);
+}
@@ -0,0 +1,152 @@
+use crate::world::{normalize_world_name, World};
+use crate::writer;
+use byteorder::{LittleEndian, WriteBytesExt};
+use sha2::{Digest, Sha256};
+
+// Bumped only if the canonical form below ever has to change; golden values
+// in the tests pin it down.
+const FINGERPRINT_TAG: &[u8] = b"gtworld-fingerprint-v1";
+
+// Sections that can be left out of the fingerprint. Everything is included by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FingerprintMask {
+    pub ignore_name: bool,
+    pub ignore_dropped: bool,
+    pub ignore_weather: bool,
+}
+
+impl World {
+    // SHA-256 over a canonical form of the world:
+    // - the name, normalized like name_matches does and lowercased
+    // - width and height
+    // - every tile in index order: foreground, background, parent index, raw
+    //   flags and its extra data encoded the way the writer encodes it
+    // - dropped items sorted by (id, x, y, count), with their flags but without uids
+    // - base and current weather as their numeric ids
+    // The format version, world flags, tile_count, tile coordinates, derived
+    // fields (ready_to_harvest, elapsed), bytes the parser skips, parse options,
+    // warnings and the CBOR payload are not part of it. Everything is written
+    // little endian regardless of how the world was read.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        self.fingerprint_ignoring(FingerprintMask::default())
+    }
+
+    pub fn fingerprint_ignoring(&self, mask: FingerprintMask) -> [u8; 32] {
+        let mut canonical = FINGERPRINT_TAG.to_vec();
+
+        if !mask.ignore_name {
+            let name = normalize_world_name(&self.name).to_lowercase();
+            canonical.write_u32::<LittleEndian>(name.len() as u32).unwrap();
+            canonical.extend_from_slice(name.as_bytes());
+        }
+
+        canonical.write_u32::<LittleEndian>(self.width).unwrap();
+        canonical.write_u32::<LittleEndian>(self.height).unwrap();
+        canonical.write_u32::<LittleEndian>(self.tiles.len() as u32).unwrap();
+        for tile in &self.tiles {
+            canonical.write_u16::<LittleEndian>(tile.foreground_item_id).unwrap();
+            canonical.write_u16::<LittleEndian>(tile.background_item_id).unwrap();
+            canonical.write_u16::<LittleEndian>(tile.parent_block_index).unwrap();
+            canonical.write_u16::<LittleEndian>(tile.flags_number).unwrap();
+            // a tile too long to encode contributes its bytes up to that field
+            let _ = writer::write_extra_tile_data::<LittleEndian, _>(tile, &self.options, &mut canonical);
+        }
+
+        if !mask.ignore_dropped {
+            let mut items: Vec<_> = self.dropped.items.iter().collect();
+            items.sort_by(|a, b| {
+                a.id.cmp(&b.id)
+                    .then(a.x.total_cmp(&b.x))
+                    .then(a.y.total_cmp(&b.y))
+                    .then(a.count.cmp(&b.count))
+                    .then(a.flags.cmp(&b.flags))
+            });
+            canonical.write_u32::<LittleEndian>(items.len() as u32).unwrap();
+            for item in items {
+                canonical.write_u16::<LittleEndian>(item.id).unwrap();
+                canonical.write_f32::<LittleEndian>(item.x).unwrap();
+                canonical.write_f32::<LittleEndian>(item.y).unwrap();
+                canonical.write_u8(item.count).unwrap();
+                canonical.write_u8(item.flags).unwrap();
+            }
+        }
+
+        if !mask.ignore_weather {
+            canonical.write_u16::<LittleEndian>(u16::from(&self.base_weather)).unwrap();
+            canonical.write_u16::<LittleEndian>(u16::from(&self.current_weather)).unwrap();
+        }
+
+        Sha256::digest(&canonical).into()
+    }
+}
+
+#[cfg(test)]
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[test]
+fn test_fingerprint_golden() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    assert_eq!(
+        hex(&world.fingerprint()),
+        "7246a2f5142543bd1c50ae45636c0db71468f4eb57824d747ad24f02a46985ee"
+    );
+
+    let empty = World::new(crate::test_item_database());
+    assert_eq!(
+        hex(&empty.fingerprint()),
+        "3977b7e27838e5a41122ba4e7e33a5489c99224bcccc72551c14f2815def4e1d"
+    );
+}
+
+#[test]
+fn test_fingerprint_canonical() {
+    use crate::dropped::DroppedItem;
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let fingerprint = world.fingerprint();
+
+    // only the version differs between these two encodings
+    let tiles = [2, 0, 14, 0, 0, 0, 0, 0];
+    let mut old = World::new(crate::test_item_database());
+    old.parse(&crate::test_world_bytes(0x0F, 1, 1, &tiles)).unwrap();
+    let mut current = World::new(crate::test_item_database());
+    current.parse(&crate::test_world_bytes(crate::version::CURRENT, 1, 1, &tiles)).unwrap();
+    assert_eq!(old.fingerprint(), current.fingerprint());
+
+    let mut other = world.clone();
+    other.name = world.name.to_uppercase();
+    other.dropped.items.reverse();
+    for item in &mut other.dropped.items {
+        item.uid += 1000;
+    }
+    other.dropped.last_dropped_item_uid += 1000;
+    assert_eq!(other.fingerprint(), fingerprint);
+
+    // names that name_matches treats as equal fingerprint the same
+    let mut padded = world.clone();
+    padded.name = format!("{}\0 ", world.name);
+    assert!(padded.name_matches(&world.name));
+    assert_eq!(padded.fingerprint(), fingerprint);
+
+    other.dropped.items.push(DroppedItem {
+        id: 2,
+        x: 1.0,
+        y: 1.0,
+        count: 1,
+        flags: 0,
+        uid: 0,
+    });
+    assert_ne!(other.fingerprint(), fingerprint);
+    let mask = FingerprintMask {
+        ignore_dropped: true,
+        ..Default::default()
+    };
+    assert_eq!(other.fingerprint_ignoring(mask), world.fingerprint_ignoring(mask));
+
+    other.tiles[0].foreground_item_id = 2;
+    assert_ne!(other.fingerprint_ignoring(mask), world.fingerprint_ignoring(mask));
+}
@@ -0,0 +1,321 @@
+use crate::builder::WorldBuilder;
+use crate::flags::TileFlags;
+use crate::tile_extra::TileType;
+use crate::world::{World, BEDROCK_ID};
+use gtitem_r::structs::ItemDatabase;
+use std::sync::{Arc, RwLock};
+
+const DIRT_ID: u16 = 2;
+const LAVA_ID: u16 = 4;
+const MAIN_DOOR_ID: u16 = 6;
+const ROCK_ID: u16 = 10;
+const CAVE_BACKGROUND_ID: u16 = 14;
+const SAND_ID: u16 = 442;
+
+// Columns between value noise lattice points along the surface.
+const SURFACE_PERIOD: u32 = 8;
+// Lattice spacing of the noise that carves caves.
+const CAVE_PERIOD: u32 = 6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldPreset {
+    // dirt over bedrock with some rock and lava, like a freshly created world
+    Standard,
+    // a low surface covered in a few layers of sand
+    Beach,
+    // ground riddled with open caves
+    Cave,
+    // a perfectly level surface of plain dirt
+    Flat,
+}
+
+// Generates worlds the way a fresh world looks: sky, ground with a cave
+// background, bedrock along the bottom and one main door standing on bedrock
+// at the surface. Everything comes from `seed`, the same generator always
+// gives the same world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldGenerator {
+    pub preset: WorldPreset,
+    pub seed: u64,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    // how many tiles the surface may rise above or sink below its base row
+    pub surface_amplitude: u32,
+    // share of ground tiles turned to rock, 0.0 to 1.0
+    pub rock_density: f32,
+    // share of ground carved into caves, 0.0 to 1.0
+    pub cave_density: f32,
+    pub lava_pockets: u32,
+}
+
+impl WorldGenerator {
+    pub fn new(preset: WorldPreset, seed: u64) -> WorldGenerator {
+        let (surface_amplitude, rock_density, cave_density, lava_pockets) = match preset {
+            WorldPreset::Standard => (2, 0.05, 0.0, 4),
+            WorldPreset::Beach => (1, 0.02, 0.0, 0),
+            WorldPreset::Cave => (3, 0.08, 0.35, 8),
+            WorldPreset::Flat => (0, 0.0, 0.0, 0),
+        };
+        WorldGenerator {
+            preset,
+            seed,
+            name: "EXIT".to_string(),
+            width: 100,
+            height: 60,
+            surface_amplitude,
+            rock_density,
+            cave_density,
+            lava_pockets,
+        }
+    }
+
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    pub fn size(mut self, width: u32, height: u32) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    pub fn surface_amplitude(mut self, surface_amplitude: u32) -> Self {
+        self.surface_amplitude = surface_amplitude;
+        self
+    }
+
+    pub fn rock_density(mut self, rock_density: f32) -> Self {
+        self.rock_density = rock_density;
+        self
+    }
+
+    pub fn cave_density(mut self, cave_density: f32) -> Self {
+        self.cave_density = cave_density;
+        self
+    }
+
+    pub fn lava_pockets(mut self, lava_pockets: u32) -> Self {
+        self.lava_pockets = lava_pockets;
+        self
+    }
+
+    // Row of the surface before noise: 24 of 60 in a standard world, lower on
+    // a beach.
+    fn base_surface(&self) -> u32 {
+        match self.preset {
+            WorldPreset::Beach => self.height / 2,
+            _ => self.height * 2 / 5,
+        }
+    }
+
+    fn bedrock_rows(&self) -> u32 {
+        (self.height / 10).clamp(1, 6)
+    }
+
+    // Panics if the world is too small to fit sky, ground and bedrock, at
+    // least 3 wide and 10 high.
+    pub fn generate(&self, item_database: Arc<RwLock<ItemDatabase>>) -> World {
+        assert!(
+            self.width >= 3 && self.height >= 10,
+            "generated worlds must be at least 3x10, got {}x{}",
+            self.width,
+            self.height
+        );
+        let mut world = WorldBuilder::new()
+            .name(&self.name)
+            .size(self.width, self.height)
+            .build(item_database);
+        let bedrock_top = self.height - self.bedrock_rows();
+
+        // surface row per column, kept between the second row and the bedrock
+        let base = self.base_surface() as f32;
+        let amplitude = self.surface_amplitude as f32;
+        let surface: Vec<u32> = (0..self.width)
+            .map(|x| {
+                let offset = (value_noise_1d(self.seed, x as f32 / SURFACE_PERIOD as f32) * 2.0 - 1.0) * amplitude;
+                ((base + offset).round() as u32).clamp(2, bedrock_top - 1)
+            })
+            .collect();
+
+        let mut rng = SplitMix64(self.seed ^ 0x9E37_79B9_7F4A_7C15);
+        for tile in world.tiles.iter_mut() {
+            let (x, y) = (tile.x, tile.y);
+            let surface = surface[x as usize];
+            if y < surface {
+                continue;
+            }
+            tile.background_item_id = CAVE_BACKGROUND_ID;
+            tile.foreground_item_id = if y >= bedrock_top {
+                BEDROCK_ID
+            } else if self.preset == WorldPreset::Beach && y < surface + 4 {
+                SAND_ID
+            } else if y > surface + 1
+                && value_noise_2d(self.seed, x as f32 / CAVE_PERIOD as f32, y as f32 / CAVE_PERIOD as f32)
+                    < self.cave_density
+            {
+                0
+            } else if rng.next_f32() < self.rock_density {
+                ROCK_ID
+            } else {
+                DIRT_ID
+            };
+        }
+
+        // lava blobs of up to 3x2 tiles, never in the top two ground rows
+        for _ in 0..self.lava_pockets {
+            let x = rng.below(self.width);
+            let top = surface[x as usize] + 2;
+            if top >= bedrock_top {
+                continue;
+            }
+            let y = top + rng.below(bedrock_top - top);
+            for (dx, dy) in [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1)] {
+                let (x, y) = (x + dx, y + dy);
+                if x < self.width && y < bedrock_top {
+                    world.get_tile_mut(x, y).unwrap().foreground_item_id = LAVA_ID;
+                }
+            }
+        }
+
+        // the door stands in the sky right above the surface, on bedrock
+        let door_x = 1 + rng.below(self.width - 2);
+        let door_surface = surface[door_x as usize];
+        let door = world.get_tile_mut(door_x, door_surface - 1).unwrap();
+        door.foreground_item_id = MAIN_DOOR_ID;
+        door.flags = TileFlags::from_u16(0x01);
+        door.flags_number = 0x01;
+        door.tile_type = TileType::Door {
+            text: "EXIT".to_string(),
+            unknown_1: 0,
+        };
+        world.get_tile_mut(door_x, door_surface).unwrap().foreground_item_id = BEDROCK_ID;
+        world
+    }
+}
+
+// Small deterministic generator so seeds give the same world on every
+// platform and release.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn next_f32(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    fn below(&mut self, bound: u32) -> u32 {
+        (self.next() % bound as u64) as u32
+    }
+}
+
+// Hash of a lattice point to 0.0..1.0.
+fn lattice(seed: u64, x: i64, y: i64) -> f32 {
+    let mut rng =
+        SplitMix64(seed ^ (x as u64).wrapping_mul(0x9E37_79B9) ^ ((y as u64).wrapping_mul(0x85EB_CA6B) << 32));
+    rng.next_f32()
+}
+
+fn smoothstep(t: f32) -> f32 {
+    t * t * (3.0 - 2.0 * t)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+fn value_noise_1d(seed: u64, x: f32) -> f32 {
+    let x0 = x.floor();
+    let t = smoothstep(x - x0);
+    lerp(lattice(seed, x0 as i64, 0), lattice(seed, x0 as i64 + 1, 0), t)
+}
+
+fn value_noise_2d(seed: u64, x: f32, y: f32) -> f32 {
+    // a different stream than the surface uses
+    let seed = seed.rotate_left(17) ^ 0xD6E8_FEB8_6659_FD93;
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (smoothstep(x - x0), smoothstep(y - y0));
+    let (x0, y0) = (x0 as i64, y0 as i64);
+    let top = lerp(lattice(seed, x0, y0), lattice(seed, x0 + 1, y0), tx);
+    let bottom = lerp(lattice(seed, x0, y0 + 1), lattice(seed, x0 + 1, y0 + 1), tx);
+    lerp(top, bottom, ty)
+}
+
+#[test]
+fn test_generate_presets() {
+    use crate::tile::Tile;
+    use crate::world::MAIN_DOOR_IDS;
+
+    let item_database = crate::test_item_database();
+    let hash = |world: &World| world.region_hash(0, 0, world.width, world.height);
+    let presets = [
+        WorldPreset::Standard,
+        WorldPreset::Beach,
+        WorldPreset::Cave,
+        WorldPreset::Flat,
+    ];
+    let mut hashes = Vec::new();
+    for preset in presets {
+        for seed in [0, 1, 0xDEAD_BEEF] {
+            let generator = WorldGenerator::new(preset, seed);
+            let world = generator.generate(Arc::clone(&item_database));
+            assert_eq!(world.tiles.len(), 6000);
+            assert_eq!(hash(&world), hash(&generator.generate(Arc::clone(&item_database))));
+
+            let doors: Vec<&Tile> = world
+                .tiles
+                .iter()
+                .filter(|tile| MAIN_DOOR_IDS.contains(&tile.foreground_item_id))
+                .collect();
+            assert_eq!(doors.len(), 1, "{:?} {}", preset, seed);
+            let below = world.get_tile(doors[0].x, doors[0].y + 1).unwrap();
+            assert_eq!(below.foreground_item_id, BEDROCK_ID);
+            assert!((0..100).all(|x| world.get_tile(x, 59).unwrap().foreground_item_id == BEDROCK_ID));
+
+            // the writer and parser accept it as it is
+            let mut parsed = World::new(Arc::clone(&item_database));
+            parsed.parse(&world.serialize().unwrap()).unwrap();
+            assert_eq!(hash(&parsed), hash(&world));
+            hashes.push(hash(&world));
+        }
+    }
+    // pinned, a change here means every seed now gives a different world
+    assert_eq!(
+        hashes,
+        [
+            0xb98b5d25dc29b085,
+            0x8b317a6d993edee7,
+            0x2051873403646e6b,
+            0xe6742c7c1d220448,
+            0x3d450dcef640637b,
+            0x22998094b5a061fb,
+            0xce4bfab3c8828503,
+            0x9baba79a777e8f6b,
+            0xc2eb65f5e9f081e6,
+            0x8516ac87efc7802f,
+            0xda53a662987b619f,
+            0xc95382fca4afd7bf,
+        ]
+    );
+
+    let flat = WorldGenerator::new(WorldPreset::Flat, 7).generate(Arc::clone(&item_database));
+    assert!(flat.topology().surface_heights.iter().all(|&y| y == 24));
+    let beach = WorldGenerator::new(WorldPreset::Beach, 7).generate(Arc::clone(&item_database));
+    assert!(beach.tiles.iter().any(|tile| tile.foreground_item_id == SAND_ID));
+    let cave = WorldGenerator::new(WorldPreset::Cave, 7).generate(Arc::clone(&item_database));
+    assert!(cave.topology().deepest_air_pocket > 5);
+
+    let small = WorldGenerator::new(WorldPreset::Standard, 3)
+        .size(3, 10)
+        .lava_pockets(20)
+        .generate(item_database);
+    assert_eq!(small.tiles.len(), 30);
+}
@@ -0,0 +1,175 @@
+use crate::flags::TileFlags;
+use crate::provenance::ProvenanceTag;
+use crate::tile::{Tile, TileDiff};
+use crate::tile_extra::TileType;
+use crate::world::World;
+use std::collections::HashMap;
+use std::ops::{Deref, DerefMut};
+
+// Mutable access to one tile that puts the tile's derived state back in order
+// when the edit ends:
+//   - x and y are restored, a tile's position belongs to the world
+//   - flags and flags_number are synced, whichever one was edited wins (the
+//     flags struct if both were)
+//   - has_extra_data follows tile_type when tile_type was replaced
+// If the edit panics the tile is put back the way it was. While the world
+// tracks provenance a tile whose content changed is tagged.
+pub struct TileGuard<'a> {
+    tile: &'a mut Tile,
+    before: Tile,
+    finished: bool,
+    provenance: Option<(&'a mut HashMap<u32, ProvenanceTag>, ProvenanceTag, u32)>,
+}
+
+impl<'a> TileGuard<'a> {
+    fn new(
+        tile: &'a mut Tile,
+        provenance: Option<(&'a mut HashMap<u32, ProvenanceTag>, ProvenanceTag, u32)>,
+    ) -> TileGuard<'a> {
+        let before = tile.clone();
+        TileGuard {
+            tile,
+            before,
+            finished: false,
+            provenance,
+        }
+    }
+
+    // The tile as it was when the guard was taken.
+    pub fn original(&self) -> &Tile {
+        &self.before
+    }
+
+    // Ends the edit and returns what changed, None when the content is the
+    // same as before.
+    pub fn finish(mut self) -> Option<TileDiff> {
+        self.fix_up();
+        self.finished = true;
+        if self.tile.same_content(&self.before) {
+            return None;
+        }
+        self.record_provenance();
+        Some(TileDiff {
+            x: self.before.x,
+            y: self.before.y,
+            before: self.before.clone(),
+            after: self.tile.clone(),
+        })
+    }
+
+    fn fix_up(&mut self) {
+        let tile = &mut *self.tile;
+        tile.x = self.before.x;
+        tile.y = self.before.y;
+        if tile.flags != self.before.flags {
+            tile.flags_number = tile.flags.to_u16();
+        } else if tile.flags_number != self.before.flags_number {
+            tile.flags = TileFlags::from_u16(tile.flags_number);
+        }
+        if tile.tile_type != self.before.tile_type {
+            tile.flags.has_extra_data = tile.tile_type != TileType::Basic;
+            tile.flags_number = tile.flags.to_u16();
+        }
+    }
+
+    fn record_provenance(&mut self) {
+        if let Some((provenance, source, index)) = self.provenance.take() {
+            provenance.insert(index, source);
+        }
+    }
+}
+
+impl Deref for TileGuard<'_> {
+    type Target = Tile;
+
+    fn deref(&self) -> &Tile {
+        self.tile
+    }
+}
+
+impl DerefMut for TileGuard<'_> {
+    fn deref_mut(&mut self) -> &mut Tile {
+        self.tile
+    }
+}
+
+impl Drop for TileGuard<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        if std::thread::panicking() {
+            // half an edit is worse than none
+            *self.tile = self.before.clone();
+        } else {
+            self.fix_up();
+            if !self.tile.same_content(&self.before) {
+                self.record_provenance();
+            }
+        }
+    }
+}
+
+impl World {
+    pub fn edit_tile(&mut self, x: u32, y: u32) -> Option<TileGuard<'_>> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let index = y * self.width + x;
+        // whether the edit changes anything is only known when it ends, after
+        // the borrow of the world
+        self.mark_dirty(index as usize);
+        let provenance = match (&mut self.provenance, &self.provenance_source) {
+            (Some(provenance), Some(source)) => Some((provenance, source.clone(), index)),
+            _ => None,
+        };
+        self.tiles
+            .get_mut(index as usize)
+            .map(|tile| TileGuard::new(tile, provenance))
+    }
+}
+
+#[test]
+fn test_edit_tile() {
+    use crate::builder::WorldBuilder;
+
+    let mut world = WorldBuilder::new().size(3, 3).build(crate::test_item_database());
+    assert!(world.edit_tile(3, 0).is_none());
+
+    // flags edited through the struct reach flags_number
+    {
+        let mut tile = world.edit_tile(1, 1).unwrap();
+        tile.foreground_item_id = 2;
+        tile.flags.is_wet = true;
+        tile.x = 7;
+    }
+    let tile = world.get_tile(1, 1).unwrap();
+    assert_eq!(tile.foreground_item_id, 2);
+    assert_eq!(tile.flags_number, 0x400);
+    assert_eq!((tile.x, tile.y), (1, 1));
+
+    // and the other way round, with the extra data flag following tile_type
+    let mut tile = world.edit_tile(1, 1).unwrap();
+    tile.flags_number |= 0x800;
+    tile.tile_type = TileType::Sign {
+        text: "hi".to_string(),
+    };
+    let diff = tile.finish().unwrap();
+    assert_eq!(diff.after.flags_number, 0x400 | 0x800 | 0x01);
+    assert_eq!(diff.after.flags, TileFlags::from_u16(diff.after.flags_number));
+    assert_eq!(diff.before.flags_number, 0x400);
+    assert!(world.get_tile(1, 1).unwrap().same_content(&diff.after));
+
+    let tile = world.edit_tile(1, 1).unwrap();
+    assert!(tile.finish().is_none());
+
+    // a panicking edit leaves the tile untouched
+    let before = world.serialize().unwrap();
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let mut tile = world.edit_tile(0, 0).unwrap();
+        tile.foreground_item_id = 554;
+        panic!("edit failed");
+    }));
+    assert!(result.is_err());
+    assert_eq!(world.serialize().unwrap(), before);
+}
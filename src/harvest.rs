@@ -0,0 +1,305 @@
+use crate::cancel::{CancelToken, Cancelled};
+use crate::tile_extra::TileType;
+use crate::world::World;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarvestRouteOptions {
+    // stop after this many tiles, the closest ones along the tour are kept
+    pub max_len: Option<usize>,
+    // only trees of this seed
+    pub seed_item_id: Option<u16>,
+    // full 2-opt sweeps over the tour, each costs O(n^2); 0 keeps the plain
+    // nearest neighbour order
+    pub two_opt_passes: usize,
+}
+
+impl Default for HarvestRouteOptions {
+    fn default() -> Self {
+        HarvestRouteOptions {
+            max_len: None,
+            seed_item_id: None,
+            two_opt_passes: 2,
+        }
+    }
+}
+
+// What World::is_farm looks at, public so callers can apply their own
+// thresholds.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FarmMetrics {
+    pub seed_tiles: usize,
+    // tiles with any foreground, seeds included
+    pub foreground_tiles: usize,
+    // seed_tiles / foreground_tiles
+    pub seed_ratio: f32,
+    pub distinct_seed_items: usize,
+    // share of seeds with another seed right beside them, farms plant in rows
+    pub row_ratio: f32,
+}
+
+// nearest neighbour steps between looks at the cancel token, each is O(n)
+const CANCEL_CHECK_INTERVAL: usize = 64;
+
+fn distance(a: (u32, u32), b: (u32, u32)) -> u32 {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+impl World {
+    // An order to visit every harvestable tree in, starting from `start`:
+    // nearest neighbour by Manhattan distance (ties go to the earlier tile in
+    // reading order), then a bounded number of 2-opt passes. The tour doesn't
+    // return to the start. The same world and options always give the same
+    // route.
+    pub fn plan_harvest_route(&self, start: (u32, u32), options: &HarvestRouteOptions) -> Vec<(u32, u32)> {
+        self.plan_harvest_route_cancellable(start, options, &CancelToken::new())
+            .unwrap()
+    }
+
+    // Like plan_harvest_route, giving up once `cancel` is set. The token is
+    // checked every CANCEL_CHECK_INTERVAL tiles added to the tour and before
+    // each 2-opt row.
+    pub fn plan_harvest_route_cancellable(
+        &self,
+        start: (u32, u32),
+        options: &HarvestRouteOptions,
+        cancel: &CancelToken,
+    ) -> Result<Vec<(u32, u32)>, Cancelled> {
+        let mut remaining: Vec<(u32, u32)> = self
+            .tiles
+            .iter()
+            .filter(|tile| {
+                options
+                    .seed_item_id
+                    .is_none_or(|item_id| tile.special_item_id(&self.options) == item_id)
+            })
+            .filter(|tile| tile.harvestable(&self.options))
+            .map(|tile| (tile.x, tile.y))
+            .collect();
+        let max_len = options.max_len.unwrap_or(remaining.len()).min(remaining.len());
+
+        let mut route = Vec::with_capacity(max_len);
+        let mut current = start;
+        while route.len() < max_len {
+            if route.len() % CANCEL_CHECK_INTERVAL == 0 {
+                cancel.check()?;
+            }
+            let (index, _) = remaining
+                .iter()
+                .enumerate()
+                .min_by_key(|(index, position)| (distance(current, **position), *index))
+                .unwrap();
+            // remove keeps reading order for the tie break
+            current = remaining.remove(index);
+            route.push(current);
+        }
+
+        for _ in 0..options.two_opt_passes {
+            if !two_opt_pass(start, &mut route, cancel)? {
+                break;
+            }
+        }
+        Ok(route)
+    }
+
+    pub fn farm_metrics(&self) -> FarmMetrics {
+        let is_seed = |x: u32, y: u32| {
+            self.get_tile(x, y)
+                .is_some_and(|tile| matches!(tile.tile_type, TileType::Seed { .. }))
+        };
+        let mut metrics = FarmMetrics::default();
+        let mut seed_items = HashSet::new();
+        let mut in_rows = 0;
+        for tile in &self.tiles {
+            if tile.foreground_item_id == 0 {
+                continue;
+            }
+            metrics.foreground_tiles += 1;
+            if matches!(tile.tile_type, TileType::Seed { .. }) {
+                metrics.seed_tiles += 1;
+                seed_items.insert(tile.foreground_item_id);
+                if (tile.x > 0 && is_seed(tile.x - 1, tile.y)) || is_seed(tile.x + 1, tile.y) {
+                    in_rows += 1;
+                }
+            }
+        }
+        metrics.distinct_seed_items = seed_items.len();
+        if metrics.seed_tiles > 0 {
+            metrics.seed_ratio = metrics.seed_tiles as f32 / metrics.foreground_tiles as f32;
+            metrics.row_ratio = in_rows as f32 / metrics.seed_tiles as f32;
+        }
+        metrics
+    }
+
+    // At least 50 trees making up at least a third of the placed blocks,
+    // mostly planted in rows, of at most 5 kinds. See farm_metrics for other
+    // thresholds.
+    pub fn is_farm(&self) -> bool {
+        let metrics = self.farm_metrics();
+        metrics.seed_tiles >= 50
+            && metrics.seed_ratio >= 1.0 / 3.0
+            && metrics.row_ratio >= 0.8
+            && metrics.distinct_seed_items <= 5
+    }
+}
+
+// Reverses any stretch of the path that makes it shorter. Returns whether
+// anything changed.
+fn two_opt_pass(start: (u32, u32), route: &mut [(u32, u32)], cancel: &CancelToken) -> Result<bool, Cancelled> {
+    let mut improved = false;
+    for i in 0..route.len() {
+        cancel.check()?;
+        for j in i + 1..route.len() {
+            let before = if i == 0 { start } else { route[i - 1] };
+            let (first, last) = (route[i], route[j]);
+            let (removed, added) = match route.get(j + 1) {
+                Some(&after) => (
+                    distance(before, first) + distance(last, after),
+                    distance(before, last) + distance(first, after),
+                ),
+                None => (distance(before, first), distance(before, last)),
+            };
+            if added < removed {
+                route[i..=j].reverse();
+                improved = true;
+            }
+        }
+    }
+    Ok(improved)
+}
+
+#[cfg(test)]
+fn test_farm(width: u32, height: u32) -> World {
+    use crate::builder::WorldBuilder;
+    use std::time::Duration;
+
+    let mut world = WorldBuilder::new().size(width, height).build(crate::test_item_database());
+    for tile in world.tiles.iter_mut() {
+        // every other column, with a gap every fifth row so not every tile is ready
+        if tile.x % 2 == 0 {
+            tile.foreground_item_id = if tile.x % 4 == 0 { 3 } else { 15 };
+            tile.tile_type = TileType::Seed {
+                time_passed: 0,
+                item_on_tree: 1,
+                ready_to_harvest: tile.y % 5 != 0,
+                elapsed: Duration::ZERO,
+            };
+        }
+    }
+    world
+}
+
+#[test]
+fn test_plan_harvest_route() {
+    let world = test_farm(20, 10);
+    let ready: HashSet<(u32, u32)> = world
+        .tiles
+        .iter()
+        .filter(|tile| tile.harvestable(&world.options))
+        .map(|tile| (tile.x, tile.y))
+        .collect();
+    assert_eq!(ready.len(), 80);
+
+    let options = HarvestRouteOptions::default();
+    let route = world.plan_harvest_route((0, 0), &options);
+    assert_eq!(route.len(), ready.len());
+    assert_eq!(route.iter().copied().collect::<HashSet<_>>(), ready);
+    assert_eq!(route[0], (0, 1));
+    assert_eq!(world.plan_harvest_route((0, 0), &options), route);
+
+    // 2-opt never makes the tour longer
+    let length = |route: &[(u32, u32)]| -> u32 {
+        std::iter::once(&(0, 0))
+            .chain(route)
+            .zip(route)
+            .map(|(a, b)| distance(*a, *b))
+            .sum()
+    };
+    let greedy = world.plan_harvest_route(
+        (0, 0),
+        &HarvestRouteOptions {
+            two_opt_passes: 0,
+            ..Default::default()
+        },
+    );
+    assert!(length(&route) <= length(&greedy));
+
+    let options = HarvestRouteOptions {
+        max_len: Some(5),
+        seed_item_id: Some(15),
+        ..Default::default()
+    };
+    let route = world.plan_harvest_route((19, 9), &options);
+    assert_eq!(route.len(), 5);
+    assert!(route
+        .iter()
+        .all(|&(x, y)| world.get_tile(x, y).unwrap().foreground_item_id == 15));
+    assert!(World::new(crate::test_item_database())
+        .plan_harvest_route((0, 0), &options)
+        .is_empty());
+}
+
+#[test]
+fn test_farm_metrics() {
+    let mut world = test_farm(20, 10);
+    let metrics = world.farm_metrics();
+    assert_eq!(metrics.seed_tiles, 100);
+    assert_eq!(metrics.foreground_tiles, 100);
+    assert_eq!(metrics.distinct_seed_items, 2);
+    // every other column is empty, so no seed has a neighbour
+    assert_eq!(metrics.row_ratio, 0.0);
+    assert!(!world.is_farm());
+
+    for x in (1..20).step_by(2) {
+        for y in 0..10 {
+            let seed = world.get_tile(x - 1, y).unwrap().clone();
+            let tile = world.get_tile_mut(x, y).unwrap();
+            tile.foreground_item_id = seed.foreground_item_id;
+            tile.tile_type = seed.tile_type;
+        }
+    }
+    assert_eq!(world.farm_metrics().row_ratio, 1.0);
+    assert!(world.is_farm());
+
+    // bury the trees under a build
+    for tile in world.tiles.iter_mut().filter(|tile| tile.y < 8) {
+        tile.foreground_item_id = 2;
+        tile.tile_type = TileType::Basic;
+    }
+    let metrics = world.farm_metrics();
+    assert_eq!((metrics.seed_tiles, metrics.seed_ratio), (40, 0.2));
+    assert!(!world.is_farm());
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    assert_eq!(world.farm_metrics().seed_tiles, 6);
+    assert!(!world.is_farm());
+}
+
+#[test]
+fn test_plan_harvest_route_cancellable() {
+    let world = test_farm(20, 10);
+    let options = HarvestRouteOptions::default();
+    let cancel = CancelToken::new();
+    assert_eq!(
+        world.plan_harvest_route_cancellable((0, 0), &options, &cancel),
+        Ok(world.plan_harvest_route((0, 0), &options))
+    );
+    cancel.cancel();
+    assert_eq!(world.plan_harvest_route_cancellable((0, 0), &options, &cancel), Err(Cancelled));
+
+    // far more 2-opt passes than could ever finish still give up at once
+    let options = HarvestRouteOptions {
+        two_opt_passes: usize::MAX,
+        ..Default::default()
+    };
+    assert_eq!(world.plan_harvest_route_cancellable((0, 0), &options, &cancel), Err(Cancelled));
+
+    // a 2-opt pass stops at its first row, leaving the route as it was
+    let mut route = world.plan_harvest_route((0, 0), &HarvestRouteOptions::default());
+    route.reverse();
+    let before = route.clone();
+    assert_eq!(two_opt_pass((0, 0), &mut route, &cancel), Err(Cancelled));
+    assert_eq!(route, before);
+    assert_eq!(two_opt_pass((0, 0), &mut route, &CancelToken::new()), Ok(true));
+}
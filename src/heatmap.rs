@@ -0,0 +1,230 @@
+use crate::options::ParseOptions;
+use crate::tile::Tile;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use image::{ImageBuffer, Rgba};
+
+// control points of matplotlib's viridis, sampled at even steps
+const VIRIDIS: [Rgba<u8>; 5] = [
+    Rgba([68, 1, 84, 255]),
+    Rgba([59, 82, 139, 255]),
+    Rgba([33, 145, 140, 255]),
+    Rgba([94, 201, 98, 255]),
+    Rgba([253, 231, 37, 255]),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColorRamp {
+    Viridis,
+    // evenly spaced stops from the lowest to the highest value
+    Custom(Vec<Rgba<u8>>),
+}
+
+impl ColorRamp {
+    // `t` is clamped to 0.0..=1.0, colors in between stops are interpolated linearly
+    pub fn sample(&self, t: f32) -> Rgba<u8> {
+        let stops = match self {
+            ColorRamp::Viridis => &VIRIDIS[..],
+            ColorRamp::Custom(stops) => &stops[..],
+        };
+        match stops {
+            [] => Rgba([0, 0, 0, 0]),
+            [only] => *only,
+            _ => {
+                let position = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+                let index = (position as usize).min(stops.len() - 2);
+                let fraction = position - index as f32;
+                let (from, to) = (stops[index], stops[index + 1]);
+                let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * fraction).round() as u8;
+                Rgba([
+                    mix(from[0], to[0]),
+                    mix(from[1], to[1]),
+                    mix(from[2], to[2]),
+                    mix(from[3], to[3]),
+                ])
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapOptions {
+    pub scale: u32,
+    pub ramp: ColorRamp,
+    // fixed (min, max) for the ramp, taken from the values present when None
+    pub range: Option<(f32, f32)>,
+    // drawn for tiles the metric returns None for
+    pub empty_color: Rgba<u8>,
+    // height in pixels of the color scale drawn under the map, 0 leaves it out
+    pub legend_height: u32,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        HeatmapOptions {
+            scale: 8,
+            ramp: ColorRamp::Viridis,
+            range: None,
+            empty_color: Rgba([128, 128, 128, 0]),
+            legend_height: 0,
+        }
+    }
+}
+
+// Maps `value` into 0.0..=1.0 within `min..=max`. A range of zero width puts
+// every value at 0.0 so a uniform metric still renders.
+pub fn normalize(value: f32, min: f32, max: f32) -> f32 {
+    if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+// Tile::grow_progress, as a metric. Pass the world's options so seeds are
+// resolved to the item the parser used.
+pub fn growth_progress(options: &ParseOptions) -> impl Fn(&Tile) -> Option<f32> + '_ {
+    move |tile| tile.grow_progress(options)
+}
+
+// The foreground item's rarity, None for empty tiles and unknown items.
+pub fn rarity(item_database: &ItemDatabase) -> impl Fn(&Tile) -> Option<f32> + '_ {
+    move |tile| {
+        if tile.foreground_item_id == 0 {
+            return None;
+        }
+        item_database
+            .get_item(&(tile.foreground_item_id as u32))
+            .map(|item| item.rarity as f32)
+    }
+}
+
+pub fn render_heatmap(
+    world: &World,
+    metric: impl Fn(&Tile) -> Option<f32>,
+    options: &HeatmapOptions,
+) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let scale = options.scale;
+    let values: Vec<Option<f32>> = world
+        .tiles
+        .iter()
+        .map(|tile| metric(tile).filter(|value| value.is_finite()))
+        .collect();
+    let (min, max) = options.range.unwrap_or_else(|| {
+        values
+            .iter()
+            .flatten()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), &value| {
+                (min.min(value), max.max(value))
+            })
+    });
+
+    let width = world.width * scale;
+    let height = world.height * scale;
+    let mut img = ImageBuffer::from_pixel(width, height + options.legend_height, options.empty_color);
+    for (tile, value) in world.tiles.iter().zip(&values) {
+        let color = match value {
+            Some(value) => options.ramp.sample(normalize(*value, min, max)),
+            None => options.empty_color,
+        };
+        for py in 0..scale {
+            for px in 0..scale {
+                img.put_pixel(tile.x * scale + px, tile.y * scale + py, color);
+            }
+        }
+    }
+
+    // the legend runs from min on the left to max on the right
+    if options.legend_height > 0 {
+        for x in 0..width {
+            let t = if width > 1 { x as f32 / (width - 1) as f32 } else { 0.0 };
+            let color = options.ramp.sample(t);
+            for y in height..height + options.legend_height {
+                img.put_pixel(x, y, color);
+            }
+        }
+    }
+    img
+}
+
+#[test]
+fn test_normalize() {
+    assert_eq!(normalize(5.0, 0.0, 10.0), 0.5);
+    assert_eq!(normalize(-3.0, 0.0, 10.0), 0.0);
+    assert_eq!(normalize(30.0, 0.0, 10.0), 1.0);
+    assert_eq!(normalize(7.0, 7.0, 7.0), 0.0);
+
+    let ramp = ColorRamp::Custom(vec![
+        Rgba([0, 0, 0, 255]),
+        Rgba([200, 100, 0, 255]),
+        Rgba([200, 200, 200, 255]),
+    ]);
+    assert_eq!(ramp.sample(0.0), Rgba([0, 0, 0, 255]));
+    assert_eq!(ramp.sample(0.25), Rgba([100, 50, 0, 255]));
+    assert_eq!(ramp.sample(0.5), Rgba([200, 100, 0, 255]));
+    assert_eq!(ramp.sample(1.0), Rgba([200, 200, 200, 255]));
+    assert_eq!(ramp.sample(2.0), Rgba([200, 200, 200, 255]));
+    assert_eq!(ColorRamp::Viridis.sample(0.0), VIRIDIS[0]);
+    assert_eq!(ColorRamp::Viridis.sample(1.0), VIRIDIS[4]);
+}
+
+#[test]
+fn test_render_heatmap() {
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    // a freshly planted dirt seed, one halfway grown, and plain dirt
+    let item_database = crate::test_item_database();
+    let grow_time = item_database.read().unwrap().get_item(&3).unwrap().grow_time;
+    let mut tiles = Vec::new();
+    for time_passed in [0, grow_time / 2] {
+        tiles.write_u16::<LittleEndian>(3).unwrap();
+        tiles.write_u16::<LittleEndian>(0).unwrap();
+        tiles.write_u16::<LittleEndian>(0).unwrap();
+        tiles.write_u16::<LittleEndian>(0x01).unwrap();
+        tiles.write_u8(4).unwrap();
+        tiles.write_u32::<LittleEndian>(time_passed).unwrap();
+        tiles.write_u8(0).unwrap();
+    }
+    tiles.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+    let mut world = World::new(item_database);
+    world
+        .parse(&crate::test_world_bytes(crate::version::CURRENT, 3, 1, &tiles))
+        .unwrap();
+
+    let progress: Vec<_> = world.tiles.iter().map(growth_progress(&world.options)).collect();
+    assert_eq!(progress[0], Some(0.0));
+    assert_eq!(progress[1], Some((grow_time / 2) as f32 / grow_time as f32));
+    assert_eq!(progress[2], None);
+
+    let ramp = ColorRamp::Custom(vec![Rgba([0, 0, 0, 255]), Rgba([255, 255, 255, 255])]);
+    let options = HeatmapOptions {
+        scale: 2,
+        ramp: ramp.clone(),
+        legend_height: 3,
+        ..Default::default()
+    };
+    let img = render_heatmap(&world, growth_progress(&world.options), &options);
+    assert_eq!(img.dimensions(), (6, 5));
+    // with the range taken from the data the halfway seed is the maximum
+    assert_eq!(*img.get_pixel(0, 0), ramp.sample(0.0));
+    assert_eq!(*img.get_pixel(3, 1), ramp.sample(1.0));
+    assert_eq!(*img.get_pixel(4, 0), options.empty_color);
+    assert_eq!(*img.get_pixel(0, 4), ramp.sample(0.0));
+    assert_eq!(*img.get_pixel(5, 2), ramp.sample(1.0));
+
+    let options = HeatmapOptions {
+        range: Some((0.0, 1.0)),
+        ..options
+    };
+    let img = render_heatmap(&world, growth_progress(&world.options), &options);
+    assert_eq!(*img.get_pixel(3, 1), ramp.sample(progress[1].unwrap()));
+
+    let item_database = world.item_database.read().unwrap();
+    let rarity = rarity(&item_database);
+    assert_eq!(
+        rarity(&world.tiles[2]),
+        Some(item_database.get_item(&2).unwrap().rarity as f32)
+    );
+    let img = render_heatmap(&world, rarity, &HeatmapOptions::default());
+    assert_eq!(img.dimensions(), (24, 8));
+}
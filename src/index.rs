@@ -0,0 +1,169 @@
+use crate::error::WorldParseError;
+use crate::flags::TileFlags;
+use crate::limits;
+use crate::options::{Endian, ParseOptions};
+use crate::tile::{Rect, Tile};
+use crate::world::{ParseLayout, World};
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use gtitem_r::structs::ItemDatabase;
+use std::io::Cursor;
+use std::sync::{Arc, RwLock};
+
+// Byte offsets of every tile in a world blob, found by stepping over each
+// record without decoding it. Only the header is parsed; tiles are decoded on
+// demand with parse_region, which honours the same ParseOptions as a full parse.
+#[derive(Debug, Clone)]
+pub struct WorldIndex<'a> {
+    data: &'a [u8],
+    // header fields only, its tiles stay empty
+    world: World,
+    pub offsets: Vec<usize>,
+    // where the tile section ends and the post-tile pad starts
+    pub tiles_end: usize,
+}
+
+impl<'a> WorldIndex<'a> {
+    pub fn build(data: &'a [u8], item_database: Arc<RwLock<ItemDatabase>>) -> Result<WorldIndex<'a>, WorldParseError> {
+        WorldIndex::build_with_options(data, item_database, ParseOptions::default())
+    }
+
+    pub fn build_with_options(
+        data: &'a [u8],
+        item_database: Arc<RwLock<ItemDatabase>>,
+        options: ParseOptions,
+    ) -> Result<WorldIndex<'a>, WorldParseError> {
+        let mut world = World::new(item_database);
+        world.options = options;
+        match world.options.endianness {
+            Endian::Little => WorldIndex::read::<LittleEndian>(data, world),
+            Endian::Big => WorldIndex::read::<BigEndian>(data, world),
+        }
+    }
+
+    fn read<B: ByteOrder>(data: &'a [u8], mut world: World) -> Result<WorldIndex<'a>, WorldParseError> {
+        let mut cursor = Cursor::new(data);
+        world.read_header_fields::<B>(&mut cursor, &mut ParseLayout::default())?;
+
+        let mut offsets = Vec::with_capacity(limits::tile_capacity(
+            world.tile_count,
+            data.len() - cursor.position() as usize,
+        ));
+        for count in 0..world.tile_count {
+            offsets.push(cursor.position() as usize);
+            let (x, y) = (count % world.width, count / world.width);
+            world.read_raw_tile::<B>(&mut cursor, x, y)?;
+        }
+
+        Ok(WorldIndex {
+            data,
+            tiles_end: cursor.position() as usize,
+            world,
+            offsets,
+        })
+    }
+
+    pub fn version(&self) -> u16 {
+        self.world.version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.world.name
+    }
+
+    pub fn width(&self) -> u32 {
+        self.world.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.world.height
+    }
+
+    pub fn tile_offset(&self, x: u32, y: u32) -> Option<usize> {
+        if x >= self.world.width || y >= self.world.height {
+            return None;
+        }
+        self.offsets.get((y * self.world.width + x) as usize).copied()
+    }
+
+    // Decodes the tiles inside the rect in row-major order, clipped to the world.
+    pub fn parse_region(&self, rect: Rect) -> Result<Vec<Tile>, WorldParseError> {
+        let mut world = self.world.clone();
+        let x_end = rect.x.saturating_add(rect.width).min(world.width);
+        let y_end = rect.y.saturating_add(rect.height).min(world.height);
+        for y in rect.y..y_end {
+            for x in rect.x..x_end {
+                let offset = match self.tile_offset(x, y) {
+                    Some(offset) => offset,
+                    None => continue,
+                };
+                let mut cursor = Cursor::new(self.data);
+                cursor.set_position(offset as u64);
+                let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, Arc::clone(&world.item_database));
+                world.update_tile(tile, &mut cursor, false)?;
+            }
+        }
+        Ok(world.tiles)
+    }
+}
+
+#[test]
+fn test_world_index() {
+    let data = std::fs::read("world.dat").unwrap();
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    let layout = world.parse_with_layout(&data).unwrap();
+
+    let index = WorldIndex::build(&data, Arc::clone(&item_database)).unwrap();
+    assert_eq!((index.name(), index.width(), index.height()), ("PIWD", 100, 60));
+    assert_eq!(index.offsets.len(), world.tiles.len());
+    assert_eq!(index.tiles_end, layout.tiles.unwrap().end);
+
+    let rect = Rect::new(45, 40, 10, 8);
+    let region = index.parse_region(rect).unwrap();
+    let expected: Vec<&Tile> = world
+        .tiles
+        .iter()
+        .filter(|tile| rect.contains(tile.x, tile.y))
+        .collect();
+    assert_eq!(region.len(), 80);
+    for (tile, expected) in region.iter().zip(expected) {
+        assert!(tile.same_content(expected));
+        assert_eq!((tile.x, tile.y), (expected.x, expected.y));
+    }
+    assert_eq!(index.parse_region(Rect::new(98, 58, 5, 5)).unwrap().len(), 4);
+
+    assert!(WorldIndex::build(&data[..index.offsets[3000] + 3], Arc::clone(&item_database)).is_err());
+
+    // a forged tile count only reserves what the data could hold
+    let forged = crate::test_world_bytes(crate::version::CURRENT, u16::MAX as u32, u16::MAX as u32, &[0; 8]);
+    assert!(WorldIndex::build(&forged, item_database).is_err());
+}
+
+#[test]
+fn test_world_index_matches_parser() {
+    use crate::options::CborDetection;
+    use crate::writer;
+
+    // every extra type the parser knows, in each byte order
+    for endianness in [Endian::Little, Endian::Big] {
+        let mut world = writer::test_sample_world();
+        world.options.endianness = endianness;
+        world.options.cbor_detection = CborDetection::ExplicitList([14].into_iter().collect());
+        let data = world.serialize().unwrap();
+        let mut parsed = World::new(crate::test_item_database());
+        parsed.options = world.options.clone();
+        parsed.parse(&data).unwrap();
+
+        let index =
+            WorldIndex::build_with_options(&data, crate::test_item_database(), world.options.clone()).unwrap();
+        let mut offset = index.offsets[0];
+        for (tile, tile_offset) in parsed.tiles.iter().zip(&index.offsets) {
+            assert_eq!(*tile_offset, offset, "{:?}", tile.tile_type);
+            offset += tile.serialized_size();
+        }
+        assert_eq!(index.tiles_end, offset);
+
+        let region = index.parse_region(Rect::new(0, 0, world.width, world.height)).unwrap();
+        assert!(region.iter().zip(&parsed.tiles).all(|(a, b)| a.same_content(b)));
+    }
+}
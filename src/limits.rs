@@ -0,0 +1,191 @@
+#[cfg(feature = "serde")]
+use serde::de::{Deserialize, Deserializer, Error, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use std::{fmt, marker::PhantomData};
+
+use crate::error::WorldParseError;
+
+// Caps on lengths taken from untrusted input, shared by the binary parser and
+// the serde impls so a world is rejected the same way in either form. Real
+// worlds stay far below them; they only stop a forged length from allocating
+// gigabytes or looping for seconds.
+
+// entries in one list inside a tile's extra data, such as a lock's access uids
+pub const MAX_LIST_LEN: usize = 1 << 16;
+// bytes in a tile's CBOR blob
+pub const MAX_CBOR_LEN: usize = 1 << 20;
+// tiles ParseOptions::recover_truncated pads a cut off world out to
+pub const MAX_RECOVERED_TILES: usize = 1 << 22;
+// bytes ParseOptions::skip_bad_tiles searches past a bad tile for the next one
+pub const MAX_RESYNC_BYTES: usize = 1 << 16;
+// bytes World::parse_from_reader takes from a stream
+pub const MAX_WORLD_LEN: usize = 1 << 26;
+
+// Tile records to reserve room for up front: every record takes at least 8
+// bytes, so a forged tile count reserves no more than `remaining` could hold.
+pub(crate) fn tile_capacity(tile_count: u32, remaining: usize) -> usize {
+    (tile_count as usize).min(remaining / 8)
+}
+
+pub(crate) fn check_len(field: &'static str, len: u64, max: usize, x: u32, y: u32) -> Result<(), WorldParseError> {
+    if len > max as u64 {
+        return Err(WorldParseError::LengthLimit { field, len, max, x, y });
+    }
+    Ok(())
+}
+
+// Reads a sequence and fails as soon as it passes `max` entries, before the
+// rest of the input is looked at.
+#[cfg(feature = "serde")]
+struct BoundedSeq<T> {
+    max: usize,
+    marker: PhantomData<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Visitor<'de> for BoundedSeq<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a list of at most {} entries", self.max)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Vec<T>, A::Error> {
+        if let Some(len) = seq.size_hint().filter(|&len| len > self.max) {
+            return Err(A::Error::invalid_length(len, &self));
+        }
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            if items.len() == self.max {
+                return Err(A::Error::invalid_length(self.max + 1, &self));
+            }
+            items.push(item);
+        }
+        Ok(items)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn bounded_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(BoundedSeq {
+        max: MAX_LIST_LEN,
+        marker: PhantomData,
+    })
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn bounded_cbor<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Cbor(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for Cbor {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bytes = deserializer.deserialize_seq(BoundedSeq {
+                max: MAX_CBOR_LEN,
+                marker: PhantomData,
+            })?;
+            Ok(Cbor(bytes))
+        }
+    }
+
+    Ok(Option::<Cbor>::deserialize(deserializer)?.map(|cbor| cbor.0))
+}
+
+#[test]
+fn test_binary_limits() {
+    use crate::options::CborDetection;
+    use crate::world::World;
+    use crate::{test_world_bytes, version};
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    let tile = |foreground: u16, flags: u16| {
+        let mut tiles = Vec::new();
+        tiles.write_u16::<LittleEndian>(foreground).unwrap();
+        tiles.write_u16::<LittleEndian>(0).unwrap();
+        tiles.write_u16::<LittleEndian>(0).unwrap();
+        tiles.write_u16::<LittleEndian>(flags).unwrap();
+        tiles
+    };
+
+    // a dirt block claiming 4 GiB of CBOR fails before anything is allocated
+    let mut tiles = tile(2, 0);
+    tiles.write_u32::<LittleEndian>(u32::MAX).unwrap();
+    let mut world = World::new(crate::test_item_database());
+    world.options.cbor_detection = CborDetection::ExplicitList([2].into_iter().collect());
+    assert!(matches!(
+        world.parse(&test_world_bytes(version::CURRENT, 1, 1, &tiles)),
+        Err(WorldParseError::LengthLimit {
+            field: "cbor_data",
+            len: 0xFFFF_FFFF,
+            max: MAX_CBOR_LEN,
+            x: 0,
+            y: 0
+        })
+    ));
+    let options = world.options.clone();
+    let data = test_world_bytes(version::CURRENT, 1, 1, &tiles);
+    assert!(matches!(
+        World::parse_raw_tiles(&data, options),
+        Err(WorldParseError::LengthLimit { field: "cbor_data", .. })
+    ));
+
+    // and a lock claiming four billion access uids
+    let mut tiles = tile(242, 0x01);
+    tiles.write_u8(3).unwrap();
+    tiles.write_u8(0).unwrap();
+    tiles.write_u32::<LittleEndian>(1).unwrap();
+    tiles.write_u32::<LittleEndian>(u32::MAX).unwrap();
+    let mut world = World::new(crate::test_item_database());
+    assert!(matches!(
+        world.parse(&test_world_bytes(version::CURRENT, 1, 1, &tiles)),
+        Err(WorldParseError::LengthLimit {
+            field: "access_uids",
+            ..
+        })
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_limits() {
+    use crate::tile::Tile;
+    use crate::tile_extra::TileType;
+    use std::time::{Duration, Instant};
+
+    let start = Instant::now();
+    let lock = |uids: usize| {
+        serde_json::json!({
+            "Lock": {
+                "settings": serde_json::to_value(crate::flags::LockSettings::default()).unwrap(),
+                "owner_uid": 1,
+                "access_count": uids,
+                "access_uids": vec![7; uids],
+                "minimum_level": 0,
+                "unknown_1": [0, 0, 0, 0, 0, 0, 0],
+            }
+        })
+    };
+    let accepted: TileType = serde_json::from_value(lock(MAX_LIST_LEN)).unwrap();
+    assert!(matches!(accepted, TileType::Lock { access_uids, .. } if access_uids.len() == MAX_LIST_LEN));
+    let json = serde_json::to_string(&lock(MAX_LIST_LEN + 1)).unwrap();
+    let err = serde_json::from_str::<TileType>(&json).unwrap_err();
+    assert!(err.to_string().contains("at most 65536 entries"), "{}", err);
+
+    let item_database = crate::test_item_database();
+    let mut tile = Tile::new(2, 0, 0, Default::default(), 0, 0, 0, item_database);
+    tile.cbor_data = Some(vec![0xA0]);
+    let mut json = serde_json::to_value(&tile).unwrap();
+    assert_eq!(serde_json::from_value::<Tile>(json.clone()).unwrap().cbor_data, Some(vec![0xA0]));
+    json["cbor_data"] = serde_json::json!(vec![0; MAX_CBOR_LEN + 1]);
+    let err = serde_json::from_value::<Tile>(json.clone()).unwrap_err();
+    assert!(err.to_string().contains("at most 1048576 entries"), "{}", err);
+    json["cbor_data"] = serde_json::Value::Null;
+    assert_eq!(serde_json::from_value::<Tile>(json).unwrap().cbor_data, None);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
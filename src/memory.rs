@@ -0,0 +1,177 @@
+use crate::tile::Tile;
+use crate::tile_extra::TileType;
+use crate::world::World;
+use std::mem::size_of;
+
+fn vec_size<T>(vec: &Vec<T>) -> usize {
+    vec.capacity() * size_of::<T>()
+}
+
+impl TileType {
+    // Heap bytes owned by the variant's strings and vectors. No wildcard arm,
+    // so a new variant doesn't compile until it's accounted for here.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            TileType::Door { text, .. }
+            | TileType::Sign { text }
+            | TileType::Mannequin { text, .. }
+            | TileType::HearthMonitor { player_name: text, .. }
+            | TileType::Crystal { unknown_1: text }
+            | TileType::CrimeInProgress { unknown_1: text, .. }
+            | TileType::CountryFlag { country: text }
+            | TileType::SilkWorm { name: text, .. }
+            | TileType::PaintingEasel { label: text, .. }
+            | TileType::PetBattleCage { label: text, .. }
+            | TileType::FishWallMount { label: text, .. }
+            | TileType::Portrait { label: text, .. }
+            | TileType::AudioRack { note: text, .. } => text.capacity(),
+            TileType::Mailbox {
+                unknown_1,
+                unknown_2,
+                unknown_3,
+                ..
+            }
+            | TileType::Bulletin {
+                unknown_1,
+                unknown_2,
+                unknown_3,
+                ..
+            }
+            | TileType::DonationBox {
+                unknown_1,
+                unknown_2,
+                unknown_3,
+                ..
+            } => unknown_1.capacity() + unknown_2.capacity() + unknown_3.capacity(),
+            TileType::Lock { access_uids, .. } | TileType::VipEntrance { access_uids, .. } => vec_size(access_uids),
+            TileType::FishTankPort { fishes, .. } => vec_size(fishes),
+            TileType::SewingMachine { bolt_id_list: list }
+            | TileType::ContainmentFieldPowerNode { unknown_1: list, .. }
+            | TileType::InfinityWeatherMachine {
+                weather_machine_list: list,
+                ..
+            } => vec_size(list),
+            TileType::PetTrainer { name, pets_id, .. } => name.capacity() + vec_size(pets_id),
+            TileType::StorageBlock { items } => vec_size(items),
+            TileType::CookingOven { ingredients, .. } => vec_size(ingredients),
+            TileType::CyBot { command_datas, .. } => vec_size(command_datas),
+            TileType::Basic
+            | TileType::Seed { .. }
+            | TileType::Dice { .. }
+            | TileType::ChemicalSource { .. }
+            | TileType::AchievementBlock { .. }
+            | TileType::BunnyEgg { .. }
+            | TileType::GamePack { .. }
+            | TileType::GameGenerator { .. }
+            | TileType::XenoniteCrystal { .. }
+            | TileType::PhoneBooth { .. }
+            | TileType::DisplayBlock { .. }
+            | TileType::VendingMachine { .. }
+            | TileType::GivingTree { .. }
+            | TileType::WeatherMachine { .. }
+            | TileType::DataBedrock { .. }
+            | TileType::Spotlight
+            | TileType::SolarCollector { .. }
+            | TileType::Forge { .. }
+            | TileType::SteamOrgan { .. }
+            | TileType::LobsterTrap
+            | TileType::SteamEngine { .. }
+            | TileType::LockBot { .. }
+            | TileType::SpiritStorageUnit { .. }
+            | TileType::Shelf { .. }
+            | TileType::ChallangeTimer
+            | TileType::GuildWeatherMachine { .. }
+            | TileType::FossilPrepStation { .. }
+            | TileType::DnaExtractor
+            | TileType::Howler
+            | TileType::ChemsynthTank { .. }
+            | TileType::GeigerCharger { .. }
+            | TileType::AdventureBegins
+            | TileType::TombRobber
+            | TileType::BalloonOMatic { .. }
+            | TileType::TrainingPort { .. }
+            | TileType::ItemSucker { .. }
+            | TileType::GuildItem
+            | TileType::Growscan { .. }
+            | TileType::SpiritBoard { .. }
+            | TileType::StormyCloud { .. }
+            | TileType::TemporaryPlatform { .. }
+            | TileType::SafeVault
+            | TileType::AngelicCountingCloud { .. }
+            | TileType::PineappleGuzzler
+            | TileType::KrakenGalaticBlock { .. }
+            | TileType::FriendsEntrance { .. } => 0,
+        }
+    }
+}
+
+impl Tile {
+    // Heap bytes owned by the extra data and the CBOR payload.
+    pub fn heap_size(&self) -> usize {
+        self.tile_type.heap_size() + self.cbor_data.as_ref().map_or(0, |data| data.capacity())
+    }
+
+    // The tile itself plus what it owns on the heap. The shared item database
+    // isn't counted.
+    pub fn memory_usage(&self) -> usize {
+        size_of::<Tile>() + self.heap_size()
+    }
+}
+
+// Bytes a world holds, by where they live. Vectors and strings count their
+// capacity, since that's what is allocated.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MemoryBreakdown {
+    // the tile vector's allocation, unused capacity included
+    pub tiles_fixed: usize,
+    // strings, vectors and CBOR payloads owned by tiles
+    pub tile_heap: usize,
+    pub dropped: usize,
+    // the World struct, name, warnings and losses
+    pub other: usize,
+    pub total: usize,
+}
+
+impl World {
+    // Approximate bytes held by this world. Allocator overhead and the shared
+    // item database are left out.
+    pub fn memory_usage(&self) -> MemoryBreakdown {
+        let tiles_fixed = vec_size(&self.tiles);
+        let tile_heap = self.tiles.iter().map(Tile::heap_size).sum();
+        let dropped = vec_size(&self.dropped.items);
+        let other = size_of::<World>() + self.name.capacity() + vec_size(&self.warnings) + vec_size(&self.losses);
+        MemoryBreakdown {
+            tiles_fixed,
+            tile_heap,
+            dropped,
+            other,
+            total: tiles_fixed + tile_heap + dropped + other,
+        }
+    }
+}
+
+#[test]
+fn test_memory_usage() {
+    let mut world = World::new(crate::test_item_database());
+    let empty = world.memory_usage();
+    assert_eq!(empty.total, empty.other);
+    assert!(empty.total >= size_of::<World>() && empty.total < size_of::<World>() + 64);
+
+    // the tile vector dominates a parsed world, strings and lists add a little
+    let data = std::fs::read("world.dat").unwrap();
+    world.parse(&data).unwrap();
+    let usage = world.memory_usage();
+    assert_eq!(usage.tiles_fixed, world.tiles.capacity() * size_of::<Tile>());
+    assert_eq!(usage.total, usage.tiles_fixed + usage.tile_heap + usage.dropped + usage.other);
+    assert!(usage.tile_heap > 0 && usage.tile_heap < data.len());
+    assert!(usage.dropped >= world.dropped.items.len() * size_of::<crate::dropped::DroppedItem>());
+
+    // one huge sign outweighs every other string in the world
+    let bare = usage.tile_heap;
+    let tile = &mut world.tiles[0];
+    let before = tile.memory_usage();
+    tile.tile_type = TileType::Sign { text: "x".repeat(100_000) };
+    assert!((100_000..100_100).contains(&(tile.memory_usage() - before)));
+    assert_eq!(tile.heap_size(), 100_000);
+    assert!(world.memory_usage().tile_heap > bare * 10);
+}
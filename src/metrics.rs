@@ -0,0 +1,118 @@
+use crate::world::World;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+// Counters and section timings from the last parse, collected when
+// ParseOptions::collect_metrics is set. CBOR payloads are copied, not decoded,
+// so their cost is part of tile_time; cbor_bytes says how much of it there was.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseMetrics {
+    pub total_bytes: usize,
+    pub tile_count: usize,
+    // tiles per extra type id, tiles without extra data aren't listed
+    pub extra_types: BTreeMap<u8, usize>,
+    pub cbor_tiles: usize,
+    pub cbor_bytes: usize,
+    pub dropped_items: usize,
+    pub warnings: usize,
+    pub losses: usize,
+    pub header_time: Duration,
+    pub tile_time: Duration,
+    pub dropped_time: Duration,
+    // weather, trailing bytes and the quick fingerprint
+    pub tail_time: Duration,
+}
+
+impl ParseMetrics {
+    pub fn total_time(&self) -> Duration {
+        self.header_time + self.tile_time + self.dropped_time + self.tail_time
+    }
+
+    // Counts come from the parsed world afterwards, so the tile loop does no
+    // extra work for them.
+    pub(crate) fn collect(world: &World, total_bytes: usize, timer: &SectionTimer) -> ParseMetrics {
+        let mut metrics = ParseMetrics {
+            total_bytes,
+            tile_count: world.tiles.len(),
+            dropped_items: world.dropped.items.len(),
+            warnings: world.warnings.len(),
+            losses: world.losses.len(),
+            header_time: timer.laps[0],
+            tile_time: timer.laps[1],
+            dropped_time: timer.laps[2],
+            tail_time: timer.laps[3],
+            ..Default::default()
+        };
+        for tile in &world.tiles {
+            if let Some(extra_type) = tile.tile_type.extra_type_id() {
+                *metrics.extra_types.entry(extra_type).or_default() += 1;
+            }
+            if let Some(cbor_data) = &tile.cbor_data {
+                metrics.cbor_tiles += 1;
+                metrics.cbor_bytes += cbor_data.len();
+            }
+        }
+        metrics
+    }
+}
+
+// Times consecutive parse sections. Disabled, lap only checks the Option.
+pub(crate) struct SectionTimer {
+    last: Option<Instant>,
+    laps: [Duration; 4],
+    section: usize,
+}
+
+impl SectionTimer {
+    pub(crate) fn start(enabled: bool) -> SectionTimer {
+        SectionTimer {
+            last: enabled.then(Instant::now),
+            laps: [Duration::ZERO; 4],
+            section: 0,
+        }
+    }
+
+    pub(crate) fn lap(&mut self) {
+        if let Some(last) = self.last {
+            let now = Instant::now();
+            if let Some(lap) = self.laps.get_mut(self.section) {
+                *lap = now - last;
+            }
+            self.last = Some(now);
+            self.section += 1;
+        }
+    }
+}
+
+impl World {
+    pub fn metrics(&self) -> Option<&ParseMetrics> {
+        self.metrics.as_ref()
+    }
+}
+
+#[test]
+fn test_parse_metrics() {
+    let data = std::fs::read("world.dat").unwrap();
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&data).unwrap();
+    assert!(world.metrics().is_none());
+
+    world.options.collect_metrics = true;
+    world.parse(&data).unwrap();
+    let metrics = world.metrics().unwrap().clone();
+    assert_eq!(metrics.total_bytes, data.len());
+    assert_eq!(metrics.tile_count, 6000);
+    assert_eq!(metrics.extra_types.get(&4), Some(&6));
+    assert_eq!(metrics.extra_types.get(&3), Some(&1));
+    assert!(metrics.extra_types.values().sum::<usize>() < metrics.tile_count);
+    assert_eq!((metrics.cbor_tiles, metrics.cbor_bytes), (1, 39));
+    assert_eq!(metrics.dropped_items, world.dropped.items.len());
+    assert_eq!(metrics.warnings, world.warnings.len());
+    assert_eq!(metrics.losses, 3);
+    assert!(metrics.tile_time > std::time::Duration::ZERO);
+    assert!(metrics.total_time() >= metrics.tile_time);
+
+    // a failed parse leaves nothing behind
+    assert!(world.parse(&data[..100]).is_err());
+    assert!(world.metrics().is_none());
+}
@@ -0,0 +1,265 @@
+use crate::tile::Tile;
+use crate::tile_extra::TileType;
+use crate::world::World;
+use std::ops::{BitAnd, BitOr, Not};
+
+// One bit per tile, each row packed into its own run of u64 words so row
+// lookups never straddle rows. Padding bits past `width` are always zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bitmap {
+    width: u32,
+    height: u32,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl Bitmap {
+    pub fn new(width: u32, height: u32) -> Bitmap {
+        let words_per_row = (width as usize).div_ceil(64);
+        Bitmap {
+            width,
+            height,
+            words_per_row,
+            words: vec![0; words_per_row * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn position(&self, x: u32, y: u32) -> Option<(usize, u64)> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        let word = y as usize * self.words_per_row + x as usize / 64;
+        Some((word, 1 << (x % 64)))
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        match self.position(x, y) {
+            Some((word, mask)) => self.words[word] & mask != 0,
+            None => false,
+        }
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, value: bool) {
+        if let Some((word, mask)) = self.position(x, y) {
+            if value {
+                self.words[word] |= mask;
+            } else {
+                self.words[word] &= !mask;
+            }
+        }
+    }
+
+    pub fn count_ones(&self) -> u32 {
+        self.words.iter().map(|word| word.count_ones()).sum()
+    }
+
+    fn combine(&self, other: &Bitmap, op: impl Fn(u64, u64) -> u64) -> Bitmap {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "bitmaps must have the same dimensions"
+        );
+        Bitmap {
+            width: self.width,
+            height: self.height,
+            words_per_row: self.words_per_row,
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| op(*a, *b))
+                .collect(),
+        }
+    }
+
+    fn row_mask(&self, word_in_row: usize) -> u64 {
+        let used = self.width as usize - word_in_row * 64;
+        if used >= 64 {
+            u64::MAX
+        } else {
+            (1 << used) - 1
+        }
+    }
+}
+
+impl BitAnd for &Bitmap {
+    type Output = Bitmap;
+
+    fn bitand(self, other: &Bitmap) -> Bitmap {
+        self.combine(other, |a, b| a & b)
+    }
+}
+
+impl BitOr for &Bitmap {
+    type Output = Bitmap;
+
+    fn bitor(self, other: &Bitmap) -> Bitmap {
+        self.combine(other, |a, b| a | b)
+    }
+}
+
+impl Not for &Bitmap {
+    type Output = Bitmap;
+
+    fn not(self) -> Bitmap {
+        let mut words = Vec::with_capacity(self.words.len());
+        for (index, word) in self.words.iter().enumerate() {
+            words.push(!word & self.row_mask(index % self.words_per_row));
+        }
+        Bitmap {
+            width: self.width,
+            height: self.height,
+            words_per_row: self.words_per_row,
+            words,
+        }
+    }
+}
+
+// Snapshot of the world taken by `World::occupancy`. It is not kept in sync
+// with later edits, is_current tells when to take a new one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OccupancyGrid {
+    pub foreground: Bitmap,
+    pub background: Bitmap,
+    pub solid: Bitmap,
+    // lock tiles and tiles whose parent_block_index points at a lock
+    pub locked: Bitmap,
+    // World::revision when the grid was taken
+    revision: u64,
+}
+
+impl OccupancyGrid {
+    pub fn has_foreground(&self, x: u32, y: u32) -> bool {
+        self.foreground.get(x, y)
+    }
+
+    pub fn has_background(&self, x: u32, y: u32) -> bool {
+        self.background.get(x, y)
+    }
+
+    pub fn is_solid(&self, x: u32, y: u32) -> bool {
+        self.solid.get(x, y)
+    }
+
+    pub fn is_locked(&self, x: u32, y: u32) -> bool {
+        self.locked.get(x, y)
+    }
+
+    pub fn is_empty(&self, x: u32, y: u32) -> bool {
+        !self.has_foreground(x, y) && !self.has_background(x, y)
+    }
+
+    // False once `world` changed through a mutation API (edit_tile,
+    // get_tile_mut, parse, ...) since the grid was taken. Writes straight to
+    // world.tiles aren't seen.
+    pub fn is_current(&self, world: &World) -> bool {
+        self.revision == world.revision
+    }
+}
+
+impl World {
+    pub fn occupancy(&self) -> OccupancyGrid {
+        let mut grid = OccupancyGrid {
+            foreground: Bitmap::new(self.width, self.height),
+            background: Bitmap::new(self.width, self.height),
+            solid: Bitmap::new(self.width, self.height),
+            locked: Bitmap::new(self.width, self.height),
+            revision: self.revision,
+        };
+
+        let item_database = self.item_database.read().unwrap();
+        for tile in &self.tiles {
+            let (x, y) = (tile.x, tile.y);
+            if tile.foreground_item_id != 0 {
+                grid.foreground.set(x, y, true);
+                let solid = item_database
+                    .get_item(&(tile.foreground_item_id as u32))
+                    .is_some_and(|item| item.collision_type == 1);
+                grid.solid.set(x, y, solid);
+            }
+            grid.background.set(x, y, tile.background_item_id != 0);
+            let is_lock = |tile: &Tile| matches!(tile.tile_type, TileType::Lock { .. });
+            let locked = is_lock(tile)
+                || (tile.parent_block_index != 0
+                    && self.tiles.get(tile.parent_block_index as usize).is_some_and(is_lock));
+            grid.locked.set(x, y, locked);
+        }
+        grid
+    }
+}
+
+#[test]
+fn test_bitmap_odd_widths() {
+    for width in [1, 63, 64, 65, 100, 130] {
+        let mut bitmap = Bitmap::new(width, 3);
+        bitmap.set(0, 0, true);
+        bitmap.set(width - 1, 1, true);
+        bitmap.set(width / 2, 2, true);
+        bitmap.set(width, 0, true);
+
+        assert!(bitmap.get(0, 0));
+        assert!(bitmap.get(width - 1, 1));
+        assert!(bitmap.get(width / 2, 2));
+        assert!(!bitmap.get(width, 0));
+        assert_eq!(bitmap.count_ones(), 3);
+
+        let inverted = !&bitmap;
+        assert_eq!(inverted.count_ones(), width * 3 - bitmap.count_ones());
+        assert_eq!((&inverted & &bitmap).count_ones(), 0);
+        assert_eq!((&inverted | &bitmap).count_ones(), width * 3);
+
+        bitmap.set(width - 1, 1, false);
+        assert!(!bitmap.get(width - 1, 1));
+    }
+}
+
+#[test]
+fn test_world_occupancy() {
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    // dirt on cave background, a blank tile, then a ladder
+    let mut tiles = Vec::new();
+    for (foreground, background) in [(2u16, 14u16), (0, 0), (554, 0)] {
+        tiles.write_u16::<LittleEndian>(foreground).unwrap();
+        tiles.write_u16::<LittleEndian>(background).unwrap();
+        tiles.extend_from_slice(&[0; 4]);
+    }
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&crate::test_world_bytes(crate::version::CURRENT, 3, 1, &tiles)).unwrap();
+
+    let grid = world.occupancy();
+    assert!(grid.is_solid(0, 0) && grid.has_background(0, 0));
+    assert!(grid.is_empty(1, 0));
+    assert!(grid.has_foreground(2, 0) && !grid.is_solid(2, 0));
+    assert_eq!(grid.foreground.count_ones(), 2);
+    assert_eq!((&grid.foreground & &!&grid.solid).count_ones(), 1);
+    assert_eq!(grid.locked.count_ones(), 0);
+    assert!(grid.is_current(&world));
+
+    // a parent only locks a tile when it is a lock
+    world.get_tile_mut(0, 0).unwrap().parent_block_index = 2;
+    assert!(!grid.is_current(&world));
+    let grid = world.occupancy();
+    assert!(grid.is_current(&world));
+    assert_eq!(grid.locked.count_ones(), 0);
+    world.get_tile_mut(2, 0).unwrap().tile_type = TileType::Lock {
+        settings: Default::default(),
+        owner_uid: 1,
+        access_count: 0,
+        access_uids: Vec::new(),
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    let grid = world.occupancy();
+    assert!(grid.is_locked(0, 0) && grid.is_locked(2, 0) && !grid.is_locked(1, 0));
+    world.parse(&crate::test_world_bytes(crate::version::CURRENT, 3, 1, &tiles)).unwrap();
+    assert!(!grid.is_current(&world));
+}
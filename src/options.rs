@@ -0,0 +1,74 @@
+use gtitem_r::structs::Item;
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UnknownItemPolicy {
+    // stop parsing and set is_error
+    #[default]
+    Error,
+    // keep the tile with its real id and record a warning
+    Placeholder,
+    // replace the unknown id with the given one and record a warning
+    Remap(u16),
+}
+
+// Items whose tiles carry a u32 length prefixed CBOR blob after the extra data.
+// The default is an ExplicitList of KNOWN_CBOR_ITEMS.
+#[derive(Debug, Clone)]
+pub enum CborDetection {
+    // items with an .xml file_name plus CBOR_QUIRK_ITEMS, records a warning each time it fires
+    Heuristic,
+    ExplicitList(HashSet<u32>),
+    Callback(fn(&Item) -> bool),
+}
+
+impl Default for CborDetection {
+    fn default() -> Self {
+        CborDetection::ExplicitList(KNOWN_CBOR_ITEMS.iter().map(|item_id| *item_id as u32).collect())
+    }
+}
+
+// CBOR carrying items the .xml heuristic misses
+pub const CBOR_QUIRK_ITEMS: [u16; 2] = [8642, 15376];
+
+// items known to carry CBOR, 14666 as in world.dat
+pub const KNOWN_CBOR_ITEMS: [u16; 3] = [14666, CBOR_QUIRK_ITEMS[0], CBOR_QUIRK_ITEMS[1]];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    pub unknown_items: UnknownItemPolicy,
+    pub cbor_detection: CborDetection,
+    pub endianness: Endian,
+    // fill World::metrics with counters and section timings
+    pub collect_metrics: bool,
+    // a world cut off inside its tiles or after them parses anyway: missing
+    // tiles become blank Basic tiles, the dropped items and weather whatever
+    // was read of them, with a ParseWarning::Truncated and is_error set
+    pub recover_truncated: bool,
+    // a tile record that fails to read becomes a blank tile with a
+    // ParseWarning::SkippedTile, and reading picks up at the next record
+    // that looks sound; is_error is set. The error is returned as usual when
+    // no such record is found.
+    pub skip_bad_tiles: bool,
+    // worlds older than this fail with WorldParseError::UnsupportedVersion.
+    // None accepts every version, no capture has shown where support ends.
+    pub min_version: Option<u16>,
+    // key a tile's extra data and CBOR by its background item when the
+    // foreground is blank, see TileLayer::of_special. No capture has such a
+    // tile, so by default they belong to the foreground item.
+    pub background_special_items: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct SerializeOptions {
+    // fail with the loss reasons rather than write output that differs from
+    // what was parsed
+    pub require_exact: bool,
+}
@@ -0,0 +1,236 @@
+use crate::error::{SerializeError, WorldParseError};
+use crate::flags::TileFlags;
+use crate::options::Endian;
+use crate::tile::Tile;
+use crate::world::World;
+use crate::writer;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fmt;
+use std::io::{self, Cursor, Read};
+use std::sync::Arc;
+
+// Patch layout, little endian throughout:
+//   magic      b"GTWP"
+//   format     u16, PATCH_FORMAT
+//   version    u16, world version the tile records are encoded for
+//   width      u32
+//   height     u32
+//   count      u32
+//   count times:
+//     x, y     u32 each
+//     len      u32
+//     record   len bytes, the tile exactly as it appears in a world file
+// Tile records carry their CBOR payload when the tile has one, so both sides
+// need the same cbor_detection for the records to line up.
+pub const PATCH_MAGIC: &[u8; 4] = b"GTWP";
+pub const PATCH_FORMAT: u16 = 1;
+
+#[derive(Debug)]
+pub enum PatchError {
+    DimensionMismatch { expected: (u32, u32), found: (u32, u32) },
+    InvalidHeader,
+    TileOutOfBounds { x: u32, y: u32 },
+    // the tile decoded to a different length than its record
+    RecordLength { x: u32, y: u32 },
+    Parse(WorldParseError),
+    Serialize(SerializeError),
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::DimensionMismatch { expected, found } => write!(
+                f,
+                "patch is for a {}x{} world, this one is {}x{}",
+                expected.0, expected.1, found.0, found.1
+            ),
+            PatchError::InvalidHeader => write!(f, "not a world patch"),
+            PatchError::TileOutOfBounds { x, y } => write!(f, "patched tile ({}, {}) is outside the world", x, y),
+            PatchError::RecordLength { x, y } => {
+                write!(f, "patched tile ({}, {}) doesn't match its record length", x, y)
+            }
+            PatchError::Parse(err) => write!(f, "failed to read patched tile: {}", err),
+            PatchError::Serialize(err) => write!(f, "failed to encode a changed tile: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PatchError::Parse(err) => Some(err),
+            PatchError::Serialize(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<WorldParseError> for PatchError {
+    fn from(err: WorldParseError) -> Self {
+        PatchError::Parse(err)
+    }
+}
+
+impl From<SerializeError> for PatchError {
+    fn from(err: SerializeError) -> Self {
+        PatchError::Serialize(err)
+    }
+}
+
+impl From<io::Error> for PatchError {
+    fn from(err: io::Error) -> Self {
+        PatchError::Parse(WorldParseError::Io(err))
+    }
+}
+
+impl World {
+    // Every tile whose content or CBOR payload differs from `base`, as a patch
+    // that turns `base` into this world. Both worlds must be the same size.
+    pub fn serialize_patch(&self, base: &World) -> Result<Vec<u8>, PatchError> {
+        if (base.width, base.height) != (self.width, self.height) || base.tiles.len() != self.tiles.len() {
+            return Err(PatchError::DimensionMismatch {
+                expected: (self.width, self.height),
+                found: (base.width, base.height),
+            });
+        }
+
+        let changed: Vec<&Tile> = self
+            .tiles
+            .iter()
+            .zip(&base.tiles)
+            .filter(|(tile, base)| !tile.same_content(base) || tile.cbor_data != base.cbor_data)
+            .map(|(tile, _)| tile)
+            .collect();
+
+        let mut out = PATCH_MAGIC.to_vec();
+        out.write_u16::<LittleEndian>(PATCH_FORMAT).unwrap();
+        out.write_u16::<LittleEndian>(self.version).unwrap();
+        out.write_u32::<LittleEndian>(self.width).unwrap();
+        out.write_u32::<LittleEndian>(self.height).unwrap();
+        out.write_u32::<LittleEndian>(changed.len() as u32).unwrap();
+        for tile in changed {
+            out.write_u32::<LittleEndian>(tile.x).unwrap();
+            out.write_u32::<LittleEndian>(tile.y).unwrap();
+            let record = writer::tile_record(tile, &self.options)?;
+            out.write_u32::<LittleEndian>(record.len() as u32).unwrap();
+            out.extend_from_slice(&record);
+        }
+        Ok(out)
+    }
+
+    // Replaces the tiles listed in the patch. Every record is decoded before
+    // anything is written, so a bad patch leaves the world untouched.
+    pub fn apply_patch_bytes(&mut self, patch: &[u8]) -> Result<(), PatchError> {
+        let mut data = Cursor::new(patch);
+        let mut magic = [0; 4];
+        data.read_exact(&mut magic)?;
+        if &magic != PATCH_MAGIC || data.read_u16::<LittleEndian>()? != PATCH_FORMAT {
+            return Err(PatchError::InvalidHeader);
+        }
+        let patch_version = data.read_u16::<LittleEndian>()?;
+        let width = data.read_u32::<LittleEndian>()?;
+        let height = data.read_u32::<LittleEndian>()?;
+        if (width, height) != (self.width, self.height) {
+            return Err(PatchError::DimensionMismatch {
+                expected: (width, height),
+                found: (self.width, self.height),
+            });
+        }
+
+        // decoding goes through a scratch world so the records are read with
+        // the patch's version and any warnings stay out of this world
+        let mut scratch = World::new(Arc::clone(&self.item_database));
+        scratch.version = patch_version;
+        scratch.width = self.width;
+        scratch.height = self.height;
+        scratch.options = self.options.clone();
+        scratch.options.endianness = Endian::Little;
+
+        let count = data.read_u32::<LittleEndian>()?;
+        for _ in 0..count {
+            let x = data.read_u32::<LittleEndian>()?;
+            let y = data.read_u32::<LittleEndian>()?;
+            let len = data.read_u32::<LittleEndian>()? as u64;
+            // a world whose tile vector is shorter than width * height has
+            // no tile to replace at the end
+            let index = y as usize * self.width as usize + x as usize;
+            if x >= self.width || y >= self.height || index >= self.tiles.len() {
+                return Err(PatchError::TileOutOfBounds { x, y });
+            }
+            let end = data.position() + len;
+            if end > patch.len() as u64 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+
+            let mut record = Cursor::new(&patch[data.position() as usize..end as usize]);
+            let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, Arc::clone(&self.item_database));
+            scratch.update_tile(tile, &mut record, false)?;
+            if record.position() != len {
+                return Err(PatchError::RecordLength { x, y });
+            }
+            data.set_position(end);
+        }
+
+        for tile in scratch.tiles {
+            let index = tile.y as usize * self.width as usize + tile.x as usize;
+            self.tiles[index] = tile;
+            self.record_change(index);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_patch_round_trip() {
+    use crate::options::CborDetection;
+
+    let mut base = crate::writer::test_sample_world();
+    base.options.cbor_detection = CborDetection::ExplicitList([14].into_iter().collect());
+    let mut world = base.clone();
+    assert_eq!(world.serialize_patch(&base).unwrap().len(), 20);
+
+    world.tiles[0].foreground_item_id = 554;
+    world.tiles[1] = Tile::new(0, 0, 0, TileFlags::default(), 0, 1, 0, Arc::clone(&world.item_database));
+    world.tiles[2].tile_type = crate::tile_extra::TileType::Sign {
+        text: "patched".to_string(),
+    };
+    let last = world.tiles.len() - 1;
+    world.tiles[last].cbor_data = Some(vec![0xA0]);
+    let patch = world.serialize_patch(&base).unwrap();
+
+    let mut patched = base.clone();
+    patched.apply_patch_bytes(&patch).unwrap();
+    for (patched, expected) in patched.tiles.iter().zip(&world.tiles) {
+        assert!(patched.same_content(expected));
+        assert_eq!(patched.cbor_data, expected.cbor_data);
+    }
+    assert_eq!(patched.serialize().unwrap(), world.serialize().unwrap());
+    assert_eq!(patched.serialize_patch(&world).unwrap().len(), 20);
+
+    // a truncated patch fails without touching anything
+    let mut untouched = base.clone();
+    assert!(untouched.apply_patch_bytes(&patch[..patch.len() - 2]).is_err());
+    assert_eq!(untouched.serialize().unwrap(), base.serialize().unwrap());
+    untouched.tiles.pop();
+    assert!(matches!(
+        untouched.apply_patch_bytes(&patch),
+        Err(PatchError::TileOutOfBounds { .. })
+    ));
+    assert!(untouched.tiles[0].same_content(&base.tiles[0]));
+    assert!(matches!(
+        untouched.apply_patch_bytes(b"GTWX"),
+        Err(PatchError::InvalidHeader)
+    ));
+
+    let smaller = crate::builder::WorldBuilder::new()
+        .size(3, 3)
+        .build(Arc::clone(&base.item_database));
+    assert!(matches!(
+        world.serialize_patch(&smaller),
+        Err(PatchError::DimensionMismatch { .. })
+    ));
+    assert!(matches!(
+        smaller.clone().apply_patch_bytes(&patch),
+        Err(PatchError::DimensionMismatch { .. })
+    ));
+}
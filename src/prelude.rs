@@ -0,0 +1,13 @@
+// Glob-import this to get the types most programs need:
+// `use gtworld_r::prelude::*;`
+
+pub use crate::builder::WorldBuilder;
+pub use crate::dropped::{Dropped, DroppedItem};
+pub use crate::error::{ParseWarning, WorldParseError};
+pub use crate::flags::{LockSettings, TileFlags};
+pub use crate::options::ParseOptions;
+pub use crate::tile::{Rect, Tile};
+pub use crate::tile_extra::TileType;
+pub use crate::weather::WeatherType;
+pub use crate::world::World;
+pub use gtitem_r::structs::ItemDatabase;
@@ -0,0 +1,148 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::world::World;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+// Where a tile's current content came from, e.g. "patch:build-42" or
+// "bot:replant". Every tile tagged while a source is tracked shares the
+// source's allocation, so tagging a whole world costs a pointer per tile.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "String", into = "String"))]
+pub struct ProvenanceTag(Arc<str>);
+
+impl ProvenanceTag {
+    pub fn new(name: &str) -> ProvenanceTag {
+        ProvenanceTag(Arc::from(name))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for ProvenanceTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for ProvenanceTag {
+    fn from(name: String) -> Self {
+        ProvenanceTag::new(&name)
+    }
+}
+
+impl From<ProvenanceTag> for String {
+    fn from(tag: ProvenanceTag) -> Self {
+        tag.0.to_string()
+    }
+}
+
+impl World {
+    // Tags every tile changed from now on by apply_patch_bytes, apply_event,
+    // edit_tile, set_flags_in_rect and paint_rect with `source`, until tracking
+    // is stopped or another source is set. Earlier tags are kept.
+    pub fn track_provenance(&mut self, source: &str) {
+        self.provenance.get_or_insert_with(HashMap::new);
+        self.provenance_source = Some(ProvenanceTag::new(source));
+    }
+
+    // Stops tagging, what was tagged so far stays queryable.
+    pub fn stop_tracking_provenance(&mut self) {
+        self.provenance_source = None;
+    }
+
+    // The source of the last tracked change to the tile, None if it hasn't
+    // changed while tracking was on.
+    pub fn provenance_of(&self, x: u32, y: u32) -> Option<&ProvenanceTag> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.provenance.as_ref()?.get(&(y * self.width + x))
+    }
+
+    // Called by the mutation APIs for every tile they changed.
+    pub(crate) fn record_change(&mut self, index: usize) {
+        if let (Some(provenance), Some(source)) = (&mut self.provenance, &self.provenance_source) {
+            provenance.insert(index as u32, source.clone());
+        }
+        self.mark_dirty(index);
+    }
+}
+
+#[test]
+fn test_provenance() {
+    use crate::builder::WorldBuilder;
+    use crate::edit::PaintColor;
+    use crate::event::WorldEvent;
+    use crate::tile::Rect;
+
+    let item_database = crate::test_item_database();
+    let mut base = WorldBuilder::new().size(4, 3).build(Arc::clone(&item_database));
+    base.edit_tile(0, 0).unwrap().foreground_item_id = 2;
+    // untracked edits leave no trace
+    assert_eq!(base.provenance, None);
+    assert_eq!(base.provenance_of(0, 0), None);
+
+    let mut fragment = base.clone();
+    for x in 1..3 {
+        fragment.get_tile_mut(x, 1).unwrap().foreground_item_id = 10;
+    }
+    let patch = fragment.serialize_patch(&base).unwrap();
+
+    let mut world = base.clone();
+    world.track_provenance("paste:fragment");
+    world.apply_patch_bytes(&patch).unwrap();
+    world.track_provenance("edit:paint");
+    assert_eq!(world.paint_rect(Rect::new(2, 1, 2, 1), PaintColor::Red, true), 1);
+    world.track_provenance("edit:hand");
+    world.edit_tile(3, 2).unwrap().foreground_item_id = 2;
+    // tiles hold the tracked source itself, not a copy of its name
+    let source = world.provenance_source.as_ref().unwrap();
+    assert!(Arc::ptr_eq(&source.0, &world.provenance_of(3, 2).unwrap().0));
+    // an edit that changes nothing keeps the old tag
+    world.edit_tile(1, 1).unwrap().foreground_item_id = 10;
+    world.stop_tracking_provenance();
+    world.edit_tile(0, 2).unwrap().foreground_item_id = 2;
+
+    let tag = |x, y| world.provenance_of(x, y).map(ProvenanceTag::as_str);
+    assert_eq!(tag(1, 1), Some("paste:fragment"));
+    assert_eq!(tag(2, 1), Some("edit:paint"));
+    assert_eq!(tag(3, 2), Some("edit:hand"));
+    assert_eq!(tag(0, 0), None);
+    assert_eq!(tag(0, 2), None);
+    assert_eq!(tag(4, 0), None);
+    assert_eq!(world.provenance.as_ref().unwrap().len(), 3);
+
+    assert_eq!(world.provenance_of(3, 2), Some(&ProvenanceTag::new("edit:hand")));
+
+    let mut tile = world.get_tile(0, 1).unwrap().clone();
+    tile.foreground_item_id = 442;
+    world.track_provenance("event:server");
+    let event = WorldEvent::TileChanged { x: 0, y: 1, tile };
+    world.apply_event(event, &item_database.read().unwrap()).unwrap();
+    assert_eq!(
+        world.provenance_of(0, 1).map(ProvenanceTag::as_str),
+        Some("event:server")
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_value(&world).unwrap();
+        assert_eq!(json["provenance"]["4"], "event:server");
+        assert!(serde_json::to_value(&base).unwrap().get("provenance").is_none());
+    }
+
+    // the table never reaches the game format and is cleared by a parse
+    assert_eq!(world.serialize().unwrap().len(), {
+        let mut untracked = world.clone();
+        untracked.provenance = None;
+        untracked.serialize().unwrap().len()
+    });
+    world.parse(&world.serialize().unwrap()).unwrap();
+    assert_eq!(world.provenance.as_ref().map(HashMap::len), Some(0));
+}
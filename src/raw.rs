@@ -0,0 +1,241 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::WorldParseError;
+use crate::flags::TileFlags;
+use crate::limits;
+use crate::options::{Endian, ParseOptions};
+use crate::tile::{Tile, TileLayer};
+use crate::world::{ParseLayout, World};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use gtitem_r::structs::ItemDatabase;
+use std::io::{self, Cursor};
+use std::sync::{Arc, RwLock};
+
+// The fixed fields every tile record starts with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RawTile {
+    pub foreground_item_id: u16,
+    pub background_item_id: u16,
+    pub parent_block_index: u16,
+    pub flags_number: u16,
+}
+
+impl RawTile {
+    pub fn flags(&self) -> TileFlags {
+        TileFlags::from_u16(self.flags_number)
+    }
+
+    // See Tile::special_item_id.
+    pub fn special_item_id(&self, options: &ParseOptions) -> u16 {
+        match TileLayer::of_special(self.foreground_item_id, self.background_item_id, options) {
+            TileLayer::Foreground => self.foreground_item_id,
+            TileLayer::Background => self.background_item_id,
+        }
+    }
+}
+
+// Ids and flags of every tile, without extra data, CBOR or item lookups.
+// Full tiles can still be decoded one at a time from the kept record offsets.
+#[derive(Debug, Clone)]
+pub struct RawTileGrid<'a> {
+    data: &'a [u8],
+    // header fields only, its tiles stay empty
+    world: World,
+    pub tiles: Vec<RawTile>,
+    pub offsets: Vec<usize>,
+}
+
+impl World {
+    // Reads the header and steps over every tile record, keeping only the
+    // fixed fields. No item database is needed with the default
+    // options.cbor_detection, which lists the CBOR items outright; Heuristic
+    // detection finds no .xml items without one, use
+    // RawTileGrid::parse_with_database for it.
+    pub fn parse_raw_tiles(data: &[u8], options: ParseOptions) -> Result<RawTileGrid<'_>, WorldParseError> {
+        RawTileGrid::read(data, Arc::new(RwLock::new(ItemDatabase::new())), options)
+    }
+
+    // Checks that every tile record in a serialized world `data` fits the
+    // buffer and has a known extra type, without decoding any of them. Only
+    // this world's item database and options are used, not its tiles.
+    pub fn validate_bytes(&self, data: &[u8]) -> Result<(), WorldParseError> {
+        RawTileGrid::read(data, Arc::clone(&self.item_database), self.options.clone()).map(|_| ())
+    }
+
+    // Reads one record's fixed fields and moves past the rest of it, with the
+    // same lengths the full parser reads.
+    pub(crate) fn read_raw_tile<B: ByteOrder>(
+        &self,
+        data: &mut Cursor<&[u8]>,
+        x: u32,
+        y: u32,
+    ) -> Result<RawTile, WorldParseError> {
+        let tile = RawTile {
+            foreground_item_id: data.read_u16::<B>()?,
+            background_item_id: data.read_u16::<B>()?,
+            parent_block_index: data.read_u16::<B>()?,
+            flags_number: data.read_u16::<B>()?,
+        };
+        let flags = tile.flags();
+        if flags.has_parent {
+            data.set_position(data.position() + 2);
+        }
+        if flags.has_extra_data {
+            let extra_tile_type = data.read_u8()?;
+            self.skip_extra_tile_data::<B>(data, extra_tile_type, tile.special_item_id(&self.options), x, y)?;
+        }
+        if self.detects_cbor(tile.special_item_id(&self.options)) {
+            let cbor_len = data.read_u32::<B>()?;
+            limits::check_len("cbor_data", cbor_len as u64, limits::MAX_CBOR_LEN, x, y)?;
+            data.set_position(data.position() + cbor_len as u64);
+        }
+        // skipping never reads, so a truncated record only shows here
+        if data.position() > data.get_ref().len() as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(tile)
+    }
+}
+
+impl<'a> RawTileGrid<'a> {
+    // Like World::parse_raw_tiles, with a database for Heuristic CBOR detection.
+    pub fn parse_with_database(
+        data: &'a [u8],
+        item_database: Arc<RwLock<ItemDatabase>>,
+        options: ParseOptions,
+    ) -> Result<RawTileGrid<'a>, WorldParseError> {
+        RawTileGrid::read(data, item_database, options)
+    }
+
+    fn read(
+        data: &'a [u8],
+        item_database: Arc<RwLock<ItemDatabase>>,
+        options: ParseOptions,
+    ) -> Result<RawTileGrid<'a>, WorldParseError> {
+        let mut world = World::new(item_database);
+        world.options = options;
+        match world.options.endianness {
+            Endian::Little => RawTileGrid::read_tiles::<LittleEndian>(data, world),
+            Endian::Big => RawTileGrid::read_tiles::<BigEndian>(data, world),
+        }
+    }
+
+    fn read_tiles<B: ByteOrder>(data: &'a [u8], mut world: World) -> Result<RawTileGrid<'a>, WorldParseError> {
+        let mut cursor = Cursor::new(data);
+        world.read_header_fields::<B>(&mut cursor, &mut ParseLayout::default())?;
+
+        let capacity = limits::tile_capacity(world.tile_count, data.len() - cursor.position() as usize);
+        let mut tiles = Vec::with_capacity(capacity);
+        let mut offsets = Vec::with_capacity(capacity);
+        for count in 0..world.tile_count {
+            offsets.push(cursor.position() as usize);
+            let (x, y) = (count % world.width, count / world.width);
+            tiles.push(world.read_raw_tile::<B>(&mut cursor, x, y)?);
+        }
+
+        Ok(RawTileGrid {
+            data,
+            world,
+            tiles,
+            offsets,
+        })
+    }
+
+    pub fn version(&self) -> u16 {
+        self.world.version
+    }
+
+    pub fn name(&self) -> &str {
+        &self.world.name
+    }
+
+    pub fn width(&self) -> u32 {
+        self.world.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.world.height
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Option<RawTile> {
+        if x >= self.world.width || y >= self.world.height {
+            return None;
+        }
+        self.tiles.get((y * self.world.width + x) as usize).copied()
+    }
+
+    // Decodes the full tile at (x, y) from its record. Extra data and unknown
+    // id checks need the item database, so it's passed in here.
+    pub fn tile(
+        &self,
+        x: u32,
+        y: u32,
+        item_database: Arc<RwLock<ItemDatabase>>,
+    ) -> Result<Option<Tile>, WorldParseError> {
+        if x >= self.world.width || y >= self.world.height {
+            return Ok(None);
+        }
+        let offset = match self.offsets.get((y * self.world.width + x) as usize) {
+            Some(offset) => *offset,
+            None => return Ok(None),
+        };
+
+        let mut world = self.world.clone();
+        world.item_database = Arc::clone(&item_database);
+        let mut cursor = Cursor::new(self.data);
+        cursor.set_position(offset as u64);
+        let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, item_database);
+        world.update_tile(tile, &mut cursor, false)?;
+        Ok(world.tiles.pop())
+    }
+}
+
+#[test]
+fn test_parse_raw_tiles() {
+    use crate::options::CborDetection;
+
+    let data = std::fs::read("world.dat").unwrap();
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    world.parse(&data).unwrap();
+
+    // without a database the heuristic can't see that 14666 is an .xml item
+    // and loses its CBOR payload, an explicit list doesn't need the lookup
+    let heuristic = ParseOptions {
+        cbor_detection: CborDetection::Heuristic,
+        ..Default::default()
+    };
+    assert!(World::parse_raw_tiles(&data, heuristic).is_err());
+    assert!(World::parse_raw_tiles(&data, ParseOptions::default()).is_ok());
+    let options = ParseOptions {
+        cbor_detection: CborDetection::ExplicitList([14666].into_iter().collect()),
+        ..Default::default()
+    };
+    let grid = World::parse_raw_tiles(&data, options.clone()).unwrap();
+    assert_eq!((grid.name(), grid.width(), grid.height()), ("PIWD", 100, 60));
+    assert_eq!(grid.tiles.len(), world.tiles.len());
+    for (raw, tile) in grid.tiles.iter().zip(&world.tiles) {
+        assert_eq!(raw.foreground_item_id, tile.foreground_item_id);
+        assert_eq!(raw.background_item_id, tile.background_item_id);
+        assert_eq!(raw.flags(), tile.flags);
+    }
+    assert_eq!(grid.get(100, 0), None);
+
+    // the main door, decoded in full from its record
+    let door = grid.get(49, 44).unwrap();
+    assert_eq!(door.foreground_item_id, 6);
+    let tile = grid.tile(49, 44, Arc::clone(&item_database)).unwrap().unwrap();
+    assert!(tile.same_content(world.get_tile(49, 44).unwrap()));
+    assert!(grid.tile(0, 60, Arc::clone(&item_database)).unwrap().is_none());
+
+    assert!(World::parse_raw_tiles(&data[..grid.offsets[3000] + 3], options.clone()).is_err());
+
+    // a forged tile count only reserves what the data could hold
+    let forged = crate::test_world_bytes(crate::version::CURRENT, u16::MAX as u32, u16::MAX as u32, &[0; 8]);
+    assert!(World::parse_raw_tiles(&forged, ParseOptions::default()).is_err());
+
+    let grid = RawTileGrid::parse_with_database(&data, item_database, ParseOptions::default()).unwrap();
+    assert_eq!(grid.get(69, 47).unwrap().foreground_item_id, 14666);
+}
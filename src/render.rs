@@ -0,0 +1,478 @@
+use crate::cancel::CancelToken;
+use crate::color;
+use crate::options::ParseOptions;
+use crate::tile::{ChangeKind, Tile, TileDiff};
+use crate::tile_extra::TileType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use image::{ImageBuffer, Rgba};
+use std::fmt;
+
+pub const SKY_COLOR: Rgba<u8> = Rgba(color::SKY);
+// drawn for ids the item database doesn't know about
+pub const UNKNOWN_ITEM_COLOR: Rgba<u8> = Rgba(color::UNKNOWN_ITEM);
+// blended over trees that are ready to harvest
+pub const READY_GLOW_COLOR: Rgba<u8> = Rgba([255, 240, 120, 255]);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenderError {
+    DimensionMismatch {
+        expected: (u32, u32),
+        found: (u32, u32),
+    },
+    Cancelled,
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderError::DimensionMismatch { expected, found } => write!(
+                f,
+                "image is {}x{} but the world renders to {}x{}",
+                found.0, found.1, expected.0, expected.1
+            ),
+            RenderError::Cancelled => write!(f, "the render was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+fn item_color(item_database: &ItemDatabase, item_id: u16) -> Rgba<u8> {
+    Rgba(color::item_rgba(item_database, item_id))
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LayerSelect {
+    Foreground,
+    Background,
+    // the foreground where there is one, the background behind it elsewhere
+    #[default]
+    Both,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RenderOptions {
+    pub layers: LayerSelect,
+    // draw seed_visuals over trees: a glow on ready ones and a bar in the
+    // fruit's color along the top of trees that carry fruit
+    pub seed_overlays: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedVisuals {
+    pub fruit_count: u8,
+    pub ready: bool,
+    // a seed's id is its fruit's plus one
+    pub fruit_item_id: u16,
+}
+
+// What a renderer needs to draw a tree. Where the client places each fruit
+// isn't known, so no positions are given; a renderer lays out fruit_count
+// fruits its own way. The seed is special_item_id under `options`, the item
+// the parser read it for.
+pub fn seed_visuals(tile: &Tile, item_database: &ItemDatabase, options: &ParseOptions) -> Option<SeedVisuals> {
+    let (item_on_tree, ready_to_harvest, elapsed) = match tile.tile_type {
+        TileType::Seed {
+            item_on_tree,
+            ready_to_harvest,
+            elapsed,
+            ..
+        } => (item_on_tree, ready_to_harvest, elapsed),
+        _ => return None,
+    };
+    let seed_item_id = tile.special_item_id(options);
+    let ready = ready_to_harvest
+        || item_database
+            .get_item(&(seed_item_id as u32))
+            .is_some_and(|item| elapsed.as_secs() >= item.grow_time as u64);
+    Some(SeedVisuals {
+        fruit_count: item_on_tree,
+        ready,
+        fruit_item_id: seed_item_id.saturating_sub(1),
+    })
+}
+
+fn draw_seed_overlay(
+    img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+    tile: &Tile,
+    scale: u32,
+    item_database: &ItemDatabase,
+    options: &ParseOptions,
+) {
+    let Some(visuals) = seed_visuals(tile, item_database, options) else {
+        return;
+    };
+    if visuals.ready {
+        for py in 0..scale {
+            for px in 0..scale {
+                let (x, y) = (tile.x * scale + px, tile.y * scale + py);
+                let pixel = *img.get_pixel(x, y);
+                img.put_pixel(x, y, blend(pixel, READY_GLOW_COLOR, 96));
+            }
+        }
+    }
+    if visuals.fruit_count > 0 {
+        let fruit_color = item_color(item_database, visuals.fruit_item_id);
+        for px in 0..scale {
+            img.put_pixel(tile.x * scale + px, tile.y * scale, fruit_color);
+        }
+    }
+}
+
+pub fn tile_color(tile: &Tile, item_database: &ItemDatabase) -> Rgba<u8> {
+    tile_color_with(tile, item_database, LayerSelect::Both)
+}
+
+// Tiles with nothing on the selected layers show the sky.
+pub fn tile_color_with(tile: &Tile, item_database: &ItemDatabase, layers: LayerSelect) -> Rgba<u8> {
+    let (foreground, background) = match layers {
+        LayerSelect::Foreground => (tile.foreground_item_id, 0),
+        LayerSelect::Background => (0, tile.background_item_id),
+        LayerSelect::Both => (tile.foreground_item_id, tile.background_item_id),
+    };
+    Rgba(color::tile_rgba(item_database, foreground, background))
+}
+
+fn fill_tile(img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>, x: u32, y: u32, scale: u32, color: Rgba<u8>) {
+    for px in 0..scale {
+        for py in 0..scale {
+            img.put_pixel(x * scale + px, y * scale + py, color);
+        }
+    }
+}
+
+impl World {
+    pub fn render(&self, scale: u32) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        self.render_cancellable(scale, &CancelToken::new()).unwrap()
+    }
+
+    // Like render, giving up with RenderError::Cancelled once `cancel` is set.
+    // The token is checked before each row.
+    pub fn render_cancellable(
+        &self,
+        scale: u32,
+        cancel: &CancelToken,
+    ) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, RenderError> {
+        self.render_with(scale, &RenderOptions::default(), cancel)
+    }
+
+    pub fn render_with(
+        &self,
+        scale: u32,
+        options: &RenderOptions,
+        cancel: &CancelToken,
+    ) -> Result<ImageBuffer<Rgba<u8>, Vec<u8>>, RenderError> {
+        let mut img = ImageBuffer::new(self.width * scale, self.height * scale);
+        let item_database = self.item_database.read().unwrap();
+        for y in 0..self.height {
+            if cancel.is_cancelled() {
+                return Err(RenderError::Cancelled);
+            }
+            for x in 0..self.width {
+                let color = match self.get_tile(x, y) {
+                    Some(tile) => tile_color_with(tile, &item_database, options.layers),
+                    None => Rgba([255, 255, 0, 255]),
+                };
+                fill_tile(&mut img, x, y, scale, color);
+                if options.seed_overlays && options.layers != LayerSelect::Background {
+                    if let Some(tile) = self.get_tile(x, y) {
+                        draw_seed_overlay(&mut img, tile, scale, &item_database, &self.options);
+                    }
+                }
+            }
+        }
+        Ok(img)
+    }
+
+    // Redraws only the tiles listed in `diff` using this world's current state.
+    pub fn render_diff_onto(
+        &self,
+        img: &mut ImageBuffer<Rgba<u8>, Vec<u8>>,
+        diff: &[TileDiff],
+        scale: u32,
+    ) -> Result<(), RenderError> {
+        let expected = (self.width * scale, self.height * scale);
+        if img.dimensions() != expected {
+            return Err(RenderError::DimensionMismatch {
+                expected,
+                found: img.dimensions(),
+            });
+        }
+
+        let item_database = self.item_database.read().unwrap();
+        for change in diff {
+            if let Some(tile) = self.get_tile(change.x, change.y) {
+                fill_tile(img, change.x, change.y, scale, tile_color(tile, &item_database));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiffRenderOptions {
+    pub scale: u32,
+    // opacity of the change boxes drawn over the after render
+    pub alpha: u8,
+    // put the before render on the left of the annotated after render
+    pub side_by_side: bool,
+}
+
+impl Default for DiffRenderOptions {
+    fn default() -> Self {
+        DiffRenderOptions {
+            scale: 8,
+            alpha: 128,
+            side_by_side: false,
+        }
+    }
+}
+
+pub fn change_color(kind: ChangeKind) -> Rgba<u8> {
+    match kind {
+        ChangeKind::Changed => Rgba([255, 0, 0, 255]),
+        ChangeKind::Placed => Rgba([0, 255, 0, 255]),
+        ChangeKind::Removed => Rgba([0, 0, 255, 255]),
+    }
+}
+
+fn blend(base: Rgba<u8>, tint: Rgba<u8>, alpha: u8) -> Rgba<u8> {
+    let mix = |base: u8, tint: u8| ((base as u32 * (255 - alpha as u32) + tint as u32 * alpha as u32) / 255) as u8;
+    Rgba([mix(base[0], tint[0]), mix(base[1], tint[1]), mix(base[2], tint[2]), base[3]])
+}
+
+// Renders `after` with a translucent box over every tile that differs from
+// `before`: red for changed, green for placed, blue for removed.
+pub fn render_diff(before: &World, after: &World, options: &DiffRenderOptions) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+    let scale = options.scale;
+    let diff = before.diff(after);
+    let before_img = options.side_by_side.then(|| before.render(scale));
+
+    // with the before render at hand and the same size, redrawing the changed
+    // tiles is cheaper than rendering the after state from scratch
+    let mut after_img = match &before_img {
+        Some(img) if (before.width, before.height) == (after.width, after.height) => {
+            let mut img = img.clone();
+            after.render_diff_onto(&mut img, &diff, scale).unwrap();
+            img
+        }
+        _ => after.render(scale),
+    };
+
+    for change in &diff {
+        let tint = change_color(change.kind());
+        for py in 0..scale {
+            for px in 0..scale {
+                let (x, y) = (change.x * scale + px, change.y * scale + py);
+                let pixel = *after_img.get_pixel(x, y);
+                after_img.put_pixel(x, y, blend(pixel, tint, options.alpha));
+            }
+        }
+    }
+
+    match before_img {
+        Some(before_img) => {
+            let width = before_img.width() + after_img.width();
+            let height = before_img.height().max(after_img.height());
+            let mut img = ImageBuffer::from_pixel(width, height, Rgba([0, 0, 0, 0]));
+            image::imageops::replace(&mut img, &before_img, 0, 0);
+            image::imageops::replace(&mut img, &after_img, before_img.width() as i64, 0);
+            img
+        }
+        None => after_img,
+    }
+}
+
+#[test]
+fn test_render_diff_onto() {
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use std::sync::Arc;
+
+    let item_database = crate::test_item_database();
+    let mut tiles = vec![0; 16];
+    let mut before = World::new(Arc::clone(&item_database));
+    before.parse(&crate::test_world_bytes(crate::version::CURRENT, 2, 1, &tiles)).unwrap();
+
+    tiles.truncate(8);
+    tiles.write_u16::<LittleEndian>(2).unwrap();
+    tiles.extend_from_slice(&[0; 6]);
+    let mut after = World::new(Arc::clone(&item_database));
+    after.parse(&crate::test_world_bytes(crate::version::CURRENT, 2, 1, &tiles)).unwrap();
+
+    let diff = before.diff(&after);
+    assert_eq!(diff.len(), 1);
+    assert_eq!((diff[0].x, diff[0].y), (1, 0));
+
+    let mut img = before.render(4);
+    after.render_diff_onto(&mut img, &diff, 4).unwrap();
+    assert_eq!(img, after.render(4));
+
+    let mut small = ImageBuffer::new(4, 4);
+    assert_eq!(
+        after.render_diff_onto(&mut small, &diff, 4),
+        Err(RenderError::DimensionMismatch {
+            expected: (8, 4),
+            found: (4, 4),
+        })
+    );
+}
+
+#[test]
+fn test_render_diff() {
+    use std::sync::Arc;
+
+    // unchanged dirt, placed dirt, removed dirt, dirt swapped for a ladder
+    let item_database = crate::test_item_database();
+    let tiles = |foregrounds: [u8; 4]| -> Vec<u8> {
+        foregrounds
+            .iter()
+            .flat_map(|&foreground| [foreground, (foreground == 42) as u8 * 2, 0, 0, 0, 0, 0, 0])
+            .collect()
+    };
+    let mut before = World::new(Arc::clone(&item_database));
+    before.parse(&crate::test_world_bytes(crate::version::CURRENT, 4, 1, &tiles([2, 0, 2, 2]))).unwrap();
+    let mut after = World::new(Arc::clone(&item_database));
+    // 554 is 0x022A, the low byte 42 marks it in the helper above
+    after.parse(&crate::test_world_bytes(crate::version::CURRENT, 4, 1, &tiles([2, 2, 0, 42]))).unwrap();
+    assert_eq!(after.tiles[3].foreground_item_id, 554);
+
+    let kinds: Vec<_> = before.diff(&after).iter().map(|change| (change.x, change.kind())).collect();
+    assert_eq!(kinds, vec![(1, ChangeKind::Placed), (2, ChangeKind::Removed), (3, ChangeKind::Changed)]);
+
+    let options = DiffRenderOptions {
+        scale: 2,
+        ..Default::default()
+    };
+    let plain = after.render(2);
+    let img = render_diff(&before, &after, &options);
+    assert_eq!(img.dimensions(), (8, 2));
+    for (x, kind) in [(1, ChangeKind::Placed), (2, ChangeKind::Removed), (3, ChangeKind::Changed)] {
+        let expected = blend(*plain.get_pixel(x * 2, 0), change_color(kind), options.alpha);
+        assert_eq!(*img.get_pixel(x * 2 + 1, 1), expected);
+    }
+    assert_eq!(img.get_pixel(0, 0), plain.get_pixel(0, 0));
+    assert_eq!(img.get_pixel(1, 1), plain.get_pixel(1, 1));
+
+    let options = DiffRenderOptions {
+        scale: 2,
+        side_by_side: true,
+        ..Default::default()
+    };
+    let composite = render_diff(&before, &after, &options);
+    assert_eq!(composite.dimensions(), (16, 2));
+    assert_eq!(*composite.get_pixel(2, 0), *before.render(2).get_pixel(2, 0));
+    assert_eq!(*composite.get_pixel(8 + 2, 0), *img.get_pixel(2, 0));
+    assert_eq!(*composite.get_pixel(8 + 7, 1), *img.get_pixel(7, 1));
+}
+
+#[test]
+fn test_render_cancellable() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let cancel = CancelToken::new();
+    assert_eq!(world.render_cancellable(2, &cancel).unwrap(), world.render(2));
+    cancel.cancel();
+    assert_eq!(world.render_cancellable(2, &cancel), Err(RenderError::Cancelled));
+}
+
+#[test]
+fn test_render_layers() {
+    use crate::builder::WorldBuilder;
+
+    // dirt on cave background, bare cave background, dirt alone, sky
+    let mut world = WorldBuilder::new().size(4, 1).build(crate::test_item_database());
+    for (x, foreground, background) in [(0, 2, 14), (1, 0, 14), (2, 2, 0)] {
+        let tile = world.get_tile_mut(x, 0).unwrap();
+        tile.foreground_item_id = foreground;
+        tile.background_item_id = background;
+    }
+    let (dirt, cave) = {
+        let item_database = world.item_database.read().unwrap();
+        (item_color(&item_database, 2), item_color(&item_database, 14))
+    };
+    assert_ne!(dirt, cave);
+
+    let render = |layers| {
+        let img = world
+            .render_with(
+                1,
+                &RenderOptions {
+                    layers,
+                    ..Default::default()
+                },
+                &CancelToken::new(),
+            )
+            .unwrap();
+        (0..4).map(|x| *img.get_pixel(x, 0)).collect::<Vec<_>>()
+    };
+    assert_eq!(render(LayerSelect::Both), vec![dirt, cave, dirt, SKY_COLOR]);
+    assert_eq!(render(LayerSelect::Foreground), vec![dirt, SKY_COLOR, dirt, SKY_COLOR]);
+    assert_eq!(render(LayerSelect::Background), vec![cave, cave, SKY_COLOR, SKY_COLOR]);
+    assert_eq!(world.render(1), world.render_with(1, &RenderOptions::default(), &CancelToken::new()).unwrap());
+}
+
+#[test]
+fn test_seed_visuals() {
+    use crate::builder::WorldBuilder;
+    use std::time::Duration;
+
+    let mut world = WorldBuilder::new().size(50, 45).build(crate::test_item_database());
+    let tile = world.get_tile_mut(49, 44).unwrap();
+    tile.foreground_item_id = 3;
+    tile.tile_type = TileType::Seed {
+        time_passed: 0,
+        item_on_tree: 3,
+        ready_to_harvest: false,
+        elapsed: Duration::ZERO,
+    };
+    let item_database = crate::test_item_database();
+    let item_database = item_database.read().unwrap();
+    let tile = world.get_tile(49, 44).unwrap().clone();
+
+    let visuals = seed_visuals(&tile, &item_database, &world.options).unwrap();
+    assert_eq!(
+        visuals,
+        SeedVisuals {
+            fruit_count: 3,
+            ready: false,
+            fruit_item_id: 2,
+        }
+    );
+    assert_eq!(seed_visuals(world.get_tile(0, 0).unwrap(), &item_database, &world.options), None);
+
+    let mut ready = tile.clone();
+    if let TileType::Seed { ready_to_harvest, .. } = &mut ready.tile_type {
+        *ready_to_harvest = true;
+    }
+    assert!(seed_visuals(&ready, &item_database, &world.options).unwrap().ready);
+
+    let options = RenderOptions {
+        seed_overlays: true,
+        ..Default::default()
+    };
+    let plain = world.render(32);
+    let img = world.render_with(32, &options, &CancelToken::new()).unwrap();
+    let fruit_color = item_color(&item_database, 2);
+    assert!((0..32).all(|x| *img.get_pixel(49 * 32 + x, 44 * 32) == fruit_color));
+    assert_eq!(img.get_pixel(49 * 32, 44 * 32 + 1), plain.get_pixel(49 * 32, 44 * 32 + 1));
+    assert_eq!(img.get_pixel(0, 0), plain.get_pixel(0, 0));
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_render_world() {
+    use gtitem_r::load_from_file;
+    use std::fs::File;
+    use std::sync::{Arc, RwLock};
+
+    let item_database = Arc::new(RwLock::new(load_from_file("items.dat").unwrap()));
+    let world = World::from_file("world.dat", item_database).unwrap();
+
+    // world save to world.json
+    let file = File::create("world.json").unwrap();
+    serde_json::to_writer_pretty(file, &world).unwrap();
+
+    let img = world.render(32);
+    img.save("output.png").unwrap();
+}
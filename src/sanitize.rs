@@ -0,0 +1,219 @@
+use crate::tile_extra::TileType;
+use crate::world::{fnv1a, World, FNV_OFFSET_BASIS};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum UidPolicy {
+    Zero,
+    // each distinct uid becomes 1, 2, 3, ... in order of first appearance,
+    // so tiles that shared an owner still do; 0 stays 0
+    #[default]
+    Pseudonymize,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TextPolicy {
+    Keep,
+    Blank,
+    // replaced by 8 hex digits of its hash, equal texts stay equal
+    #[default]
+    Hash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SanitizeOptions {
+    pub uids: UidPolicy,
+    pub text: TextPolicy,
+    // renumber dropped item uids 1, 2, 3, ...
+    pub renumber_dropped: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        SanitizeOptions {
+            uids: UidPolicy::default(),
+            text: TextPolicy::default(),
+            renumber_dropped: true,
+        }
+    }
+}
+
+struct Sanitizer {
+    options: SanitizeOptions,
+    pseudonyms: HashMap<u32, u32>,
+}
+
+impl Sanitizer {
+    fn uid(&mut self, uid: &mut u32) {
+        if *uid == 0 {
+            return;
+        }
+        *uid = match self.options.uids {
+            UidPolicy::Zero => 0,
+            UidPolicy::Pseudonymize => {
+                let next = self.pseudonyms.len() as u32 + 1;
+                *self.pseudonyms.entry(*uid).or_insert(next)
+            }
+        };
+    }
+
+    fn uids(&mut self, uids: &mut [u32]) {
+        for uid in uids {
+            self.uid(uid);
+        }
+    }
+
+    fn text(&self, text: &mut String) {
+        if text.is_empty() {
+            return;
+        }
+        match self.options.text {
+            TextPolicy::Keep => {}
+            TextPolicy::Blank => text.clear(),
+            TextPolicy::Hash => *text = format!("{:08x}", fnv1a(FNV_OFFSET_BASIS, text.as_bytes()) as u32),
+        }
+    }
+}
+
+impl World {
+    // Scrubs what identifies players before a capture is shared: owner and
+    // access uids on locks and entrances, heart monitor uids, free text
+    // (doors, signs, mailboxes, player and pet names, labels, ...) and dropped
+    // item uids. Ids, flags and the shape of every record stay as they were,
+    // so the world parses and renders the same way. Country flags, audio rack
+    // notes and CBOR payloads are left alone.
+    pub fn sanitize(&mut self, options: SanitizeOptions) {
+        let mut sanitizer = Sanitizer {
+            options,
+            pseudonyms: HashMap::new(),
+        };
+        self.mark_all_dirty();
+        for tile in self.tiles.iter_mut() {
+            match &mut tile.tile_type {
+                TileType::Lock {
+                    owner_uid, access_uids, ..
+                }
+                | TileType::VipEntrance {
+                    owner_uid, access_uids, ..
+                } => {
+                    sanitizer.uid(owner_uid);
+                    sanitizer.uids(access_uids);
+                }
+                TileType::FriendsEntrance { owner_user_id, .. } => sanitizer.uid(owner_user_id),
+                // unknown_1 holds the player's uid, it matches the lock owner
+                // and access list in captures
+                TileType::HearthMonitor { unknown_1, player_name } => {
+                    sanitizer.uid(unknown_1);
+                    sanitizer.text(player_name);
+                }
+                TileType::Door { text, .. }
+                | TileType::Sign { text }
+                | TileType::Mannequin { text, .. }
+                | TileType::Crystal { unknown_1: text }
+                | TileType::CrimeInProgress { unknown_1: text, .. }
+                | TileType::SilkWorm { name: text, .. }
+                | TileType::PaintingEasel { label: text, .. }
+                | TileType::PetBattleCage { label: text, .. }
+                | TileType::PetTrainer { name: text, .. }
+                | TileType::FishWallMount { label: text, .. }
+                | TileType::Portrait { label: text, .. } => sanitizer.text(text),
+                TileType::Mailbox {
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                    ..
+                }
+                | TileType::Bulletin {
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                    ..
+                }
+                | TileType::DonationBox {
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                    ..
+                } => {
+                    sanitizer.text(unknown_1);
+                    sanitizer.text(unknown_2);
+                    sanitizer.text(unknown_3);
+                }
+                _ => {}
+            }
+        }
+
+        if options.renumber_dropped {
+            for (index, item) in self.dropped.items.iter_mut().enumerate() {
+                item.uid = index as u32 + 1;
+            }
+            self.dropped.last_dropped_item_uid = self.dropped.items.len() as u32;
+        }
+    }
+}
+
+#[test]
+fn test_sanitize() {
+    use byteorder::{ByteOrder, LittleEndian};
+
+    let contains_u32 = |data: &[u8], value: u32| {
+        let mut bytes = [0; 4];
+        LittleEndian::write_u32(&mut bytes, value);
+        data.windows(4).any(|window| window == bytes)
+    };
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let mut uids = Vec::new();
+    let mut texts = Vec::new();
+    for tile in &world.tiles {
+        match &tile.tile_type {
+            TileType::Lock {
+                owner_uid, access_uids, ..
+            } => {
+                uids.push(*owner_uid);
+                uids.extend(access_uids);
+            }
+            TileType::Door { text, .. } | TileType::Sign { text } if text.len() > 3 => texts.push(text.clone()),
+            _ => {}
+        }
+    }
+    assert_eq!(uids, vec![30865966, 22545020]);
+    assert!(!texts.is_empty());
+
+    let original = world.clone();
+    world.sanitize(SanitizeOptions::default());
+    let data = world.serialize().unwrap();
+    for uid in &uids {
+        assert!(!contains_u32(&data, *uid));
+    }
+    for text in &texts {
+        assert!(!data.windows(text.len()).any(|window| window == text.as_bytes()));
+    }
+    assert!(world.dropped.items.iter().map(|item| item.uid).eq(1..=world.dropped.items.len() as u32));
+    assert!(matches!(
+        &world.get_tile(49, 50).unwrap().tile_type,
+        TileType::Lock { owner_uid: 1, access_uids, .. } if *access_uids == vec![2]
+    ));
+
+    // same layout, so it parses back to the same ids and record types
+    let mut parsed = World::new(crate::test_item_database());
+    parsed.parse(&data).unwrap();
+    assert_eq!(parsed.tiles.len(), original.tiles.len());
+    for (sanitized, original) in parsed.tiles.iter().zip(&original.tiles) {
+        assert_eq!(sanitized.foreground_item_id, original.foreground_item_id);
+        assert_eq!(sanitized.tile_type.extra_type_id(), original.tile_type.extra_type_id());
+    }
+
+    let mut world = original.clone();
+    world.sanitize(SanitizeOptions {
+        uids: UidPolicy::Zero,
+        text: TextPolicy::Blank,
+        renumber_dropped: false,
+    });
+    assert!(matches!(
+        world.get_tile(49, 50).unwrap().tile_type,
+        TileType::Lock { owner_uid: 0, .. }
+    ));
+    assert_eq!(world.dropped, original.dropped);
+}
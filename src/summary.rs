@@ -0,0 +1,161 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::edit::PAINT_MASK;
+use crate::tile::{ChangeKind, TileDiff};
+use crate::tile_extra::TileType;
+use crate::world::World;
+use std::collections::HashMap;
+
+// Counts of what changed between two polls of a world. Every TileDiff lands
+// in exactly one of placed, broken, painted and changed:
+//   - placed: the foreground went from blank to an item, or the foreground
+//     stayed and the background went from blank to an item
+//   - broken: the same in reverse
+//   - painted: only the paint bits of the flags differ
+//   - changed: anything else (a block swapped, extra data or other flags)
+// locks_added and locks_removed count tiles that gained or lost lock data on
+// top of that. Dropped items are matched by uid and position.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiffSummary {
+    pub placed: usize,
+    pub broken: usize,
+    pub painted: usize,
+    pub changed: usize,
+    pub locks_added: usize,
+    pub locks_removed: usize,
+    pub items_dropped: usize,
+    pub items_collected: usize,
+    pub weather_changed: bool,
+}
+
+impl DiffSummary {
+    pub fn is_empty(&self) -> bool {
+        *self == DiffSummary::default()
+    }
+
+    fn add(&mut self, diff: &TileDiff) {
+        let (before, after) = (&diff.before, &diff.after);
+        let same_foreground = before.foreground_item_id == after.foreground_item_id;
+        let background = (before.background_item_id, after.background_item_id);
+        match diff.kind() {
+            ChangeKind::Placed => self.placed += 1,
+            ChangeKind::Removed => self.broken += 1,
+            ChangeKind::Changed if same_foreground && background.0 == 0 && background.1 != 0 => self.placed += 1,
+            ChangeKind::Changed if same_foreground && background.0 != 0 && background.1 == 0 => self.broken += 1,
+            ChangeKind::Changed
+                if same_foreground
+                    && background.0 == background.1
+                    && before.parent_block_index == after.parent_block_index
+                    && before.tile_type == after.tile_type
+                    && (before.flags_number ^ after.flags_number) & !PAINT_MASK == 0 =>
+            {
+                self.painted += 1
+            }
+            ChangeKind::Changed => self.changed += 1,
+        }
+
+        let is_lock = |tile_type: &TileType| matches!(tile_type, TileType::Lock { .. });
+        match (is_lock(&before.tile_type), is_lock(&after.tile_type)) {
+            (false, true) => self.locks_added += 1,
+            (true, false) => self.locks_removed += 1,
+            _ => {}
+        }
+    }
+}
+
+impl World {
+    // diff, classified. `self` is the earlier poll.
+    pub fn diff_summary(&self, other: &World) -> DiffSummary {
+        let mut summary = DiffSummary::default();
+        for diff in self.diff(other) {
+            summary.add(&diff);
+        }
+
+        // Items match by uid and position, so two items sharing a uid are
+        // still told apart. One that moved counts as collected and dropped.
+        let mut before: HashMap<(u32, u32, u32), usize> = HashMap::new();
+        for item in &self.dropped.items {
+            *before.entry((item.uid, item.x.to_bits(), item.y.to_bits())).or_insert(0) += 1;
+        }
+        for item in &other.dropped.items {
+            match before.get_mut(&(item.uid, item.x.to_bits(), item.y.to_bits())) {
+                Some(count) if *count > 0 => *count -= 1,
+                _ => summary.items_dropped += 1,
+            }
+        }
+        summary.items_collected = before.values().sum();
+        summary.weather_changed = other.weather_changed(self);
+        summary
+    }
+}
+
+#[test]
+fn test_diff_summary() {
+    use crate::builder::WorldBuilder;
+    use crate::dropped::DroppedItem;
+    use crate::weather::WeatherType;
+
+    let mut before = WorldBuilder::new().size(4, 2).build(crate::test_item_database());
+    before.tiles[1].foreground_item_id = 2;
+    before.tiles[2].foreground_item_id = 2;
+    before.tiles[3].background_item_id = 14;
+    before.tiles[4].foreground_item_id = 2;
+    before.tiles[5].foreground_item_id = 2;
+    for uid in [1, 2] {
+        before.dropped.items.push(DroppedItem {
+            id: 2,
+            x: 0.0,
+            y: 0.0,
+            count: 1,
+            flags: 0,
+            uid,
+        });
+    }
+    let mut after = before.clone();
+    assert!(before.diff_summary(&after).is_empty());
+
+    after.tiles[0].foreground_item_id = 2;
+    after.tiles[1].foreground_item_id = 0;
+    after.tiles[2].flags_number = 0x2000;
+    after.tiles[3].background_item_id = 0;
+    after.tiles[4].foreground_item_id = 554;
+    after.tiles[5].foreground_item_id = 5814;
+    after.tiles[5].tile_type = TileType::Lock {
+        settings: Default::default(),
+        owner_uid: 1,
+        access_count: 0,
+        access_uids: Vec::new(),
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    after.tiles[6].background_item_id = 14;
+    after.dropped.items.remove(0);
+    after.dropped.items[0].count = 5;
+    after.dropped.items.push(DroppedItem {
+        id: 3,
+        x: 1.0,
+        y: 1.0,
+        count: 1,
+        flags: 0,
+        uid: 3,
+    });
+    after.set_weather(WeatherType::Default, WeatherType::Night);
+
+    assert_eq!(
+        before.diff_summary(&after),
+        DiffSummary {
+            placed: 2,
+            broken: 2,
+            painted: 1,
+            changed: 2,
+            locks_added: 1,
+            locks_removed: 0,
+            items_dropped: 1,
+            items_collected: 1,
+            weather_changed: true,
+        }
+    );
+    assert_eq!(after.diff_summary(&before).locks_removed, 1);
+}
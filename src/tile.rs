@@ -0,0 +1,593 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::flags::TileFlags;
+use crate::options::ParseOptions;
+use crate::tile_extra::TileType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Tile {
+    pub foreground_item_id: u16,
+    pub background_item_id: u16,
+    pub parent_block_index: u16,
+    pub flags: TileFlags,
+    pub flags_number: u16,
+    pub tile_type: TileType,
+    pub x: u32,
+    pub y: u32,
+    // raw CBOR blob that follows some tiles, kept as-is
+    #[cfg_attr(feature = "serde", serde(default, deserialize_with = "crate::limits::bounded_cbor"))]
+    pub cbor_data: Option<Vec<u8>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub item_database: Arc<RwLock<ItemDatabase>>,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileDiff {
+    pub x: u32,
+    pub y: u32,
+    pub before: Tile,
+    pub after: Tile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ChangeKind {
+    // something was placed on a tile that had no foreground
+    Placed,
+    // the foreground was broken and nothing replaced it
+    Removed,
+    Changed,
+}
+
+impl TileDiff {
+    pub fn kind(&self) -> ChangeKind {
+        match (self.before.foreground_item_id, self.after.foreground_item_id) {
+            (0, after) if after != 0 => ChangeKind::Placed,
+            (before, 0) if before != 0 => ChangeKind::Removed,
+            _ => ChangeKind::Changed,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x && y >= self.y && x - self.x < self.width && y - self.y < self.height
+    }
+}
+
+// A tile's position in reading order (y * width + x) as parent_block_index
+// stores it. The format keeps it in 16 bits, so tiles past index 65535 of a
+// larger world can't be pointed at; 0 doubles as "no parent".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TileIndex(u16);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileIndexError {
+    OutOfBounds { x: u32, y: u32 },
+    Overflow { index: u64 },
+}
+
+impl fmt::Display for TileIndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TileIndexError::OutOfBounds { x, y } => write!(f, "tile ({}, {}) is outside the world", x, y),
+            TileIndexError::Overflow { index } => write!(
+                f,
+                "tile index {} doesn't fit a parent index, at most {} can be stored",
+                index,
+                TileIndex::MAX.get()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TileIndexError {}
+
+impl TileIndex {
+    pub const MAX: TileIndex = TileIndex(u16::MAX);
+
+    pub fn new(index: u64) -> Result<TileIndex, TileIndexError> {
+        u16::try_from(index)
+            .map(TileIndex)
+            .map_err(|_| TileIndexError::Overflow { index })
+    }
+
+    // Computed in 64 bits, so a huge width can't wrap it into range.
+    pub fn from_position(x: u32, y: u32, width: u32) -> Result<TileIndex, TileIndexError> {
+        TileIndex::new(y as u64 * width as u64 + x as u64)
+    }
+
+    pub fn get(self) -> u16 {
+        self.0
+    }
+
+    pub fn to_usize(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl From<u16> for TileIndex {
+    fn from(index: u16) -> Self {
+        TileIndex(index)
+    }
+}
+
+impl From<TileIndex> for u16 {
+    fn from(index: TileIndex) -> Self {
+        index.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TileLayer {
+    Foreground,
+    Background,
+}
+
+impl TileLayer {
+    // The layer whose item a tile's extra data and CBOR belong to: the
+    // foreground, or with options.background_special_items the background
+    // when the foreground is blank. The extra data layout itself comes from
+    // its type byte, only the item specific parts (CBOR detection, grow
+    // times, the 5814 lock quirk) use this.
+    pub fn of_special(foreground_item_id: u16, background_item_id: u16, options: &ParseOptions) -> TileLayer {
+        if options.background_special_items && foreground_item_id == 0 && background_item_id != 0 {
+            TileLayer::Background
+        } else {
+            TileLayer::Foreground
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum GrowthError {
+    // a growing tile whose grow time can't be looked up
+    UnknownItem { item_id: u16, x: u32, y: u32 },
+}
+
+impl fmt::Display for GrowthError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrowthError::UnknownItem { item_id, x, y } => write!(
+                f,
+                "item {} at ({}, {}) is not in the item database",
+                item_id, x, y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GrowthError {}
+
+impl Tile {
+    pub fn new(
+        foreground_item_id: u16,
+        background_item_id: u16,
+        parent_block_index: u16,
+        flags: TileFlags,
+        flags_number: u16,
+        x: u32,
+        y: u32,
+        item_database: Arc<RwLock<ItemDatabase>>
+    ) -> Tile {
+        Tile {
+            foreground_item_id,
+            background_item_id,
+            parent_block_index,
+            flags,
+            flags_number,
+            tile_type: TileType::Basic,
+            x,
+            y,
+            cbor_data: None,
+            item_database,
+        }
+    }
+
+    pub fn special_layer(&self, options: &ParseOptions) -> TileLayer {
+        TileLayer::of_special(self.foreground_item_id, self.background_item_id, options)
+    }
+
+    // The item id the extra data and CBOR are keyed by, see TileLayer::of_special.
+    pub fn special_item_id(&self, options: &ParseOptions) -> u16 {
+        match self.special_layer(options) {
+            TileLayer::Foreground => self.foreground_item_id,
+            TileLayer::Background => self.background_item_id,
+        }
+    }
+
+    pub fn same_content(&self, other: &Tile) -> bool {
+        self.foreground_item_id == other.foreground_item_id
+            && self.background_item_id == other.background_item_id
+            && self.parent_block_index == other.parent_block_index
+            && self.flags_number == other.flags_number
+            && self.tile_type == other.tile_type
+    }
+
+    // How many fruits the tree yields when harvested. The byte is a plain
+    // count (1 to 4 in captures), rolled when the seed is planted, so it's
+    // already set on a tree that is still growing.
+    pub fn fruit_count(&self) -> Option<u8> {
+        match self.tile_type {
+            TileType::Seed { item_on_tree, .. } => Some(item_on_tree),
+            _ => None,
+        }
+    }
+
+    // How far a seed has grown, from 0.0 just planted to 1.0 ready to harvest.
+    // The grow time is looked up for special_item_id, the item the parser
+    // read the seed for under the same `options`.
+    pub fn grow_progress(&self, options: &ParseOptions) -> Option<f32> {
+        match self.tile_type {
+            TileType::Seed {
+                ready_to_harvest: true, ..
+            } => Some(1.0),
+            TileType::Seed { elapsed, .. } => {
+                let item_database = self.item_database.read().unwrap();
+                let item = item_database.get_item(&(self.special_item_id(options) as u32))?;
+                if item.grow_time == 0 {
+                    return Some(1.0);
+                }
+                Some((elapsed.as_secs_f32() / item.grow_time as f32).min(1.0))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn harvestable(&self, options: &ParseOptions) -> bool {
+        match self.tile_type {
+            TileType::Seed {
+                ready_to_harvest,
+                elapsed,
+                ..
+            } => {
+                if ready_to_harvest {
+                    true
+                } else {
+                    let item_database = self.item_database.read().unwrap();
+                    item_database
+                        .get_item(&(self.special_item_id(options) as u32))
+                        .is_some_and(|item| elapsed.as_secs() >= item.grow_time as u64)
+                }
+            }
+            TileType::ChemicalSource {
+                ready_to_harvest,
+                elapsed,
+                ..
+            } => {
+                if ready_to_harvest {
+                    true
+                } else {
+                    let item_database = self.item_database.read().unwrap();
+                    item_database
+                        .get_item(&(self.special_item_id(options) as u32))
+                        .is_some_and(|item| elapsed.as_secs() >= item.grow_time as u64)
+                }
+            }
+            _ => false,
+        }
+    }
+
+    // Moves a seed's or chemical source's timer `extra_seconds` forward, as if
+    // that much time had passed since the world was captured: time_passed and
+    // elapsed both grow and ready_to_harvest is worked out again from the
+    // item's grow time. This changes the timer state the writer puts back into
+    // the world. Other tiles are left alone. time_passed stops at u32::MAX.
+    // The item is special_item_id under `options`, as for the parser.
+    pub fn refresh_harvest_state(
+        &mut self,
+        extra_seconds: u64,
+        item_database: &ItemDatabase,
+        options: &ParseOptions,
+    ) -> Result<(), GrowthError> {
+        let item_id = self.special_item_id(options);
+        let (time_passed, ready_to_harvest, elapsed) = match &mut self.tile_type {
+            TileType::Seed {
+                time_passed,
+                ready_to_harvest,
+                elapsed,
+                ..
+            }
+            | TileType::ChemicalSource {
+                time_passed,
+                ready_to_harvest,
+                elapsed,
+            } => (time_passed, ready_to_harvest, elapsed),
+            _ => return Ok(()),
+        };
+        let grow_time = item_database
+            .get_item(&(item_id as u32))
+            .ok_or(GrowthError::UnknownItem {
+                item_id,
+                x: self.x,
+                y: self.y,
+            })?
+            .grow_time;
+        *time_passed = (*time_passed as u64).saturating_add(extra_seconds).min(u32::MAX as u64) as u32;
+        *elapsed = elapsed.saturating_add(Duration::from_secs(extra_seconds));
+        *ready_to_harvest = grow_time <= *time_passed;
+        Ok(())
+    }
+}
+
+impl World {
+    // Runs refresh_harvest_state on every seed and chemical source, so a
+    // simulation can step a parsed world forward without reparsing it. Every
+    // growing tile is checked against `item_database` first, an error leaves
+    // all timers as they were. Returns how many tiles became ready.
+    pub fn advance_time(&mut self, seconds: u64, item_database: &ItemDatabase) -> Result<usize, GrowthError> {
+        let is_growing =
+            |tile: &Tile| matches!(tile.tile_type, TileType::Seed { .. } | TileType::ChemicalSource { .. });
+        if let Some(tile) = self
+            .tiles
+            .iter()
+            .filter(|tile| is_growing(tile))
+            .find(|tile| item_database.get_item(&(tile.special_item_id(&self.options) as u32)).is_none())
+        {
+            return Err(GrowthError::UnknownItem {
+                item_id: tile.special_item_id(&self.options),
+                x: tile.x,
+                y: tile.y,
+            });
+        }
+
+        // read from the variant, harvestable() would lock the item database
+        // the caller may be holding
+        let is_ready = |tile: &Tile| {
+            matches!(
+                tile.tile_type,
+                TileType::Seed {
+                    ready_to_harvest: true,
+                    ..
+                } | TileType::ChemicalSource {
+                    ready_to_harvest: true,
+                    ..
+                }
+            )
+        };
+        let mut ready = 0;
+        let mut changed = Vec::new();
+        for (index, tile) in self.tiles.iter_mut().enumerate().filter(|(_, tile)| is_growing(tile)) {
+            let was_ready = is_ready(tile);
+            tile.refresh_harvest_state(seconds, item_database, &self.options)?;
+            if !was_ready && is_ready(tile) {
+                ready += 1;
+            }
+            changed.push(index);
+        }
+        for index in changed {
+            self.mark_dirty(index);
+        }
+        Ok(ready)
+    }
+}
+
+#[test]
+fn test_background_special_tile() {
+    use crate::options::CborDetection;
+    use crate::{test_world_bytes, version};
+    use byteorder::{LittleEndian, WriteBytesExt};
+    use std::collections::HashSet;
+
+    // a sign with CBOR on a tile whose only item is the Black Wallpaper
+    // background, then a dirt tile that only lines up if the CBOR was read
+    let mut tiles = Vec::new();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(1158).unwrap();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(0x01).unwrap();
+    tiles.write_u8(2).unwrap();
+    tiles.write_u16::<LittleEndian>(2).unwrap();
+    tiles.extend_from_slice(b"hi");
+    tiles.write_u32::<LittleEndian>(u32::MAX).unwrap();
+    tiles.write_u32::<LittleEndian>(3).unwrap();
+    tiles.extend_from_slice(&[0xA1, 0x01, 0x02]);
+    tiles.write_u16::<LittleEndian>(2).unwrap();
+    tiles.write_u16::<LittleEndian>(14).unwrap();
+    tiles.extend_from_slice(&[0; 4]);
+    let data = test_world_bytes(version::CURRENT, 2, 1, &tiles);
+
+    let options = ParseOptions {
+        cbor_detection: CborDetection::ExplicitList(HashSet::from([1158])),
+        background_special_items: true,
+        ..Default::default()
+    };
+    let mut world = World::new(crate::test_item_database());
+    world.options = options.clone();
+    world.parse(&data).unwrap();
+    let tile = &world.tiles[0];
+    assert_eq!(tile.special_layer(&options), TileLayer::Background);
+    assert_eq!(tile.special_item_id(&options), 1158);
+    assert_eq!(tile.special_layer(&ParseOptions::default()), TileLayer::Foreground);
+    assert_eq!(tile.tile_type, TileType::Sign { text: "hi".to_string() });
+    assert_eq!(tile.cbor_data, Some(vec![0xA1, 0x01, 0x02]));
+    assert_eq!(world.tiles[1].special_layer(&options), TileLayer::Foreground);
+    assert_eq!((world.tiles[1].foreground_item_id, world.tiles[1].background_item_id), (2, 14));
+    assert_eq!(world.serialize().unwrap(), data);
+
+    // the raw pass keys CBOR the same way
+    let grid = World::parse_raw_tiles(&data, options.clone()).unwrap();
+    assert_eq!(grid.tiles[1].foreground_item_id, 2);
+    assert_eq!(grid.tiles[0].special_item_id(&options), 1158);
+
+    // by default the CBOR belongs to the blank foreground, so it isn't read
+    world.options.background_special_items = false;
+    world.parse(&data).unwrap();
+    assert_eq!(world.tiles[0].cbor_data, None);
+    assert_ne!(world.tiles[1].foreground_item_id, 2);
+}
+
+#[test]
+fn test_tile_index() {
+    use crate::builder::WorldBuilder;
+
+    // 70000 tiles, more than a 16 bit parent index can address
+    let mut world = WorldBuilder::new().size(350, 200).build(crate::test_item_database());
+    assert_eq!(world.tile_index(5, 0), Ok(TileIndex::from(5)));
+    assert_eq!(world.tile_index(85, 187), Ok(TileIndex::MAX));
+    assert_eq!(
+        world.tile_index(349, 199),
+        Err(TileIndexError::Overflow { index: 69999 })
+    );
+    assert_eq!(
+        world.tile_index(350, 0),
+        Err(TileIndexError::OutOfBounds { x: 350, y: 0 })
+    );
+    assert_eq!(
+        TileIndex::from_position(0, 1, u32::MAX),
+        Err(TileIndexError::Overflow { index: u32::MAX as u64 })
+    );
+
+    // a parent past the limit is refused instead of wrapping to index 1064
+    assert_eq!(
+        world.set_parent(0, 0, 100, 190),
+        Err(TileIndexError::Overflow { index: 66600 })
+    );
+    assert_eq!(world.get_tile(0, 0).unwrap().parent_block_index, 0);
+    world.set_parent(10, 190, 5, 0).unwrap();
+    assert_eq!(world.get_tile(10, 190).unwrap().parent_block_index, 5);
+
+    let mut parsed = World::new(crate::test_item_database());
+    parsed.parse(&world.serialize().unwrap()).unwrap();
+    assert_eq!(parsed.tiles.len(), 70000);
+    assert_eq!(parsed.get_tile(10, 190).unwrap().parent_block_index, 5);
+}
+
+#[test]
+fn test_advance_time() {
+    use crate::builder::WorldBuilder;
+    use std::time::Duration;
+
+    let item_database = crate::test_item_database();
+    let database = item_database.read().unwrap();
+    let grow_time = database.get_item(&3).unwrap().grow_time;
+    let seed = |time_passed: u32| TileType::Seed {
+        time_passed,
+        item_on_tree: 2,
+        ready_to_harvest: false,
+        elapsed: Duration::from_secs(time_passed as u64),
+    };
+
+    let mut world = WorldBuilder::new().size(3, 1).build(Arc::clone(&item_database));
+    for (x, item_id, tile_type) in [
+        (0, 3, seed(grow_time - 10)),
+        (
+            1,
+            15,
+            TileType::ChemicalSource {
+                time_passed: 0,
+                ready_to_harvest: false,
+                elapsed: Duration::ZERO,
+            },
+        ),
+    ] {
+        let mut tile = world.edit_tile(x, 0).unwrap();
+        tile.foreground_item_id = item_id;
+        tile.tile_type = tile_type;
+    }
+
+    world.get_tile_mut(0, 0).unwrap().refresh_harvest_state(5, &database, &ParseOptions::default()).unwrap();
+    assert_eq!(world.get_tile(0, 0).unwrap().tile_type, seed(grow_time - 5));
+    // basic tiles have no timer
+    world.get_tile_mut(2, 0).unwrap().refresh_harvest_state(5, &database, &ParseOptions::default()).unwrap();
+    assert_eq!(world.get_tile(2, 0).unwrap().tile_type, TileType::Basic);
+
+    assert_eq!(world.advance_time(5, &database), Ok(1));
+    assert!(matches!(
+        world.get_tile(0, 0).unwrap().tile_type,
+        TileType::Seed { time_passed, ready_to_harvest: true, elapsed, .. }
+            if time_passed == grow_time && elapsed == Duration::from_secs(grow_time as u64)
+    ));
+    assert!(matches!(
+        world.get_tile(1, 0).unwrap().tile_type,
+        TileType::ChemicalSource { time_passed: 5, .. }
+    ));
+    assert_eq!(world.advance_time(5, &database), Ok(0));
+
+    // the advanced timers are what gets written
+    let mut parsed = World::new(Arc::clone(&item_database));
+    parsed.parse(&world.serialize().unwrap()).unwrap();
+    assert_eq!(parsed.get_tile(0, 0).unwrap().tile_type, world.get_tile(0, 0).unwrap().tile_type);
+
+    let mut tile = world.get_tile(0, 0).unwrap().clone();
+    tile.refresh_harvest_state(u64::MAX, &database, &world.options).unwrap();
+    assert!(matches!(
+        tile.tile_type,
+        TileType::Seed { time_passed: u32::MAX, elapsed: Duration::MAX, .. }
+    ));
+
+    // an unknown seed stops the whole tick before any timer moves
+    let before: Vec<TileType> = world.tiles.iter().map(|tile| tile.tile_type.clone()).collect();
+    let mut tile = world.edit_tile(2, 0).unwrap();
+    tile.foreground_item_id = u16::MAX;
+    tile.tile_type = seed(0);
+    drop(tile);
+    assert_eq!(
+        world.advance_time(5, &database),
+        Err(GrowthError::UnknownItem {
+            item_id: u16::MAX,
+            x: 2,
+            y: 0
+        })
+    );
+    for (tile, before) in world.tiles.iter().zip(&before).take(2) {
+        assert_eq!(&tile.tile_type, before);
+    }
+}
+
+#[test]
+fn test_background_seed() {
+    use crate::{test_world_bytes, version};
+    use byteorder::{LittleEndian, WriteBytesExt};
+
+    // a seed planted on the background, keyed there by background_special_items
+    let item_database = crate::test_item_database();
+    let grow_time = item_database.read().unwrap().get_item(&3).unwrap().grow_time;
+    let mut tiles = Vec::new();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(3).unwrap();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(0x01).unwrap();
+    tiles.write_u8(4).unwrap();
+    tiles.write_u32::<LittleEndian>(grow_time - 5).unwrap();
+    tiles.write_u8(2).unwrap();
+
+    let mut world = World::new(Arc::clone(&item_database));
+    world.options.background_special_items = true;
+    world.parse(&test_world_bytes(version::CURRENT, 1, 1, &tiles)).unwrap();
+    let options = world.options.clone();
+    let tile = &world.tiles[0];
+    assert!(matches!(tile.tile_type, TileType::Seed { ready_to_harvest: false, .. }));
+    assert!(!tile.harvestable(&options));
+    assert!(!world.is_harvestable(0, 0));
+    assert_eq!(tile.grow_progress(&options), Some((grow_time - 5) as f32 / grow_time as f32));
+
+    let database = item_database.read().unwrap();
+    assert_eq!(world.advance_time(4, &database), Ok(0));
+    assert_eq!(world.advance_time(1, &database), Ok(1));
+    assert!(world.tiles[0].harvestable(&options));
+    assert_eq!(world.tiles[0].grow_progress(&options), Some(1.0));
+}
@@ -0,0 +1,1954 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::error::WorldParseError;
+use crate::flags::LockSettings;
+use crate::limits;
+use crate::options::Endian;
+use crate::tile::Tile;
+use crate::world::World;
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use gtitem_r::structs::ItemDatabase;
+use std::io::{self, Cursor, Read};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TileType {
+    Basic,
+    Door {
+        text: String,
+        unknown_1: u8,
+    },
+    Sign {
+        text: String,
+    },
+    Lock {
+        settings: LockSettings,
+        owner_uid: u32,
+        access_count: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        access_uids: Vec<u32>,
+        minimum_level: u8,
+        // the rest of the extended lock settings, all zero in captures so far
+        unknown_1: [u8; 7],
+    },
+    Seed {
+        time_passed: u32,
+        item_on_tree: u8,
+        ready_to_harvest: bool,
+        elapsed: Duration,
+    },
+    Mailbox {
+        unknown_1: String,
+        unknown_2: String,
+        unknown_3: String,
+        unknown_4: u8,
+    },
+    Bulletin {
+        unknown_1: String,
+        unknown_2: String,
+        unknown_3: String,
+        unknown_4: u8,
+    },
+    Dice {
+        symbol: u8,
+    },
+    ChemicalSource {
+        time_passed: u32,
+        ready_to_harvest: bool,
+        elapsed: Duration,
+    },
+    AchievementBlock {
+        unknown_1: u32,
+        tile_type: u8,
+    },
+    HearthMonitor {
+        unknown_1: u32,
+        player_name: String,
+    },
+    DonationBox {
+        unknown_1: String,
+        unknown_2: String,
+        unknown_3: String,
+        unknown_4: u8,
+    },
+    Mannequin {
+        text: String,
+        unknown_1: u8,
+        clothing_1: u32,
+        clothing_2: u16,
+        clothing_3: u16,
+        clothing_4: u16,
+        clothing_5: u16,
+        clothing_6: u16,
+        clothing_7: u16,
+        clothing_8: u16,
+        clothing_9: u16,
+        clothing_10: u16,
+    },
+    BunnyEgg {
+        egg_placed: u32,
+    },
+    GamePack {
+        team: u8,
+    },
+    GameGenerator {},
+    XenoniteCrystal {
+        unknown_1: u8,
+        unknown_2: u32,
+    },
+    PhoneBooth {
+        clothing_1: u16,
+        clothing_2: u16,
+        clothing_3: u16,
+        clothing_4: u16,
+        clothing_5: u16,
+        clothing_6: u16,
+        clothing_7: u16,
+        clothing_8: u16,
+        clothing_9: u16,
+    },
+    Crystal {
+        unknown_1: String,
+    },
+    CrimeInProgress {
+        unknown_1: String,
+        unknown_2: u32,
+        unknown_3: u8,
+    },
+    DisplayBlock {
+        item_id: u32,
+    },
+    VendingMachine {
+        item_id: u32,
+        price: i32,
+    },
+    GivingTree {
+        unknown_1: u16,
+        unknown_2: u32,
+    },
+    CountryFlag {
+        country: String,
+    },
+    // Only the configurable machines store this: Heatwave (3694), Stuff
+    // (3832) and Background (5000). Heatwave is set to a color, not an item,
+    // so the u32 is kept whole as settings rather than read as an item id;
+    // for Stuff and Background it's believed to hold the chosen item, but no
+    // capture confirms it.
+    WeatherMachine {
+        settings: u32,
+    },
+    // 21 bytes from v20 on, all zero before. The one capture holds
+    // 00 | 1 | 1 | 1 | 0 | 0xFFFFFFFF, so it reads as a byte and five u32s;
+    // what they mean is unknown.
+    DataBedrock {
+        unknown_1: u8,
+        unknown_2: u32,
+        unknown_3: u32,
+        unknown_4: u32,
+        unknown_5: u32,
+        unknown_6: u32,
+    },
+    Spotlight,
+    FishTankPort {
+        flags: u8,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        fishes: Vec<FishInfo>,
+    },
+    SolarCollector {
+        unknown_1: [u8; 5],
+    },
+    Forge {
+        temperature: u32,
+    },
+    SteamOrgan {
+        instrument_type: u8,
+        note: u32,
+    },
+    SilkWorm {
+        type_: u8,
+        name: String,
+        age: u32,
+        unknown_1: u32,
+        unknown_2: u32,
+        can_be_fed: u8,
+        color: SilkWormColor,
+        sick_duration: u32,
+    },
+    SewingMachine {
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        bolt_id_list: Vec<u32>,
+    },
+    LobsterTrap,
+    PaintingEasel {
+        item_id: u32,
+        label: String,
+    },
+    PetBattleCage {
+        label: String,
+        base_pet: u32,
+        combined_pet_1: u32,
+        combined_pet_2: u32,
+    },
+    PetTrainer {
+        name: String,
+        pet_total_count: u32,
+        unknown_1: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        pets_id: Vec<u32>,
+    },
+    SteamEngine {
+        temperature: u32,
+    },
+    LockBot {
+        time_passed: u32,
+    },
+    SpiritStorageUnit {
+        ghost_jar_count: u32,
+    },
+    Shelf {
+        top_left_item_id: u32,
+        top_right_item_id: u32,
+        bottom_left_item_id: u32,
+        bottom_right_item_id: u32,
+    },
+    VipEntrance {
+        unknown_1: u8,
+        owner_uid: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        access_uids: Vec<u32>,
+    },
+    ChallangeTimer,
+    FishWallMount {
+        label: String,
+        item_id: u32,
+        lb: u8,
+    },
+    Portrait {
+        label: String,
+        unknown_1: u32,
+        unknown_2: u32,
+        unknown_3: u32,
+        unknown_4: u32,
+        face: u32,
+        hat: u32,
+        hair: u32,
+        unknown_5: u16,
+        unknown_6: u16,
+    },
+    GuildWeatherMachine {
+        unknown_1: u32,
+        gravity: u32,
+        flags: u8,
+    },
+    FossilPrepStation {
+        unknown_1: u32,
+    },
+    DnaExtractor,
+    Howler,
+    ChemsynthTank {
+        current_chem: u32,
+        target_chem: u32,
+    },
+    StorageBlock {
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        items: Vec<StorageBlockItemInfo>,
+    },
+    CookingOven {
+        temperature_level: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        ingredients: Vec<CookingOvenIngredientInfo>,
+        unknown_1: u32,
+        unknown_2: u32,
+        unknown_3: u32,
+    },
+    AudioRack {
+        note: String,
+        volume: u32,
+    },
+    GeigerCharger {
+        unknown_1: u32,
+    },
+    AdventureBegins,
+    TombRobber,
+    BalloonOMatic {
+        total_rarity: u32,
+        team_type: u8,
+    },
+    TrainingPort {
+        fish_lb: u32,
+        fish_status: u16,
+        fish_id: u32,
+        fish_total_exp: u32,
+        fish_level: u32,
+        unknown_2: u32,
+    },
+    ItemSucker {
+        item_id_to_suck: u32,
+        item_amount: u32,
+        flags: u16,
+        limit: u32,
+    },
+    CyBot {
+        sync_timer: u32,
+        activated: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        command_datas: Vec<CyBotCommandData>,
+    },
+    GuildItem,
+    Growscan {
+        unknown_1: u8,
+    },
+    ContainmentFieldPowerNode {
+        ghost_jar_count: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        unknown_1: Vec<u32>,
+    },
+    SpiritBoard {
+        unknown_1: u32,
+        unknown_2: u32,
+        unknown_3: u32,
+    },
+    StormyCloud {
+        sting_duration: u32,
+        is_solid: u32,
+        non_solid_duration: u32,
+    },
+    TemporaryPlatform {
+        unknown_1: u32,
+    },
+    SafeVault,
+    AngelicCountingCloud {
+        is_raffling: u32,
+        unknown_1: u16,
+        ascii_code: u8,
+    },
+    InfinityWeatherMachine {
+        interval_minutes: u32,
+        #[cfg_attr(feature = "serde", serde(deserialize_with = "crate::limits::bounded_list"))]
+        weather_machine_list: Vec<u32>,
+    },
+    PineappleGuzzler,
+    KrakenGalaticBlock {
+        pattern_index: u8,
+        unknown_1: u32,
+        r: u8,
+        g: u8,
+        b: u8,
+    },
+    FriendsEntrance {
+        owner_user_id: u32,
+        unknown_1: u16,
+        unknown_2: u16,
+    },
+}
+
+impl TileType {
+    // Item ids stored inside the extra data (displayed, sold, worn or held
+    // items). Zero means an empty slot and is left out.
+    pub fn embedded_item_ids(&self) -> Vec<u32> {
+        let ids: Vec<u32> = match self {
+            TileType::DisplayBlock { item_id }
+            | TileType::VendingMachine { item_id, .. }
+            | TileType::PaintingEasel { item_id, .. }
+            | TileType::FishWallMount { item_id, .. } => vec![*item_id],
+            TileType::Shelf {
+                top_left_item_id,
+                top_right_item_id,
+                bottom_left_item_id,
+                bottom_right_item_id,
+            } => vec![
+                *top_left_item_id,
+                *top_right_item_id,
+                *bottom_left_item_id,
+                *bottom_right_item_id,
+            ],
+            TileType::Mannequin {
+                clothing_1,
+                clothing_2,
+                clothing_3,
+                clothing_4,
+                clothing_5,
+                clothing_6,
+                clothing_7,
+                clothing_8,
+                clothing_9,
+                clothing_10,
+                ..
+            } => [
+                clothing_2,
+                clothing_3,
+                clothing_4,
+                clothing_5,
+                clothing_6,
+                clothing_7,
+                clothing_8,
+                clothing_9,
+                clothing_10,
+            ]
+            .into_iter()
+            .map(|id| *id as u32)
+            .chain([*clothing_1])
+            .collect(),
+            TileType::PhoneBooth {
+                clothing_1,
+                clothing_2,
+                clothing_3,
+                clothing_4,
+                clothing_5,
+                clothing_6,
+                clothing_7,
+                clothing_8,
+                clothing_9,
+            } => [
+                clothing_1, clothing_2, clothing_3, clothing_4, clothing_5, clothing_6, clothing_7,
+                clothing_8, clothing_9,
+            ]
+            .into_iter()
+            .map(|id| *id as u32)
+            .collect(),
+            TileType::FishTankPort { fishes, .. } => {
+                fishes.iter().map(|fish| fish.fish_item_id).collect()
+            }
+            TileType::StorageBlock { items } => items.iter().map(|item| item.id).collect(),
+            TileType::CookingOven { ingredients, .. } => ingredients
+                .iter()
+                .map(|ingredient| ingredient.item_id)
+                .collect(),
+            TileType::ItemSucker {
+                item_id_to_suck, ..
+            } => vec![*item_id_to_suck],
+            TileType::TrainingPort { fish_id, .. } => vec![*fish_id],
+            TileType::SewingMachine { bolt_id_list } => bolt_id_list.clone(),
+            TileType::InfinityWeatherMachine {
+                weather_machine_list,
+                ..
+            } => weather_machine_list.clone(),
+            _ => Vec::new(),
+        };
+        ids.into_iter().filter(|id| *id != 0).collect()
+    }
+
+    // Every name() value, one per variant.
+    pub const NAMES: &'static [&'static str] = &[
+        "Basic", "Door", "Sign", "Lock", "Seed", "Mailbox", "Bulletin", "Dice", "ChemicalSource", "AchievementBlock",
+        "HearthMonitor", "DonationBox", "Mannequin", "BunnyEgg", "GamePack", "GameGenerator", "XenoniteCrystal",
+        "PhoneBooth", "Crystal", "CrimeInProgress", "DisplayBlock", "VendingMachine", "GivingTree", "CountryFlag",
+        "WeatherMachine", "DataBedrock", "Spotlight", "FishTankPort", "SolarCollector", "Forge", "SteamOrgan",
+        "SilkWorm", "SewingMachine", "LobsterTrap", "PaintingEasel", "PetBattleCage", "PetTrainer", "SteamEngine",
+        "LockBot", "SpiritStorageUnit", "Shelf", "VipEntrance", "ChallangeTimer", "FishWallMount", "Portrait",
+        "GuildWeatherMachine", "FossilPrepStation", "DnaExtractor", "Howler", "ChemsynthTank", "StorageBlock",
+        "CookingOven", "AudioRack", "GeigerCharger", "AdventureBegins", "TombRobber", "BalloonOMatic", "TrainingPort",
+        "ItemSucker", "CyBot", "GuildItem", "Growscan", "ContainmentFieldPowerNode", "SpiritBoard", "StormyCloud",
+        "TemporaryPlatform", "SafeVault", "AngelicCountingCloud", "InfinityWeatherMachine", "PineappleGuzzler",
+        "KrakenGalaticBlock", "FriendsEntrance",
+    ];
+
+    // The variant's name as written in the source, e.g. "VendingMachine".
+    // Stable across releases, so it can be matched on or stored.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TileType::Basic => "Basic",
+            TileType::Door { .. } => "Door",
+            TileType::Sign { .. } => "Sign",
+            TileType::Lock { .. } => "Lock",
+            TileType::Seed { .. } => "Seed",
+            TileType::Mailbox { .. } => "Mailbox",
+            TileType::Bulletin { .. } => "Bulletin",
+            TileType::Dice { .. } => "Dice",
+            TileType::ChemicalSource { .. } => "ChemicalSource",
+            TileType::AchievementBlock { .. } => "AchievementBlock",
+            TileType::HearthMonitor { .. } => "HearthMonitor",
+            TileType::DonationBox { .. } => "DonationBox",
+            TileType::Mannequin { .. } => "Mannequin",
+            TileType::BunnyEgg { .. } => "BunnyEgg",
+            TileType::GamePack { .. } => "GamePack",
+            TileType::GameGenerator { .. } => "GameGenerator",
+            TileType::XenoniteCrystal { .. } => "XenoniteCrystal",
+            TileType::PhoneBooth { .. } => "PhoneBooth",
+            TileType::Crystal { .. } => "Crystal",
+            TileType::CrimeInProgress { .. } => "CrimeInProgress",
+            TileType::DisplayBlock { .. } => "DisplayBlock",
+            TileType::VendingMachine { .. } => "VendingMachine",
+            TileType::GivingTree { .. } => "GivingTree",
+            TileType::CountryFlag { .. } => "CountryFlag",
+            TileType::WeatherMachine { .. } => "WeatherMachine",
+            TileType::DataBedrock { .. } => "DataBedrock",
+            TileType::Spotlight => "Spotlight",
+            TileType::FishTankPort { .. } => "FishTankPort",
+            TileType::SolarCollector { .. } => "SolarCollector",
+            TileType::Forge { .. } => "Forge",
+            TileType::SteamOrgan { .. } => "SteamOrgan",
+            TileType::SilkWorm { .. } => "SilkWorm",
+            TileType::SewingMachine { .. } => "SewingMachine",
+            TileType::LobsterTrap => "LobsterTrap",
+            TileType::PaintingEasel { .. } => "PaintingEasel",
+            TileType::PetBattleCage { .. } => "PetBattleCage",
+            TileType::PetTrainer { .. } => "PetTrainer",
+            TileType::SteamEngine { .. } => "SteamEngine",
+            TileType::LockBot { .. } => "LockBot",
+            TileType::SpiritStorageUnit { .. } => "SpiritStorageUnit",
+            TileType::Shelf { .. } => "Shelf",
+            TileType::VipEntrance { .. } => "VipEntrance",
+            TileType::ChallangeTimer => "ChallangeTimer",
+            TileType::FishWallMount { .. } => "FishWallMount",
+            TileType::Portrait { .. } => "Portrait",
+            TileType::GuildWeatherMachine { .. } => "GuildWeatherMachine",
+            TileType::FossilPrepStation { .. } => "FossilPrepStation",
+            TileType::DnaExtractor => "DnaExtractor",
+            TileType::Howler => "Howler",
+            TileType::ChemsynthTank { .. } => "ChemsynthTank",
+            TileType::StorageBlock { .. } => "StorageBlock",
+            TileType::CookingOven { .. } => "CookingOven",
+            TileType::AudioRack { .. } => "AudioRack",
+            TileType::GeigerCharger { .. } => "GeigerCharger",
+            TileType::AdventureBegins => "AdventureBegins",
+            TileType::TombRobber => "TombRobber",
+            TileType::BalloonOMatic { .. } => "BalloonOMatic",
+            TileType::TrainingPort { .. } => "TrainingPort",
+            TileType::ItemSucker { .. } => "ItemSucker",
+            TileType::CyBot { .. } => "CyBot",
+            TileType::GuildItem => "GuildItem",
+            TileType::Growscan { .. } => "Growscan",
+            TileType::ContainmentFieldPowerNode { .. } => "ContainmentFieldPowerNode",
+            TileType::SpiritBoard { .. } => "SpiritBoard",
+            TileType::StormyCloud { .. } => "StormyCloud",
+            TileType::TemporaryPlatform { .. } => "TemporaryPlatform",
+            TileType::SafeVault => "SafeVault",
+            TileType::AngelicCountingCloud { .. } => "AngelicCountingCloud",
+            TileType::InfinityWeatherMachine { .. } => "InfinityWeatherMachine",
+            TileType::PineappleGuzzler => "PineappleGuzzler",
+            TileType::KrakenGalaticBlock { .. } => "KrakenGalaticBlock",
+            TileType::FriendsEntrance { .. } => "FriendsEntrance",
+        }
+    }
+
+    // A vending machine price above zero is World Locks per item, below zero
+    // it is items per World Lock (-5 sells 5 items for 1 WL). Zero means the
+    // owner never set a price, and an empty machine has item_id 0.
+    pub fn is_for_sale(&self) -> bool {
+        match self {
+            TileType::VendingMachine { item_id, price } => *item_id != 0 && *price != 0,
+            _ => false,
+        }
+    }
+
+    // Stormy cloud timers are whole seconds, like a seed's time_passed. None
+    // for any other tile type.
+    pub fn sting_duration(&self) -> Option<Duration> {
+        match self {
+            TileType::StormyCloud { sting_duration, .. } => Some(Duration::from_secs(*sting_duration as u64)),
+            _ => None,
+        }
+    }
+
+    pub fn non_solid_duration(&self) -> Option<Duration> {
+        match self {
+            TileType::StormyCloud {
+                non_solid_duration, ..
+            } => Some(Duration::from_secs(*non_solid_duration as u64)),
+            _ => None,
+        }
+    }
+
+    // How long a temporary platform has left before it vanishes, taking
+    // unknown_1 as whole seconds remaining. That reading is a best guess from
+    // the platform's behaviour in game, unknown_1 itself stays as read.
+    pub fn platform_lifetime(&self) -> Option<Duration> {
+        match self {
+            TileType::TemporaryPlatform { unknown_1 } => Some(Duration::from_secs(*unknown_1 as u64)),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FishInfo {
+    pub fish_item_id: u32,
+    pub lbs: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SilkWormColor {
+    pub a: u8,
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StorageBlockItemInfo {
+    pub id: u32,
+    pub amount: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CookingOvenIngredientInfo {
+    pub item_id: u32,
+    pub time_added: u32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CyBotCommandData {
+    pub command_id: u32,
+    pub is_command_used: u32,
+    // trails every command record; its layout hasn't been worked out, so the
+    // bytes are kept as read and written back unchanged
+    pub args: [u8; 7],
+}
+
+impl World {
+    pub(crate) fn get_extra_tile_data<B: ByteOrder>(
+        &self,
+        tile: &mut Tile,
+        data: &mut Cursor<&[u8]>,
+        item_type: u8,
+        item_database: &Arc<RwLock<ItemDatabase>>,
+    ) -> Result<(), WorldParseError> {
+        // Extra type 5, between Seed and Mailbox, isn't identified: world.dat has
+        // no such tile and items.dat doesn't record which extra type an item
+        // writes, so nothing ties an item or a layout to it. The later gaps, 13,
+        // 29, 46, 64, 69, 70, 71, 76 and 78, are likewise absent from world.dat.
+        // Reading past any of them would misalign every tile after it, so they
+        // fail the parse instead.
+        match item_type {
+            1 => {
+                // TileType::Door
+                let str_len = data.read_u16::<B>()?;
+                let mut text = vec![0; str_len as usize];
+                data.read_exact(&mut text)?;
+                let text = String::from_utf8_lossy(&text).to_string();
+                let unknown_1 = data.read_u8()?;
+
+                tile.tile_type = TileType::Door { text, unknown_1 };
+            }
+            2 => {
+                // TileType::Sign
+                let str_len = data.read_u16::<B>()?;
+                let mut text = vec![0; str_len as usize];
+                data.read_exact(&mut text)?;
+                let text = String::from_utf8_lossy(&text).to_string();
+                let _ = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::Sign { text };
+            }
+            3 => {
+                // TileType::Lock
+                let settings = LockSettings::from_u8(data.read_u8()?);
+                let owner_uid = data.read_u32::<B>()?;
+                let access_count = data.read_u32::<B>()?;
+                limits::check_len("access_uids", access_count as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut access_uids = Vec::new();
+                for _ in 0..access_count {
+                    access_uids.push(data.read_u32::<B>()?);
+                }
+                let minimum_level = data.read_u8()?;
+                let mut unknown_1 = [0; 7];
+                data.read_exact(&mut unknown_1)?;
+
+                if tile.special_item_id(&self.options) == 5814 {
+                    data.set_position(data.position() + 16);
+                }
+
+                tile.tile_type = TileType::Lock {
+                    settings,
+                    owner_uid,
+                    access_count,
+                    access_uids,
+                    minimum_level,
+                    unknown_1,
+                };
+            }
+            4 => {
+                // TileType::Seed
+                let time_passed = data.read_u32::<B>()?;
+                let item_on_tree = data.read_u8()?;
+                let ready_to_harvest = {
+                    let item_database = item_database.read().unwrap();
+                    item_database
+                        .get_item(&(tile.special_item_id(&self.options) as u32))
+                        .is_some_and(|item| item.grow_time <= time_passed)
+                };
+                let elapsed = Duration::from_secs(time_passed as u64);
+
+                tile.tile_type = TileType::Seed {
+                    time_passed,
+                    item_on_tree,
+                    ready_to_harvest,
+                    elapsed,
+                };
+            }
+            6 => {
+                // TileType::Mailbox
+                let str_len_1 = data.read_u16::<B>()?;
+                let mut unknown_1 = vec![0; str_len_1 as usize];
+                data.read_exact(&mut unknown_1)?;
+
+                let str_len_2 = data.read_u16::<B>()?;
+                let mut unknown_2 = vec![0; str_len_2 as usize];
+                data.read_exact(&mut unknown_2)?;
+
+                let str_len_3 = data.read_u16::<B>()?;
+                let mut unknown_3 = vec![0; str_len_3 as usize];
+                data.read_exact(&mut unknown_3)?;
+
+                let unknown_4 = data.read_u8()?;
+
+                tile.tile_type = TileType::Mailbox {
+                    unknown_1: String::from_utf8_lossy(&unknown_1).to_string(),
+                    unknown_2: String::from_utf8_lossy(&unknown_2).to_string(),
+                    unknown_3: String::from_utf8_lossy(&unknown_3).to_string(),
+                    unknown_4,
+                };
+            }
+            7 => {
+                // TileType::Bulletin
+                let str_len_1 = data.read_u16::<B>()?;
+                let mut unknown_1 = vec![0; str_len_1 as usize];
+                data.read_exact(&mut unknown_1)?;
+
+                let str_len_2 = data.read_u16::<B>()?;
+                let mut unknown_2 = vec![0; str_len_2 as usize];
+                data.read_exact(&mut unknown_2)?;
+
+                let str_len_3 = data.read_u16::<B>()?;
+                let mut unknown_3 = vec![0; str_len_3 as usize];
+                data.read_exact(&mut unknown_3)?;
+
+                let unknown_4 = data.read_u8()?;
+
+                tile.tile_type = TileType::Bulletin {
+                    unknown_1: String::from_utf8_lossy(&unknown_1).to_string(),
+                    unknown_2: String::from_utf8_lossy(&unknown_2).to_string(),
+                    unknown_3: String::from_utf8_lossy(&unknown_3).to_string(),
+                    unknown_4,
+                };
+            }
+            8 => {
+                // TileType::Dice
+                let symbol = data.read_u8()?;
+
+                tile.tile_type = TileType::Dice { symbol };
+            }
+            9 => {
+                // TileType::ChemicalSource
+                let time_passed = data.read_u32::<B>()?;
+                let ready_to_harvest = {
+                    let item_database = item_database.read().unwrap();
+                    item_database
+                        .get_item(&(tile.special_item_id(&self.options) as u32))
+                        .is_some_and(|item| time_passed >= item.grow_time)
+                };
+                let elapsed = Duration::from_secs(time_passed as u64);
+
+                tile.tile_type = TileType::ChemicalSource { time_passed, ready_to_harvest, elapsed };
+            }
+            10 => {
+                // TileType::AchievementBlock
+                let unknown_1 = data.read_u32::<B>()?;
+                let tile_type = data.read_u8()?;
+
+                tile.tile_type = TileType::AchievementBlock {
+                    unknown_1,
+                    tile_type,
+                };
+            }
+            11 => {
+                // TileType::HearthMonitor
+                let unknown_1 = data.read_u32::<B>()?;
+                let str_len = data.read_u16::<B>()?;
+                let mut player_name = vec![0; str_len as usize];
+                data.read_exact(&mut player_name)?;
+                let player_name = String::from_utf8_lossy(&player_name).to_string();
+
+                tile.tile_type = TileType::HearthMonitor {
+                    unknown_1,
+                    player_name,
+                };
+            }
+            12 => {
+                // TileType::DonationBox
+                let str_len_1 = data.read_u16::<B>()?;
+                let mut unknown_1 = vec![0; str_len_1 as usize];
+                data.read_exact(&mut unknown_1)?;
+
+                let str_len_2 = data.read_u16::<B>()?;
+                let mut unknown_2 = vec![0; str_len_2 as usize];
+                data.read_exact(&mut unknown_2)?;
+
+                let str_len_3 = data.read_u16::<B>()?;
+                let mut unknown_3 = vec![0; str_len_3 as usize];
+                data.read_exact(&mut unknown_3)?;
+
+                let unknown_4 = data.read_u8()?;
+
+                tile.tile_type = TileType::DonationBox {
+                    unknown_1: String::from_utf8_lossy(&unknown_1).to_string(),
+                    unknown_2: String::from_utf8_lossy(&unknown_2).to_string(),
+                    unknown_3: String::from_utf8_lossy(&unknown_3).to_string(),
+                    unknown_4,
+                };
+            }
+            14 => {
+                // TileType::Mannequin
+                let str_len = data.read_u16::<B>()?;
+                let mut text = vec![0; str_len as usize];
+                data.read_exact(&mut text)?;
+                let text = String::from_utf8_lossy(&text).to_string();
+                let unknown_1 = data.read_u8()?;
+                let clothing_1 = data.read_u32::<B>()?;
+                let clothing_2 = data.read_u16::<B>()?;
+                let clothing_3 = data.read_u16::<B>()?;
+                let clothing_4 = data.read_u16::<B>()?;
+                let clothing_5 = data.read_u16::<B>()?;
+                let clothing_6 = data.read_u16::<B>()?;
+                let clothing_7 = data.read_u16::<B>()?;
+                let clothing_8 = data.read_u16::<B>()?;
+                let clothing_9 = data.read_u16::<B>()?;
+                let clothing_10 = data.read_u16::<B>()?;
+
+                tile.tile_type = TileType::Mannequin {
+                    text,
+                    unknown_1,
+                    clothing_1,
+                    clothing_2,
+                    clothing_3,
+                    clothing_4,
+                    clothing_5,
+                    clothing_6,
+                    clothing_7,
+                    clothing_8,
+                    clothing_9,
+                    clothing_10,
+                };
+            }
+            15 => {
+                // TileType::BunnyEgg
+                let egg_placed = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::BunnyEgg { egg_placed };
+            }
+            16 => {
+                // TileType::GamePack
+                let team = data.read_u8()?;
+
+                tile.tile_type = TileType::GamePack { team };
+            }
+            17 => {
+                // TileType::GameGenerator
+                tile.tile_type = TileType::GameGenerator {};
+            }
+            18 => {
+                // TileType::XenoniteCrystal
+                let unknown_1 = data.read_u8()?;
+                let unknown_2 = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::XenoniteCrystal {
+                    unknown_1,
+                    unknown_2,
+                };
+            }
+            19 => {
+                // TileType::PhoneBooth
+                let clothing_1 = data.read_u16::<B>()?;
+                let clothing_2 = data.read_u16::<B>()?;
+                let clothing_3 = data.read_u16::<B>()?;
+                let clothing_4 = data.read_u16::<B>()?;
+                let clothing_5 = data.read_u16::<B>()?;
+                let clothing_6 = data.read_u16::<B>()?;
+                let clothing_7 = data.read_u16::<B>()?;
+                let clothing_8 = data.read_u16::<B>()?;
+                let clothing_9 = data.read_u16::<B>()?;
+
+                tile.tile_type = TileType::PhoneBooth {
+                    clothing_1,
+                    clothing_2,
+                    clothing_3,
+                    clothing_4,
+                    clothing_5,
+                    clothing_6,
+                    clothing_7,
+                    clothing_8,
+                    clothing_9,
+                };
+            }
+            20 => {
+                // TileType::Crystal
+                let str_len = data.read_u16::<B>()?;
+                let mut unknown_1 = vec![0; str_len as usize];
+                data.read_exact(&mut unknown_1)?;
+
+                tile.tile_type = TileType::Crystal {
+                    unknown_1: String::from_utf8_lossy(&unknown_1).to_string(),
+                };
+            }
+            21 => {
+                // TileType::CrimeInProgress
+                let str_len = data.read_u16::<B>()?;
+                let mut unknown_1 = vec![0; str_len as usize];
+                data.read_exact(&mut unknown_1)?;
+                let unknown_2 = data.read_u32::<B>()?;
+                let unknown_3 = data.read_u8()?;
+
+                tile.tile_type = TileType::CrimeInProgress {
+                    unknown_1: String::from_utf8_lossy(&unknown_1).to_string(),
+                    unknown_2,
+                    unknown_3,
+                };
+            }
+            22 => {
+                // TileType::Spotlight
+                tile.tile_type = TileType::Spotlight;
+            }
+            23 => {
+                // TileType::DisplayBlock
+                let item_id = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::DisplayBlock { item_id };
+            }
+            24 => {
+                // TileType::VendingMachine
+                let item_id = data.read_u32::<B>()?;
+                let price = data.read_i32::<B>()?;
+
+                tile.tile_type = TileType::VendingMachine { item_id, price };
+            }
+            25 => {
+                // TileType::FishTankPort
+                let flags = data.read_u8()?;
+                let fish_count = data.read_u32::<B>()?;
+                limits::check_len("fishes", (fish_count / 2) as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut fishes = Vec::new();
+                for _ in 0..(fish_count / 2) {
+                    let fish_item_id = data.read_u32::<B>()?;
+                    let lbs = data.read_u32::<B>()?;
+                    fishes.push(FishInfo { fish_item_id, lbs });
+                }
+                tile.tile_type = TileType::FishTankPort { flags, fishes };
+            }
+            26 => {
+                // TileType::SolarCollector
+                let mut unknown_1 = [0; 5];
+                data.read_exact(&mut unknown_1)?;
+                tile.tile_type = TileType::SolarCollector { unknown_1 };
+            }
+            27 => {
+                // TileType::Forge
+                let temperature = data.read_u32::<B>()?;
+                tile.tile_type = TileType::Forge { temperature };
+            }
+            28 => {
+                // TileType::GivingTree
+                let unknown_1 = data.read_u16::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+                tile.tile_type = TileType::GivingTree {
+                    unknown_1,
+                    unknown_2,
+                };
+            }
+            30 => {
+                // TileType::SteamOrgan
+                let instrument_type = data.read_u8()?;
+                let note = data.read_u32::<B>()?;
+                tile.tile_type = TileType::SteamOrgan {
+                    instrument_type,
+                    note,
+                };
+            }
+            31 => {
+                // TileType::SilkWorm
+                let type_ = data.read_u8()?;
+                let name_len = data.read_u16::<B>()?;
+                let mut name = vec![0; name_len as usize];
+                data.read_exact(&mut name)?;
+                let name = String::from_utf8_lossy(&name).to_string();
+                let age = data.read_u32::<B>()?;
+                let unknown_1 = data.read_u32::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+                let can_be_fed = data.read_u8()?;
+                let color = data.read_u32::<B>()?;
+                let sick_duration = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::SilkWorm {
+                    type_,
+                    name,
+                    age,
+                    unknown_1,
+                    unknown_2,
+                    can_be_fed,
+                    color: SilkWormColor {
+                        a: (color >> 24) as u8,
+                        r: ((color >> 16) & 0xFF) as u8,
+                        g: ((color >> 8) & 0xFF) as u8,
+                        b: (color & 0xFF) as u8,
+                    },
+                    sick_duration,
+                };
+            }
+            32 => {
+                // TileType::SewingMachine
+                let bolt_len = data.read_u16::<B>()?;
+                let mut bolt_id_list = Vec::new();
+                for _ in 0..bolt_len {
+                    let bolt_id = data.read_u32::<B>()?;
+                    bolt_id_list.push(bolt_id);
+                }
+                tile.tile_type = TileType::SewingMachine { bolt_id_list };
+            }
+            33 => {
+                // TileType::CountryFlag
+                let country_len = data.read_u16::<B>()?;
+                let mut country = vec![0; country_len as usize];
+                data.read_exact(&mut country)?;
+                let country = String::from_utf8_lossy(&country).to_string();
+
+                tile.tile_type = TileType::CountryFlag { country };
+            }
+            34 => {
+                // TileType::LobsterTrap
+                tile.tile_type = TileType::LobsterTrap;
+            }
+            35 => {
+                // TileType::PaintingEasel
+                let item_id = data.read_u32::<B>()?;
+                let label_len = data.read_u16::<B>()?;
+                let mut label = vec![0; label_len as usize];
+                data.read_exact(&mut label)?;
+                let label = String::from_utf8_lossy(&label).to_string();
+
+                tile.tile_type = TileType::PaintingEasel { item_id, label };
+            }
+            36 => {
+                // TileType::PetBattleCage
+                let label_len = data.read_u16::<B>()?;
+                let mut label = vec![0; label_len as usize];
+                data.read_exact(&mut label)?;
+                let label = String::from_utf8_lossy(&label).to_string();
+                let base_pet = data.read_u32::<B>()?;
+                let combined_pet_1 = data.read_u32::<B>()?;
+                let combined_pet_2 = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::PetBattleCage {
+                    label,
+                    base_pet,
+                    combined_pet_1,
+                    combined_pet_2,
+                };
+            }
+            37 => {
+                // TileType::PetTrainer
+                let name_len = data.read_u16::<B>()?;
+                let mut name = vec![0; name_len as usize];
+                data.read_exact(&mut name)?;
+                let name = String::from_utf8_lossy(&name).to_string();
+                let pet_total_count = data.read_u32::<B>()?;
+                limits::check_len("pets_id", pet_total_count as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let unknown_1 = data.read_u32::<B>()?;
+                let mut pets_id = Vec::new();
+                for _ in 0..pet_total_count {
+                    let pet_id = data.read_u32::<B>()?;
+                    pets_id.push(pet_id);
+                }
+
+                tile.tile_type = TileType::PetTrainer {
+                    name,
+                    pet_total_count,
+                    unknown_1,
+                    pets_id,
+                };
+            }
+            38 => {
+                // TileType::SteamEngine
+                let temperature = data.read_u32::<B>()?;
+                tile.tile_type = TileType::SteamEngine { temperature };
+            }
+            39 => {
+                // TileType::LockBot
+                let time_passed = data.read_u32::<B>()?;
+                tile.tile_type = TileType::LockBot { time_passed };
+            }
+            40 => {
+                // TileType::WeatherMachine
+                let settings = data.read_u32::<B>()?;
+                tile.tile_type = TileType::WeatherMachine { settings };
+            }
+            41 => {
+                // TileType::SpiritStorageUnit
+                let ghost_jar_count = data.read_u32::<B>()?;
+                tile.tile_type = TileType::SpiritStorageUnit { ghost_jar_count };
+            }
+            42 => {
+                // TileType::DataBedrock
+                let unknown_1 = data.read_u8()?;
+                let mut fields = [0; 5];
+                for field in fields.iter_mut() {
+                    *field = data.read_u32::<B>()?;
+                }
+                let [unknown_2, unknown_3, unknown_4, unknown_5, unknown_6] = fields;
+                tile.tile_type = TileType::DataBedrock {
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                    unknown_4,
+                    unknown_5,
+                    unknown_6,
+                };
+            }
+            43 => {
+                // TileType::Shelf
+                let top_left_item_id = data.read_u32::<B>()?;
+                let top_right_item_id = data.read_u32::<B>()?;
+                let bottom_left_item_id = data.read_u32::<B>()?;
+                let bottom_right_item_id = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::Shelf {
+                    top_left_item_id,
+                    top_right_item_id,
+                    bottom_left_item_id,
+                    bottom_right_item_id,
+                };
+            }
+            44 => {
+                // TileType::VipEntrance
+                let unknown_1 = data.read_u8()?;
+                let owner_uid = data.read_u32::<B>()?;
+                let access_count = data.read_u32::<B>()?;
+                limits::check_len("access_uids", access_count as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut access_uids = Vec::new();
+                for _ in 0..access_count {
+                    let uid = data.read_u32::<B>()?;
+                    access_uids.push(uid);
+                }
+
+                tile.tile_type = TileType::VipEntrance {
+                    unknown_1,
+                    owner_uid,
+                    access_uids,
+                };
+            }
+            45 => {
+                // TileType::ChallangeTimer
+                tile.tile_type = TileType::ChallangeTimer;
+            }
+            47 => {
+                // TileType::FishWallMount
+                let label_len = data.read_u16::<B>()?;
+                let mut label = vec![0; label_len as usize];
+                data.read_exact(&mut label)?;
+                let label = String::from_utf8_lossy(&label).to_string();
+                let item_id = data.read_u32::<B>()?;
+                let lb = data.read_u8()?;
+
+                tile.tile_type = TileType::FishWallMount { label, item_id, lb };
+            }
+            48 => {
+                // TileType::Portrait
+                let label_len = data.read_u16::<B>()?;
+                let mut label = vec![0; label_len as usize];
+                data.read_exact(&mut label)?;
+                let label = String::from_utf8_lossy(&label).to_string();
+                let unknown_1 = data.read_u32::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+                let unknown_3 = data.read_u32::<B>()?;
+                let unknown_4 = data.read_u32::<B>()?;
+                let face = data.read_u32::<B>()?;
+                let hat = data.read_u32::<B>()?;
+                let hair = data.read_u32::<B>()?;
+                let unknown_5 = data.read_u16::<B>()?;
+                let unknown_6 = data.read_u16::<B>()?;
+
+                tile.tile_type = TileType::Portrait {
+                    label,
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                    unknown_4,
+                    face,
+                    hat,
+                    hair,
+                    unknown_5,
+                    unknown_6,
+                };
+            }
+            49 => {
+                // TileType::GuildWeatherMachine
+                let unknown_1 = data.read_u32::<B>()?;
+                let gravity = data.read_u32::<B>()?;
+                let flags = data.read_u8()?;
+
+                tile.tile_type = TileType::GuildWeatherMachine {
+                    unknown_1,
+                    gravity,
+                    flags,
+                };
+            }
+            50 => {
+                // TileType::FossilPrepStation
+                let unknown_1 = data.read_u32::<B>()?;
+                tile.tile_type = TileType::FossilPrepStation { unknown_1 };
+            }
+            51 => {
+                // TileType::DnaExtractor
+                tile.tile_type = TileType::DnaExtractor;
+            }
+            52 => {
+                // TileType::Howler
+                tile.tile_type = TileType::Howler;
+            }
+            53 => {
+                // TileType::ChemsynthTank
+                let current_chem = data.read_u32::<B>()?;
+                let target_chem = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::ChemsynthTank {
+                    current_chem,
+                    target_chem,
+                };
+            }
+            54 => {
+                // TileType::StorageBlock
+                let data_len = data.read_u16::<B>()?;
+                let mut items = Vec::new();
+                for _ in 0..(data_len / 13) {
+                    data.set_position(data.position() + 3);
+                    let id = data.read_u32::<B>()?;
+                    data.set_position(data.position() + 2);
+                    let amount = data.read_u32::<B>()?;
+                    items.push(StorageBlockItemInfo { id, amount });
+                }
+                tile.tile_type = TileType::StorageBlock { items };
+            }
+            55 => {
+                // TileType::CookingOven
+                let temperature_level = data.read_u32::<B>()?;
+                let ingredient_count = data.read_u32::<B>()?;
+                limits::check_len("ingredients", ingredient_count as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut ingredients = Vec::new();
+                for _ in 0..ingredient_count {
+                    let item_id = data.read_u32::<B>()?;
+                    let time_added = data.read_u32::<B>()?;
+                    ingredients.push(CookingOvenIngredientInfo {
+                        item_id,
+                        time_added,
+                    });
+                }
+                let unknown_1 = data.read_u32::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+                let unknown_3 = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::CookingOven {
+                    temperature_level,
+                    ingredients,
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                };
+            }
+            56 => {
+                // TileType::AudioRack
+                let note_len = data.read_u16::<B>()?;
+                let mut note = vec![0; note_len as usize];
+                data.read_exact(&mut note)?;
+                let note = String::from_utf8_lossy(&note).to_string();
+                let volume = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::AudioRack { note, volume };
+            }
+            57 => {
+                // TileType::GeigerCharger
+                let unknown_1 = data.read_u32::<B>()?;
+                tile.tile_type = TileType::GeigerCharger { unknown_1 };
+            }
+            58 => {
+                // TileType::AdventureBegins
+                tile.tile_type = TileType::AdventureBegins;
+            }
+            59 => {
+                // TileType::TombRobber
+                tile.tile_type = TileType::TombRobber;
+            }
+            60 => {
+                // TileType::BalloonOMatic
+                let total_rarity = data.read_u32::<B>()?;
+                let team_type = data.read_u8()?;
+
+                tile.tile_type = TileType::BalloonOMatic {
+                    total_rarity,
+                    team_type,
+                };
+            }
+            61 => {
+                // TileType::TrainingPort
+                let fish_lb = data.read_u32::<B>()?;
+                let fish_status = data.read_u16::<B>()?;
+                let fish_id = data.read_u32::<B>()?;
+                let fish_total_exp = data.read_u32::<B>()?;
+                let fish_level = data.read_u32::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::TrainingPort {
+                    fish_lb,
+                    fish_status,
+                    fish_id,
+                    fish_total_exp,
+                    fish_level,
+                    unknown_2,
+                };
+            }
+            62 => {
+                // TileType::ItemSucker
+                let item_id_to_suck = data.read_u32::<B>()?;
+                let item_amount = data.read_u32::<B>()?;
+                let flags = data.read_u16::<B>()?;
+                let limit = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::ItemSucker {
+                    item_id_to_suck,
+                    item_amount,
+                    flags,
+                    limit,
+                };
+            }
+            63 => {
+                // TileType::CyBot
+                let sync_timer = data.read_u32::<B>()?;
+                let activated = data.read_u32::<B>()?;
+                let command_data_count = data.read_u32::<B>()?;
+                limits::check_len("command_datas", command_data_count as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut command_datas = Vec::new();
+                for _ in 0..command_data_count {
+                    let command_id = data.read_u32::<B>()?;
+                    let is_command_used = data.read_u32::<B>()?;
+                    let mut args = [0; 7];
+                    data.read_exact(&mut args)?;
+                    command_datas.push(CyBotCommandData {
+                        command_id,
+                        is_command_used,
+                        args,
+                    });
+                }
+                tile.tile_type = TileType::CyBot {
+                    sync_timer,
+                    activated,
+                    command_datas,
+                };
+            }
+            65 => {
+                // TileType::GuildItem
+                data.set_position(data.position() + 17);
+                tile.tile_type = TileType::GuildItem;
+            }
+            66 => {
+                // TileType::Growscan
+                let unknown_1 = data.read_u8()?;
+                tile.tile_type = TileType::Growscan { unknown_1 };
+            }
+            67 => {
+                // TileType::ContainmentFieldPowerNode
+                let ghost_jar_count = data.read_u32::<B>()?;
+                let unknown_1_size = data.read_u32::<B>()?;
+                limits::check_len("unknown_1", unknown_1_size as u64, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut unknown_1 = Vec::new();
+                for _ in 0..unknown_1_size {
+                    let value = data.read_u32::<B>()?;
+                    unknown_1.push(value);
+                }
+
+                tile.tile_type = TileType::ContainmentFieldPowerNode {
+                    ghost_jar_count,
+                    unknown_1,
+                };
+            }
+            68 => {
+                // TileType::SpiritBoard
+                let unknown_1 = data.read_u32::<B>()?;
+                let unknown_2 = data.read_u32::<B>()?;
+                let unknown_3 = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::SpiritBoard {
+                    unknown_1,
+                    unknown_2,
+                    unknown_3,
+                };
+            }
+            72 => {
+                // TileType::StormyCloud
+                let sting_duration = data.read_u32::<B>()?;
+                let is_solid = data.read_u32::<B>()?;
+                let non_solid_duration = data.read_u32::<B>()?;
+
+                tile.tile_type = TileType::StormyCloud {
+                    sting_duration,
+                    is_solid,
+                    non_solid_duration,
+                };
+            }
+            73 => {
+                // TileType::TemporaryPlatform
+                let unknown_1 = data.read_u32::<B>()?;
+                tile.tile_type = TileType::TemporaryPlatform { unknown_1 };
+            }
+            74 => {
+                // TileType::SafeVault
+                tile.tile_type = TileType::SafeVault;
+            }
+            75 => {
+                // TileType::AngelicCountingCloud
+                let is_raffling = data.read_u32::<B>()?;
+                let unknown_1 = data.read_u16::<B>()?;
+                let ascii_code = data.read_u8()?;
+
+                tile.tile_type = TileType::AngelicCountingCloud {
+                    is_raffling,
+                    unknown_1,
+                    ascii_code,
+                };
+            }
+            77 => {
+                // TileType::InfinityWeatherMachine
+                let interval_minutes = data.read_u32::<B>()?;
+                let weather_machine_list_size = data.read_u32::<B>()?;
+                let len = weather_machine_list_size as u64;
+                limits::check_len("weather_machine_list", len, limits::MAX_LIST_LEN, tile.x, tile.y)?;
+                let mut weather_machine_list = Vec::new();
+                for _ in 0..weather_machine_list_size {
+                    let weather_machine = data.read_u32::<B>()?;
+                    weather_machine_list.push(weather_machine);
+                }
+
+                tile.tile_type = TileType::InfinityWeatherMachine {
+                    interval_minutes,
+                    weather_machine_list,
+                };
+            }
+            79 => {
+                // TileType::PineappleGuzzler
+                tile.tile_type = TileType::PineappleGuzzler;
+            }
+            80 => {
+                // TileType::KrakenGalaticBlock
+                let pattern_index = data.read_u8()?;
+                let unknown_1 = data.read_u32::<B>()?;
+                let r = data.read_u8()?;
+                let g = data.read_u8()?;
+                let b = data.read_u8()?;
+
+                tile.tile_type = TileType::KrakenGalaticBlock {
+                    pattern_index,
+                    unknown_1,
+                    r,
+                    g,
+                    b,
+                };
+            }
+            81 => {
+                // TileType::FriendsEntrance
+                let owner_user_id = data.read_u32::<B>()?;
+                let unknown_1 = data.read_u16::<B>()?;
+                let unknown_2 = data.read_u16::<B>()?;
+
+                tile.tile_type = TileType::FriendsEntrance {
+                    owner_user_id,
+                    unknown_1,
+                    unknown_2,
+                };
+            }
+            _ => {
+                return Err(WorldParseError::UnknownExtraType {
+                    extra_type: item_type,
+                    item_id: tile.special_item_id(&self.options),
+                    offset: data.position().saturating_sub(1),
+                    x: tile.x,
+                    y: tile.y,
+                });
+            }
+        };
+        // a block ending in skipped bytes reads nothing past the end, so its
+        // truncation only shows here
+        if data.position() > data.get_ref().len() as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        Ok(())
+    }
+
+    // Length in bytes of the extra block starting at the cursor, type byte
+    // excluded, without moving the cursor. Variable blocks are measured from
+    // their length prefixes. None for an unknown type or a block that runs past
+    // the end of the data.
+    pub fn extra_block_len(&self, item_type: u8, item_id: u16, data: &Cursor<&[u8]>) -> Option<usize> {
+        let mut cursor = data.clone();
+        let skipped = match self.options.endianness {
+            Endian::Little => self.skip_extra_tile_data::<LittleEndian>(&mut cursor, item_type, item_id, 0, 0),
+            Endian::Big => self.skip_extra_tile_data::<BigEndian>(&mut cursor, item_type, item_id, 0, 0),
+        };
+        skipped.ok()?;
+        if cursor.position() > data.get_ref().len() as u64 {
+            return None;
+        }
+        Some((cursor.position() - data.position()) as usize)
+    }
+
+    // Moves past a tile's extra data without decoding it. The lengths mirror
+    // get_extra_tile_data arm for arm, keep the two in step. The cursor may end
+    // up past the end of the data, callers check that.
+    pub(crate) fn skip_extra_tile_data<B: ByteOrder>(
+        &self,
+        data: &mut Cursor<&[u8]>,
+        item_type: u8,
+        item_id: u16,
+        x: u32,
+        y: u32,
+    ) -> Result<(), WorldParseError> {
+        fn skip(data: &mut Cursor<&[u8]>, len: u64) {
+            data.set_position(data.position() + len);
+        }
+        fn skip_string<B: ByteOrder>(data: &mut Cursor<&[u8]>) -> Result<(), WorldParseError> {
+            let str_len = data.read_u16::<B>()?;
+            skip(data, str_len as u64);
+            Ok(())
+        }
+        fn read_count<B: ByteOrder>(data: &mut Cursor<&[u8]>) -> Result<u64, WorldParseError> {
+            Ok(data.read_u32::<B>()? as u64)
+        }
+
+        if let Some(len) = fixed_extra_block_len(item_type) {
+            skip(data, len as u64);
+            return Ok(());
+        }
+        match item_type {
+            1 => {
+                skip_string::<B>(data)?;
+                skip(data, 1);
+            }
+            2 => {
+                skip_string::<B>(data)?;
+                skip(data, 4);
+            }
+            3 => {
+                skip(data, 5);
+                let access_count = read_count::<B>(data)?;
+                skip(data, access_count * 4 + 8);
+                if item_id == 5814 {
+                    skip(data, 16);
+                }
+            }
+            6 | 7 | 12 => {
+                skip_string::<B>(data)?;
+                skip_string::<B>(data)?;
+                skip_string::<B>(data)?;
+                skip(data, 1);
+            }
+            11 => {
+                skip(data, 4);
+                skip_string::<B>(data)?;
+            }
+            14 => {
+                skip_string::<B>(data)?;
+                skip(data, 23);
+            }
+            20 | 33 => skip_string::<B>(data)?,
+            21 => {
+                skip_string::<B>(data)?;
+                skip(data, 5);
+            }
+            25 => {
+                skip(data, 1);
+                let fish_count = read_count::<B>(data)?;
+                skip(data, fish_count / 2 * 8);
+            }
+            31 => {
+                skip(data, 1);
+                skip_string::<B>(data)?;
+                skip(data, 21);
+            }
+            32 => {
+                let bolt_len = data.read_u16::<B>()? as u64;
+                skip(data, bolt_len * 4);
+            }
+            35 => {
+                skip(data, 4);
+                skip_string::<B>(data)?;
+            }
+            36 => {
+                skip_string::<B>(data)?;
+                skip(data, 12);
+            }
+            37 => {
+                skip_string::<B>(data)?;
+                let pet_total_count = read_count::<B>(data)?;
+                skip(data, 4 + pet_total_count * 4);
+            }
+            42 => skip(data, 21),
+            44 => {
+                skip(data, 5);
+                let access_count = read_count::<B>(data)?;
+                skip(data, access_count * 4);
+            }
+            47 => {
+                skip_string::<B>(data)?;
+                skip(data, 5);
+            }
+            48 => {
+                skip_string::<B>(data)?;
+                skip(data, 32);
+            }
+            54 => {
+                let data_len = data.read_u16::<B>()? as u64;
+                skip(data, data_len / 13 * 13);
+            }
+            55 => {
+                skip(data, 4);
+                let ingredient_count = read_count::<B>(data)?;
+                skip(data, ingredient_count * 8 + 12);
+            }
+            56 => {
+                skip_string::<B>(data)?;
+                skip(data, 4);
+            }
+            63 => {
+                skip(data, 8);
+                let command_data_count = read_count::<B>(data)?;
+                skip(data, command_data_count * 15);
+            }
+            67 | 77 => {
+                skip(data, 4);
+                let list_size = read_count::<B>(data)?;
+                skip(data, list_size * 4);
+            }
+            _ => {
+                return Err(WorldParseError::UnknownExtraType {
+                    extra_type: item_type,
+                    item_id,
+                    offset: data.position().saturating_sub(1),
+                    x,
+                    y,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+// Extra block length for the types whose blocks are always the same size.
+// Everything else is variable, from a string, a count or the world version:
+//   - strings: 1, 2, 6, 7, 11, 12, 14, 20, 21, 31, 33, 35, 36, 47, 48, 56
+//   - counted lists: 3, 25, 32, 37, 44, 54, 55, 63, 67, 77
+//   - version dependent: 3 (extended lock), 42 (bedrock v2)
+//   - item dependent: 3 (the guild lock carries 16 more bytes)
+pub fn fixed_extra_block_len(item_type: u8) -> Option<usize> {
+    let len = match item_type {
+        17 | 22 | 34 | 45 | 51 | 52 | 58 | 59 | 74 | 79 => 0,
+        8 | 16 | 66 => 1,
+        9 | 15 | 23 | 27 | 38 | 39 | 40 | 41 | 50 | 57 | 73 => 4,
+        4 | 10 | 18 | 26 | 30 | 60 => 5,
+        28 => 6,
+        75 => 7,
+        24 | 53 | 80 | 81 => 8,
+        49 => 9,
+        68 | 72 => 12,
+        62 => 14,
+        43 => 16,
+        65 => 17,
+        19 => 18,
+        61 => 22,
+        _ => return None,
+    };
+    Some(len)
+}
+
+#[test]
+fn test_extra_block_len() {
+    use crate::writer::{test_sample_world, write_extra_tile_data};
+
+    for endianness in [Endian::Little, Endian::Big] {
+        let mut world = test_sample_world();
+        world.options.endianness = endianness;
+        for tile in world.tiles.iter().filter(|tile| tile.tile_type != TileType::Basic) {
+            let mut out = Vec::new();
+            let options = &world.options;
+            match endianness {
+                Endian::Little => write_extra_tile_data::<LittleEndian, _>(tile, options, &mut out),
+                Endian::Big => write_extra_tile_data::<BigEndian, _>(tile, options, &mut out),
+            }
+            .unwrap();
+            let item_type = out[0];
+            let mut cursor = Cursor::new(&out[..]);
+            cursor.set_position(1);
+            let len = world.extra_block_len(item_type, tile.foreground_item_id, &cursor);
+            assert_eq!(len, Some(out.len() - 1), "{:?}", tile.tile_type);
+            assert_eq!(cursor.position(), 1);
+            if let Some(fixed) = fixed_extra_block_len(item_type) {
+                assert_eq!(fixed, out.len() - 1, "{:?}", tile.tile_type);
+            }
+            if out.len() > 1 {
+                let mut truncated = Cursor::new(&out[..out.len() - 1]);
+                truncated.set_position(1);
+                assert_eq!(world.extra_block_len(item_type, tile.foreground_item_id, &truncated), None);
+            }
+        }
+    }
+    assert_eq!(fixed_extra_block_len(1), None);
+    assert_eq!(World::new(crate::test_item_database()).extra_block_len(200, 2, &Cursor::new(&[0; 64][..])), None);
+
+    let data = std::fs::read("world.dat").unwrap();
+    let world = World::new(crate::test_item_database());
+    assert!(world.validate_bytes(&data).is_ok());
+    assert!(world.validate_bytes(&data[..data.len() / 2]).is_err());
+}
+
+#[test]
+fn test_truncated_extra_data() {
+    use crate::writer::{test_sample_world, write_extra_tile_data};
+    use crate::{test_world_bytes, version};
+
+    // every block cut short at every length fails cleanly
+    let world = test_sample_world();
+    for tile in world.tiles.iter().filter(|tile| tile.tile_type != TileType::Basic) {
+        let mut out = Vec::new();
+        write_extra_tile_data::<LittleEndian, _>(tile, &world.options, &mut out).unwrap();
+        for len in 1..out.len() {
+            let mut cursor = Cursor::new(&out[..len]);
+            cursor.set_position(1);
+            let result = world.get_extra_tile_data::<LittleEndian>(
+                &mut tile.clone(),
+                &mut cursor,
+                out[0],
+                &world.item_database,
+            );
+            assert!(
+                matches!(&result, Err(WorldParseError::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof),
+                "{:?} cut to {} bytes: {:?}",
+                tile.tile_type,
+                len,
+                result
+            );
+        }
+    }
+
+    // a mailbox whose first string claims more bytes than there are
+    let mut tiles = Vec::new();
+    for value in [2u16, 0, 0, 0x01] {
+        tiles.extend(value.to_le_bytes());
+    }
+    tiles.push(6);
+    tiles.extend(40u16.to_le_bytes());
+    tiles.extend(b"hello");
+    // names the tile and where its block starts, right after the 29 byte
+    // header and the 8 bytes before the type
+    let mut world = World::new(crate::test_item_database());
+    let err = world
+        .parse(&test_world_bytes(version::CURRENT, 1, 1, &tiles))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        WorldParseError::TruncatedExtraData {
+            extra_type: 6,
+            item_id: 2,
+            offset: 37,
+            x: 0,
+            y: 0
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "world data ends inside the extra data (type 6) of the tile at (0, 0) with item 2, starting at byte 37"
+    );
+    assert!(world.is_error);
+}
+
+#[test]
+fn test_timer_durations() {
+    let cloud = TileType::StormyCloud {
+        sting_duration: 30,
+        is_solid: 1,
+        non_solid_duration: 90,
+    };
+    assert_eq!(cloud.sting_duration(), Some(Duration::from_secs(30)));
+    assert_eq!(cloud.non_solid_duration(), Some(Duration::from_secs(90)));
+    assert_eq!(cloud.platform_lifetime(), None);
+
+    let platform = TileType::TemporaryPlatform { unknown_1: u32::MAX };
+    assert_eq!(platform.platform_lifetime(), Some(Duration::from_secs(u32::MAX as u64)));
+    assert_eq!(platform.sting_duration(), None);
+    assert_eq!(TileType::Basic.non_solid_duration(), None);
+}
+
+#[test]
+fn test_unknown_extra_type() {
+    use crate::{test_world_bytes, version};
+
+    // a spotlight has no payload, the dirt after it must still line up
+    let tiles = [0, 0, 0, 0, 0, 0, 0x01, 0x00, 22, 2, 0, 0, 0, 0, 0, 0, 0];
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&test_world_bytes(version::CURRENT, 2, 1, &tiles)).unwrap();
+    assert_eq!(world.tiles[0].tile_type, TileType::Spotlight);
+    assert_eq!(world.tiles[1].foreground_item_id, 2);
+
+    for extra_type in [5, 13, 29, 46, 64, 69, 70, 71, 76, 78, 82] {
+        let tiles = [0, 0, 0, 0, 0, 0, 0x01, 0x00, extra_type, 2, 0, 0, 0, 0, 0, 0, 0];
+        let mut world = World::new(crate::test_item_database());
+        let err = world.parse(&test_world_bytes(version::CURRENT, 2, 1, &tiles)).unwrap_err();
+        assert!(matches!(
+            err,
+            WorldParseError::UnknownExtraType { extra_type: found, x: 0, y: 0, .. } if found == extra_type
+        ));
+        assert!(world.is_error);
+        assert!(world.tiles.is_empty());
+    }
+
+    // type 200 on a dirt block in the second tile: the header is 29 bytes and
+    // the first tile 8, so its type byte is at 29 + 8 + 8
+    let tiles = [0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0x01, 0x00, 200, 1, 2, 3];
+    let mut world = World::new(crate::test_item_database());
+    let err = world.parse(&test_world_bytes(version::CURRENT, 2, 1, &tiles)).unwrap_err();
+    assert!(matches!(
+        err,
+        WorldParseError::UnknownExtraType {
+            extra_type: 200,
+            item_id: 2,
+            offset: 45,
+            x: 1,
+            y: 0
+        }
+    ));
+    assert_eq!(
+        err.to_string(),
+        "tile at (1, 0) with item 2 has unknown extra data type 200 at byte 45"
+    );
+    assert!(world.is_error);
+    let options = world.options.clone();
+    assert!(matches!(
+        World::parse_raw_tiles(&test_world_bytes(version::CURRENT, 2, 1, &tiles), options),
+        Err(WorldParseError::UnknownExtraType { offset: 45, item_id: 2, .. })
+    ));
+}
+
+#[test]
+fn test_seed_payload() {
+    // seeds of five families in a version 23 capture; each payload is exactly
+    // time_passed and item_on_tree, every later tile still lines up
+    let mut world = World::new(crate::test_item_database());
+    let data = std::fs::read("world.dat").unwrap();
+    world.parse(&data).unwrap();
+    let seeds: Vec<_> = world
+        .tiles
+        .iter()
+        .filter_map(|tile| match tile.tile_type {
+            TileType::Seed {
+                time_passed,
+                item_on_tree,
+                ..
+            } => Some((tile.foreground_item_id, time_passed, item_on_tree)),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        seeds,
+        vec![
+            (15, 31, 3),
+            (197, 2088, 2),
+            (13, 68, 2),
+            (4585, 105233, 4),
+            (4585, 105233, 4),
+            (4767, 12857, 1)
+        ]
+    );
+    let fruits: Vec<_> = world.tiles.iter().filter_map(Tile::fruit_count).collect();
+    assert_eq!(fruits, vec![3, 2, 2, 4, 4, 1]);
+    assert_eq!(world.tiles[0].fruit_count(), None);
+    assert_eq!(world.serialized_size(), data.len() - crate::fidelity::trailing_len(&world));
+}
+
+#[test]
+fn test_data_bedrock() {
+    use crate::fidelity::LossReason;
+
+    let data = std::fs::read("world.dat").unwrap();
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&data).unwrap();
+    let tile = world.get_tile(1, 59).unwrap();
+    assert_eq!(tile.foreground_item_id, 3760);
+    assert_eq!(
+        tile.tile_type,
+        TileType::DataBedrock {
+            unknown_1: 0,
+            unknown_2: 1,
+            unknown_3: 1,
+            unknown_4: 1,
+            unknown_5: 0,
+            unknown_6: u32::MAX,
+        }
+    );
+    assert!(!world
+        .losses
+        .iter()
+        .any(|loss| matches!(loss, LossReason::TileRecord { .. })));
+
+    // the payload survives a round trip
+    let mut parsed = World::new(crate::test_item_database());
+    parsed.parse(&world.serialize().unwrap()).unwrap();
+    assert_eq!(parsed.get_tile(1, 59).unwrap().tile_type, tile.tile_type);
+    assert_eq!(tile.serialized_size(), 8 + 1 + 21);
+}
+
+#[test]
+fn test_item_id_extra_fields() {
+    use crate::{test_world_bytes, version};
+    use byteorder::WriteBytesExt;
+
+    // a display block's u32 is an item id, all 46 in the capture name one
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    assert_eq!(
+        world.get_tile(58, 39).unwrap().tile_type,
+        TileType::DisplayBlock { item_id: 1262 }
+    );
+    let database = item_database.read().unwrap();
+    let display_blocks: Vec<u32> = world
+        .tiles
+        .iter()
+        .filter_map(|tile| match tile.tile_type {
+            TileType::DisplayBlock { item_id } => Some(item_id),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(display_blocks.len(), 46);
+    assert!(display_blocks.iter().all(|id| database.get_item(id).is_some()));
+
+    // a Heatwave machine, the whole u32 comes back as settings
+    let mut tiles = Vec::new();
+    tiles.write_u16::<LittleEndian>(3694).unwrap();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(0).unwrap();
+    tiles.write_u16::<LittleEndian>(0x01).unwrap();
+    tiles.write_u8(40).unwrap();
+    tiles.write_u32::<LittleEndian>(0x80FF_4020).unwrap();
+    let data = test_world_bytes(version::CURRENT, 1, 1, &tiles);
+    let mut world = World::new(Arc::clone(&item_database));
+    world.parse(&data).unwrap();
+    assert_eq!(
+        world.tiles[0].tile_type,
+        TileType::WeatherMachine { settings: 0x80FF_4020 }
+    );
+    assert_eq!(world.serialize().unwrap(), data);
+}
+
+#[test]
+fn test_names() {
+    // the sample has one tile of every extra type, Basic is the rest
+    let mut names: Vec<&str> = crate::writer::test_sample_tile_types().iter().map(TileType::name).collect();
+    names.push(TileType::Basic.name());
+    names.sort();
+    names.dedup();
+    let mut expected = TileType::NAMES.to_vec();
+    expected.sort();
+    assert_eq!(names, expected);
+}
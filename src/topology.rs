@@ -0,0 +1,132 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::world::{World, BEDROCK_ID};
+#[cfg(feature = "render")]
+use image::{ImageBuffer, Rgba};
+
+// Shape of the terrain, column by column. A tile is solid when its foreground
+// blocks movement, the same test World::occupancy uses.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Topology {
+    pub height: u32,
+    // per column, the y of the first solid tile from the top; `height` for a
+    // column with nothing solid in it
+    pub surface_heights: Vec<u32>,
+    pub avg_surface: f32,
+    // how far below its column's surface the deepest non-solid tile sits, 0
+    // when the ground under the surface is unbroken
+    pub deepest_air_pocket: u32,
+    // share of tiles above the surface
+    pub sky_ratio: f32,
+    // rows made entirely of bedrock
+    pub bedrock_rows: u32,
+}
+
+impl World {
+    pub fn topology(&self) -> Topology {
+        let solid = self.occupancy().solid;
+        let mut surface_heights = Vec::with_capacity(self.width as usize);
+        let mut deepest_air_pocket = 0;
+        for x in 0..self.width {
+            let surface = (0..self.height).find(|&y| solid.get(x, y)).unwrap_or(self.height);
+            if let Some(y) = (surface..self.height).rev().find(|&y| !solid.get(x, y)) {
+                deepest_air_pocket = deepest_air_pocket.max(y - surface);
+            }
+            surface_heights.push(surface);
+        }
+
+        let bedrock_rows = (0..self.height)
+            .filter(|&y| {
+                (0..self.width).all(|x| {
+                    self.get_tile(x, y)
+                        .is_some_and(|tile| tile.foreground_item_id == BEDROCK_ID)
+                })
+            })
+            .count() as u32;
+
+        let sky: u64 = surface_heights.iter().map(|&height| height as u64).sum();
+        let tiles = self.width as u64 * self.height as u64;
+        Topology {
+            height: self.height,
+            avg_surface: if surface_heights.is_empty() {
+                0.0
+            } else {
+                sky as f32 / surface_heights.len() as f32
+            },
+            surface_heights,
+            deepest_air_pocket,
+            sky_ratio: if tiles == 0 { 0.0 } else { sky as f32 / tiles as f32 },
+            bedrock_rows,
+        }
+    }
+}
+
+#[cfg(feature = "render")]
+impl Topology {
+    // One pixel per tile: sky above the surface line, the line itself in black
+    // and ground below it.
+    pub fn profile_image(&self) -> ImageBuffer<Rgba<u8>, Vec<u8>> {
+        const LINE_COLOR: Rgba<u8> = Rgba([0, 0, 0, 255]);
+        const GROUND_COLOR: Rgba<u8> = Rgba([121, 85, 58, 255]);
+
+        ImageBuffer::from_fn(self.surface_heights.len() as u32, self.height, |x, y| {
+            let surface = self.surface_heights[x as usize];
+            if y < surface {
+                crate::render::SKY_COLOR
+            } else if y == surface {
+                LINE_COLOR
+            } else {
+                GROUND_COLOR
+            }
+        })
+    }
+}
+
+#[test]
+fn test_topology() {
+    use crate::builder::WorldBuilder;
+
+    // the usual new world: sky down to row 24, dirt below with a cave, bedrock
+    // in the last 6 rows
+    let mut world = WorldBuilder::new().size(100, 60).build(crate::test_item_database());
+    for tile in world.tiles.iter_mut() {
+        tile.foreground_item_id = match tile.y {
+            0..=23 => 0,
+            54.. => BEDROCK_ID,
+            _ => 2,
+        };
+    }
+    // a 3 deep hill on the first ten columns and a pocket 20 rows under the surface
+    for x in 0..10 {
+        for y in 21..24 {
+            world.get_tile_mut(x, y).unwrap().foreground_item_id = 2;
+        }
+    }
+    world.get_tile_mut(50, 44).unwrap().foreground_item_id = 0;
+
+    let topology = world.topology();
+    assert_eq!(topology.surface_heights.len(), 100);
+    assert_eq!(topology.surface_heights[0], 21);
+    assert_eq!(topology.surface_heights[99], 24);
+    assert_eq!(topology.avg_surface, (10.0 * 21.0 + 90.0 * 24.0) / 100.0);
+    assert_eq!(topology.deepest_air_pocket, 20);
+    assert_eq!(topology.sky_ratio, (10.0 * 21.0 + 90.0 * 24.0) / 6000.0);
+    assert_eq!(topology.bedrock_rows, 6);
+
+    // a mass cleared world is all sky
+    let cleared = WorldBuilder::new().size(4, 3).build(crate::test_item_database());
+    let topology = cleared.topology();
+    assert_eq!(topology.surface_heights, vec![3; 4]);
+    assert_eq!((topology.sky_ratio, topology.deepest_air_pocket, topology.bedrock_rows), (1.0, 0, 0));
+
+    #[cfg(feature = "render")]
+    {
+        let image = world.topology().profile_image();
+        assert_eq!(image.dimensions(), (100, 60));
+        assert_eq!(*image.get_pixel(0, 20), crate::render::SKY_COLOR);
+        assert_eq!(image.get_pixel(0, 21).0, [0, 0, 0, 255]);
+        assert_eq!(image.get_pixel(99, 24).0, [0, 0, 0, 255]);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::dropped::{Dropped, DroppedItem};
+use crate::error::SerializeError;
+use crate::occupancy::Bitmap;
+use crate::options::ParseOptions;
+use crate::patch::{PatchError, PATCH_FORMAT, PATCH_MAGIC};
+use crate::tile::Tile;
+use crate::world::World;
+use crate::writer;
+use byteorder::{LittleEndian, WriteBytesExt};
+use std::collections::HashMap;
+
+// What changed since the last drain, ready to send to a client that holds a
+// copy of the world as it was then.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileUpdatePacket {
+    // the tile as it is now, `record` encoded as in a world file of the
+    // world's version (little endian, with its CBOR payload)
+    Tile { x: u32, y: u32, record: Vec<u8> },
+    // Turns the previous dropped items into the current ones: first remove
+    // the items at `removed` (indexes into the previous list, ascending),
+    // then insert each of `added` at its index (into the new list,
+    // ascending). Order is kept, so serialize gives the same bytes.
+    Dropped {
+        removed: Vec<u32>,
+        added: Vec<(u32, DroppedItem)>,
+        last_dropped_item_uid: u32,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct UpdateTracker {
+    // sized to the world when it was last drained, a size that no longer
+    // matches (the world was reset or parsed) means every tile changed
+    dirty: Bitmap,
+    dropped: Dropped,
+}
+
+impl Tile {
+    // The tile as a world parsed with `options` stores it, the record a
+    // TileUpdatePacket::Tile carries. drain_pending_updates encodes through
+    // this with the world's options.
+    pub fn to_packet_bytes(&self, options: &ParseOptions) -> Result<Vec<u8>, SerializeError> {
+        writer::tile_record(self, options)
+    }
+}
+
+impl World {
+    // Starts collecting changes for drain_pending_updates, from the world as
+    // it is now. Every mutation API marks the tiles it touches: edit_tile,
+    // get_tile_mut and set_parent (whether or not anything is changed through
+    // them), tiles_iter_mut (every tile), apply_patch_bytes, apply_event,
+    // set_flags_in_rect, paint_rect, advance_time, sanitize and update_tile.
+    // A parse marks the whole world.
+    // Writes straight into `tiles` bypass all of them and go unseen.
+    pub fn track_updates(&mut self) {
+        self.updates = Some(UpdateTracker {
+            dirty: Bitmap::new(self.width, self.height),
+            dropped: self.dropped.clone(),
+        });
+    }
+
+    pub fn stop_tracking_updates(&mut self) {
+        self.updates = None;
+    }
+
+    // One packet per tile marked since the last drain (or since tracking
+    // started), in reading order, then one for the dropped items if they
+    // changed. Empty when nothing changed or nothing is being tracked. A tile
+    // too long to encode fails the drain and leaves everything marked.
+    pub fn drain_pending_updates(&mut self) -> Result<Vec<TileUpdatePacket>, SerializeError> {
+        let Some(tracker) = &mut self.updates else {
+            return Ok(Vec::new());
+        };
+        let mut packets = Vec::new();
+        let everything = (tracker.dirty.width(), tracker.dirty.height()) != (self.width, self.height);
+        for tile in &self.tiles {
+            if everything || tracker.dirty.get(tile.x, tile.y) {
+                packets.push(TileUpdatePacket::Tile {
+                    x: tile.x,
+                    y: tile.y,
+                    record: tile.to_packet_bytes(&self.options)?,
+                });
+            }
+        }
+        tracker.dirty = Bitmap::new(self.width, self.height);
+
+        let previous = std::mem::replace(&mut tracker.dropped, self.dropped.clone());
+        let (removed, added) = dropped_delta(&previous.items, &self.dropped.items);
+        if !removed.is_empty()
+            || !added.is_empty()
+            || previous.last_dropped_item_uid != self.dropped.last_dropped_item_uid
+        {
+            packets.push(TileUpdatePacket::Dropped {
+                removed,
+                added,
+                last_dropped_item_uid: self.dropped.last_dropped_item_uid,
+            });
+        }
+        Ok(packets)
+    }
+
+    // Applies drained packets to a copy of the world they came from, which
+    // must have the same size, version and cbor_detection. Tile packets are
+    // decoded first, so a bad record leaves the world untouched.
+    pub fn apply_update_packets(&mut self, packets: &[TileUpdatePacket]) -> Result<(), PatchError> {
+        // tile records are patch records, so they go through apply_patch_bytes
+        let mut patch = PATCH_MAGIC.to_vec();
+        patch.write_u16::<LittleEndian>(PATCH_FORMAT).unwrap();
+        patch.write_u16::<LittleEndian>(self.version).unwrap();
+        patch.write_u32::<LittleEndian>(self.width).unwrap();
+        patch.write_u32::<LittleEndian>(self.height).unwrap();
+        let records: Vec<(u32, u32, &Vec<u8>)> = packets
+            .iter()
+            .filter_map(|packet| match packet {
+                TileUpdatePacket::Tile { x, y, record } => Some((*x, *y, record)),
+                TileUpdatePacket::Dropped { .. } => None,
+            })
+            .collect();
+        patch.write_u32::<LittleEndian>(records.len() as u32).unwrap();
+        for (x, y, record) in records {
+            patch.write_u32::<LittleEndian>(x).unwrap();
+            patch.write_u32::<LittleEndian>(y).unwrap();
+            patch.write_u32::<LittleEndian>(record.len() as u32).unwrap();
+            patch.extend_from_slice(record);
+        }
+        self.apply_patch_bytes(&patch)?;
+
+        for packet in packets {
+            if let TileUpdatePacket::Dropped {
+                removed,
+                added,
+                last_dropped_item_uid,
+            } = packet
+            {
+                for &index in removed.iter().rev() {
+                    if (index as usize) < self.dropped.items.len() {
+                        self.dropped.items.remove(index as usize);
+                    }
+                }
+                for (index, item) in added {
+                    let index = (*index as usize).min(self.dropped.items.len());
+                    self.dropped.items.insert(index, item.clone());
+                }
+                self.dropped.items_count = self.dropped.items.len() as u32;
+                self.dropped.last_dropped_item_uid = *last_dropped_item_uid;
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn mark_dirty(&mut self, index: usize) {
+        self.revision = self.revision.wrapping_add(1);
+        if let Some(tracker) = &mut self.updates {
+            if self.width > 0 {
+                let index = index as u32;
+                tracker.dirty.set(index % self.width, index / self.width, true);
+            }
+        }
+    }
+
+    pub(crate) fn mark_all_dirty(&mut self) {
+        self.revision = self.revision.wrapping_add(1);
+        if let Some(tracker) = &mut self.updates {
+            // a size that doesn't match reads as everything changed
+            tracker.dirty = Bitmap::new(0, 0);
+        }
+    }
+}
+
+// Items are matched by their bytes, in order: each previous item keeps the
+// first equal current item after the last one kept. Whatever isn't kept was
+// removed or added.
+fn dropped_delta(previous: &[DroppedItem], current: &[DroppedItem]) -> (Vec<u32>, Vec<(u32, DroppedItem)>) {
+    let key = |item: &DroppedItem| (item.id, item.x.to_bits(), item.y.to_bits(), item.count, item.flags, item.uid);
+    let mut positions: HashMap<_, Vec<usize>> = HashMap::new();
+    for (index, item) in current.iter().enumerate() {
+        positions.entry(key(item)).or_default().push(index);
+    }
+
+    let mut kept = vec![false; current.len()];
+    let mut removed = Vec::new();
+    let mut next = 0;
+    for (index, item) in previous.iter().enumerate() {
+        let found = positions.get(&key(item)).and_then(|positions| {
+            let at = positions.partition_point(|&position| position < next);
+            positions.get(at).copied()
+        });
+        match found {
+            Some(position) => {
+                kept[position] = true;
+                next = position + 1;
+            }
+            None => removed.push(index as u32),
+        }
+    }
+    let added = current
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| !kept[*index])
+        .map(|(index, item)| (index as u32, item.clone()))
+        .collect();
+    (removed, added)
+}
+
+#[test]
+fn test_drain_pending_updates() {
+    use crate::edit::PaintColor;
+    use crate::event::WorldEvent;
+    use crate::flags::TileFlags;
+    use crate::sanitize::SanitizeOptions;
+    use crate::tile::Rect;
+    use crate::tile_extra::TileType;
+
+    let item_database = crate::test_item_database();
+    let database = item_database.read().unwrap();
+    let mut world = World::new(std::sync::Arc::clone(&item_database));
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let mut copy = world.clone();
+
+    // nothing is collected before tracking starts
+    world.edit_tile(0, 0).unwrap().foreground_item_id = 2;
+    copy.edit_tile(0, 0).unwrap().foreground_item_id = 2;
+    assert!(world.drain_pending_updates().unwrap().is_empty());
+    world.track_updates();
+    assert!(world.drain_pending_updates().unwrap().is_empty());
+
+    let mut drain_into_copy = |world: &mut World, tiles: usize| {
+        let packets = world.drain_pending_updates().unwrap();
+        let tile_packets = packets
+            .iter()
+            .filter(|packet| matches!(packet, TileUpdatePacket::Tile { .. }))
+            .count();
+        assert_eq!(tile_packets, tiles);
+        copy.apply_update_packets(&packets).unwrap();
+        assert_eq!(copy.serialize().unwrap(), world.serialize().unwrap());
+        assert!(world.drain_pending_updates().unwrap().is_empty());
+    };
+
+    world.edit_tile(1, 0).unwrap().foreground_item_id = 2;
+    world.paint_rect(Rect::new(0, 0, 3, 1), PaintColor::Red, true);
+    world.get_tile_mut(10, 10).unwrap().background_item_id = 14;
+    world.set_flags_in_rect(Rect::new(20, 20, 2, 2), TileFlags::from_u16(0x400), TileFlags::default(), false);
+    drain_into_copy(&mut world, 7);
+
+    let mut sign = world.get_tile(5, 5).unwrap().clone();
+    sign.foreground_item_id = 20;
+    sign.tile_type = TileType::Sign {
+        text: "streamed".to_string(),
+    };
+    sign.flags = TileFlags::from_u16(0x01);
+    sign.flags_number = 0x01;
+    let uid = world.dropped.items[2].uid;
+    let events = [
+        WorldEvent::TileChanged { x: 5, y: 5, tile: sign },
+        WorldEvent::ItemCollected(uid),
+        WorldEvent::ItemDropped(DroppedItem {
+            id: 2,
+            x: 64.0,
+            y: 64.0,
+            count: 3,
+            flags: 0,
+            uid: 1000,
+        }),
+    ];
+    for event in events {
+        world.apply_event(event, &database).unwrap();
+    }
+    world.set_parent(6, 5, 49, 50).unwrap();
+    // in place edits and reordering of the dropped items
+    world.dropped.items[0].count = 99;
+    world.dropped.items.swap(1, 3);
+    world.dropped.add(2, 8.0, 8.0, 1, 0);
+    drain_into_copy(&mut world, 2);
+
+    // every growing tile's timer moves
+    let growing = world
+        .tiles
+        .iter()
+        .filter(|tile| matches!(tile.tile_type, TileType::Seed { .. } | TileType::ChemicalSource { .. }))
+        .count();
+    world.advance_time(60, &database).unwrap();
+    drain_into_copy(&mut world, growing);
+
+    world.sanitize(SanitizeOptions::default());
+    drain_into_copy(&mut world, 6000);
+
+    // a parse replaces everything
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    drain_into_copy(&mut world, 6000);
+
+    let record = world.get_tile(49, 44).unwrap().to_packet_bytes(&world.options).unwrap();
+    assert_eq!(record.len(), world.get_tile(49, 44).unwrap().serialized_size());
+
+    // drained records and to_packet_bytes agree under the world's options: a
+    // 5814 lock keyed by its background carries 16 more bytes
+    let mut keyed = world.clone();
+    keyed.options.background_special_items = true;
+    keyed.track_updates();
+    let mut tile = keyed.edit_tile(3, 3).unwrap();
+    tile.foreground_item_id = 0;
+    tile.background_item_id = 5814;
+    tile.flags_number = 0x01;
+    tile.flags = TileFlags::from_u16(0x01);
+    tile.tile_type = TileType::Lock {
+        settings: crate::flags::LockSettings::from_u8(0),
+        owner_uid: 1,
+        access_count: 0,
+        access_uids: Vec::new(),
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    drop(tile);
+    let packets = keyed.drain_pending_updates().unwrap();
+    let lock = keyed.get_tile(3, 3).unwrap();
+    let own = lock.to_packet_bytes(&keyed.options).unwrap();
+    assert_eq!(packets[0], TileUpdatePacket::Tile { x: 3, y: 3, record: own.clone() });
+    assert_eq!(own.len(), lock.to_packet_bytes(&ParseOptions::default()).unwrap().len() + 16);
+
+    // a tile past the end of a short tile vector is refused, not indexed
+    let mut short = world.clone();
+    short.tiles.truncate(10);
+    short.track_updates();
+    let revision = short.revision;
+    assert!(short.get_tile_mut(49, 44).is_none());
+    assert_eq!(short.revision, revision);
+    assert!(short.drain_pending_updates().unwrap().is_empty());
+    let packets = [TileUpdatePacket::Tile { x: 49, y: 44, record }];
+    assert!(matches!(
+        short.apply_update_packets(&packets),
+        Err(PatchError::TileOutOfBounds { x: 49, y: 44 })
+    ));
+}
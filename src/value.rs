@@ -0,0 +1,61 @@
+use crate::world::World;
+use std::collections::HashMap;
+
+impl World {
+    // Rough worth of the world: every non-blank foreground plus every dropped
+    // item times its count. An item's value comes from `prices` when it's
+    // listed there, otherwise from its rarity in the item database, so without
+    // prices this is a rarity-weighted item count. Unknown items are worth 0.
+    pub fn estimate_value(&self, prices: Option<&HashMap<u16, u64>>) -> u64 {
+        let item_database = self.item_database.read().unwrap();
+        let value_of = |item_id: u16| -> u64 {
+            if let Some(price) = prices.and_then(|prices| prices.get(&item_id)) {
+                return *price;
+            }
+            item_database
+                .get_item(&(item_id as u32))
+                .map_or(0, |item| item.rarity as u64)
+        };
+
+        let tiles: u64 = self
+            .tiles
+            .iter()
+            .filter(|tile| tile.foreground_item_id != 0)
+            .map(|tile| value_of(tile.foreground_item_id))
+            .sum();
+        let dropped: u64 = self
+            .dropped
+            .items
+            .iter()
+            .map(|item| value_of(item.id) * item.count as u64)
+            .sum();
+        tiles + dropped
+    }
+}
+
+#[test]
+fn test_estimate_value() {
+    use crate::builder::WorldBuilder;
+    use crate::dropped::DroppedItem;
+
+    let item_database = crate::test_item_database();
+    let rarity = |item_id: u32| item_database.read().unwrap().get_item(&item_id).unwrap().rarity as u64;
+    let mut world = WorldBuilder::new().size(3, 1).build(item_database.clone());
+    assert_eq!(world.estimate_value(None), 0);
+
+    world.tiles[0].foreground_item_id = 2;
+    world.tiles[1].foreground_item_id = 2;
+    world.tiles[2].background_item_id = 14;
+    world.dropped.items.push(DroppedItem {
+        id: 554,
+        x: 0.0,
+        y: 0.0,
+        count: 3,
+        flags: 0,
+        uid: 1,
+    });
+    assert_eq!(world.estimate_value(None), 2 * rarity(2) + 3 * rarity(554));
+
+    let prices: HashMap<u16, u64> = [(2, 100)].into_iter().collect();
+    assert_eq!(world.estimate_value(Some(&prices)), 200 + 3 * rarity(554));
+}
@@ -0,0 +1,8 @@
+// Known world format versions. Only V23 is backed by a capture (the bundled
+// world.dat). No older capture shows where the layout changed, so every
+// version is read and written with the V23 layout and only recorded in
+// World::version.
+
+pub const V23: u16 = 0x17;
+
+pub const CURRENT: u16 = V23;
@@ -0,0 +1,309 @@
+use crate::color;
+use crate::tile::TileDiff;
+use crate::tile_extra::TileType;
+use crate::world::World;
+use gtitem_r::structs::ItemDatabase;
+use std::collections::HashMap;
+
+// Short names of the tile flag bits, lowest first.
+const FLAG_NAMES: [&str; 16] = [
+    "extra data",
+    "parent",
+    "spliced",
+    "seeds too",
+    "seedling",
+    "flipped",
+    "on",
+    "public",
+    "background on",
+    "alt mode",
+    "wet",
+    "glued",
+    "on fire",
+    "red",
+    "green",
+    "blue",
+];
+
+// Where an item's sprite is: a cell of 32x32 pixels in one of the atlases
+// listed by ViewModel::atlas.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Icon {
+    pub atlas: u16,
+    pub x: u8,
+    pub y: u8,
+}
+
+// One tile as a viewer draws it. Copy and free of strings so a frame can walk
+// every cell without allocating; names come from ViewModel::item_name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cell {
+    pub foreground_item_id: u16,
+    pub background_item_id: u16,
+    // the minimap color, the same as render::tile_color
+    pub color: [u8; 4],
+    // sprite of the foreground, or of the background when there's none
+    pub icon: Option<Icon>,
+    pub flags_number: u16,
+}
+
+impl Cell {
+    // The set flags as words, e.g. "glued, on fire, red"; empty for none.
+    pub fn flags_summary(&self) -> String {
+        FLAG_NAMES
+            .iter()
+            .enumerate()
+            .filter(|(bit, _)| self.flags_number & (1 << bit) != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntityKind {
+    Lock,
+    Door,
+    Sign,
+}
+
+// Something a viewer lists for the user to pick: its label is the text on a
+// door or sign, and the lock's item name for locks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub x: u32,
+    pub y: u32,
+    pub label: String,
+}
+
+// A world pre-resolved for a desktop viewer. Everything a frame needs is
+// looked up once here, so drawing never touches the item database; edits
+// come in through apply_diff, which only redoes the tiles that changed.
+#[derive(Debug, Clone, Default)]
+pub struct ViewModel {
+    pub width: u32,
+    pub height: u32,
+    cells: Vec<Cell>,
+    names: HashMap<u16, String>,
+    atlases: Vec<String>,
+    // sorted in reading order
+    entities: Vec<Entity>,
+}
+
+impl ViewModel {
+    pub fn from_world(world: &World, item_database: &ItemDatabase) -> ViewModel {
+        let mut model = ViewModel {
+            width: world.width,
+            height: world.height,
+            ..Default::default()
+        };
+        for tile in &world.tiles {
+            let cell = model.resolve(tile.foreground_item_id, tile.background_item_id, item_database);
+            model.cells.push(Cell {
+                flags_number: tile.flags_number,
+                ..cell
+            });
+            if let Some(entity) = model.entity_of(tile.x, tile.y, &tile.tile_type, tile.foreground_item_id) {
+                model.entities.push(entity);
+            }
+        }
+        model
+    }
+
+    // Updates the cells and entities of the diffed tiles. Returns how many
+    // cells changed; diffs outside the model are skipped.
+    pub fn apply_diff(&mut self, diffs: &[TileDiff], item_database: &ItemDatabase) -> usize {
+        let mut changed = 0;
+        for diff in diffs {
+            if diff.x >= self.width || diff.y >= self.height {
+                continue;
+            }
+            let tile = &diff.after;
+            let cell = Cell {
+                flags_number: tile.flags_number,
+                ..self.resolve(tile.foreground_item_id, tile.background_item_id, item_database)
+            };
+            let index = diff.y as usize * self.width as usize + diff.x as usize;
+            // a model from a world with fewer tiles than its size has no cell here
+            let Some(slot) = self.cells.get_mut(index) else {
+                continue;
+            };
+            if *slot != cell {
+                *slot = cell;
+                changed += 1;
+            }
+
+            let position = self.entities.binary_search_by_key(&(diff.y, diff.x), |entity| (entity.y, entity.x));
+            let entity = self.entity_of(diff.x, diff.y, &tile.tile_type, tile.foreground_item_id);
+            match (position, entity) {
+                (Ok(position), Some(entity)) => self.entities[position] = entity,
+                (Ok(position), None) => {
+                    self.entities.remove(position);
+                }
+                (Err(position), Some(entity)) => self.entities.insert(position, entity),
+                (Err(_), None) => {}
+            }
+        }
+        changed
+    }
+
+    pub fn cell(&self, x: u32, y: u32) -> Option<Cell> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.cells.get((y * self.width + x) as usize).copied()
+    }
+
+    // All cells in reading order.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    pub fn row(&self, y: u32) -> Option<&[Cell]> {
+        if y >= self.height {
+            return None;
+        }
+        let start = (y * self.width) as usize;
+        self.cells.get(start..start + self.width as usize)
+    }
+
+    // The name of any item a cell or entity refers to; empty for blank and
+    // ids the item database didn't know.
+    pub fn item_name(&self, item_id: u16) -> &str {
+        self.names.get(&item_id).map_or("", String::as_str)
+    }
+
+    // The texture file an Icon's atlas index refers to.
+    pub fn atlas(&self, index: u16) -> Option<&str> {
+        self.atlases.get(index as usize).map(String::as_str)
+    }
+
+    pub fn entities(&self) -> &[Entity] {
+        &self.entities
+    }
+
+    pub fn entity_at(&self, x: u32, y: u32) -> Option<&Entity> {
+        let position = self.entities.binary_search_by_key(&(y, x), |entity| (entity.y, entity.x)).ok()?;
+        Some(&self.entities[position])
+    }
+
+    fn resolve(&mut self, foreground: u16, background: u16, item_database: &ItemDatabase) -> Cell {
+        let mut icon = None;
+        for item_id in [foreground, background] {
+            let Some(item) = item_database.get_item(&(item_id as u32)).filter(|_| item_id != 0) else {
+                continue;
+            };
+            self.names.entry(item_id).or_insert_with(|| item.name.clone());
+            if icon.is_none() {
+                let atlas = match self.atlases.iter().position(|atlas| *atlas == item.texture_file_name) {
+                    Some(atlas) => atlas,
+                    None => {
+                        self.atlases.push(item.texture_file_name.clone());
+                        self.atlases.len() - 1
+                    }
+                };
+                icon = Some(Icon {
+                    atlas: atlas as u16,
+                    x: item.texture_x,
+                    y: item.texture_y,
+                });
+            }
+        }
+        Cell {
+            foreground_item_id: foreground,
+            background_item_id: background,
+            color: color::tile_rgba(item_database, foreground, background),
+            icon,
+            flags_number: 0,
+        }
+    }
+
+    fn entity_of(&self, x: u32, y: u32, tile_type: &TileType, foreground: u16) -> Option<Entity> {
+        let (kind, label) = match tile_type {
+            TileType::Lock { .. } => (EntityKind::Lock, self.item_name(foreground).to_string()),
+            TileType::Door { text, .. } => (EntityKind::Door, text.clone()),
+            TileType::Sign { text } => (EntityKind::Sign, text.clone()),
+            _ => return None,
+        };
+        Some(Entity { kind, x, y, label })
+    }
+}
+
+#[test]
+fn test_view_model() {
+    let item_database = crate::test_item_database();
+    let mut world = World::new(std::sync::Arc::clone(&item_database));
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let database = item_database.read().unwrap();
+    let mut model = ViewModel::from_world(&world, &database);
+    // atlas indexes depend on the order items were first seen, compare names
+    let resolved = |model: &ViewModel| {
+        let cells: Vec<_> = model
+            .cells()
+            .iter()
+            .map(|cell| {
+                let icon = cell.icon.map(|icon| (model.atlas(icon.atlas).unwrap().to_string(), icon.x, icon.y));
+                (cell.foreground_item_id, cell.background_item_id, cell.color, icon, cell.flags_number)
+            })
+            .collect();
+        (cells, model.entities().to_vec())
+    };
+
+    assert_eq!(model.cells().len(), 6000);
+    assert_eq!(model.row(44).unwrap()[49], model.cell(49, 44).unwrap());
+    assert_eq!(model.cell(100, 0), None);
+    assert_eq!(model.row(60), None);
+    let door = model.cell(49, 44).unwrap();
+    assert_eq!(door.foreground_item_id, 6);
+    assert_eq!(model.item_name(6), "Main Door");
+    let icon = door.icon.unwrap();
+    assert_eq!(model.atlas(icon.atlas), Some("tiles_page1.rttex"));
+    assert_eq!(door.flags_summary(), "extra data");
+    for tile in world.tiles.iter().step_by(97) {
+        let cell = model.cell(tile.x, tile.y).unwrap();
+        let color = crate::color::tile_rgba(&database, tile.foreground_item_id, tile.background_item_id);
+        assert_eq!(cell.color, color);
+        assert_eq!(cell.flags_number, tile.flags_number);
+    }
+
+    // every lock, door and sign, in reading order
+    let locks = model.entities().iter().filter(|entity| entity.kind == EntityKind::Lock).count();
+    assert_eq!(locks, 1);
+    assert_eq!(model.entities().len(), 1 + 27 + 6);
+    assert!(model
+        .entities()
+        .windows(2)
+        .all(|pair| (pair[0].y, pair[0].x) < (pair[1].y, pair[1].x)));
+    assert_eq!(model.entity_at(49, 50).unwrap().kind, EntityKind::Lock);
+    assert_eq!(model.entity_at(49, 44).unwrap().label, "EXIT");
+
+    // a sign goes up where the door was, another tile gets painted
+    let mut after = world.clone();
+    let door_tile = after.get_tile_mut(49, 44).unwrap();
+    door_tile.foreground_item_id = 20;
+    door_tile.tile_type = TileType::Sign {
+        text: "moved".to_string(),
+    };
+    after.edit_tile(0, 0).unwrap().flags.painted_red = true;
+    let diffs = world.diff(&after);
+    assert_eq!(model.apply_diff(&diffs, &database), 2);
+    assert_eq!(resolved(&model), resolved(&ViewModel::from_world(&after, &database)));
+    assert_eq!(model.entity_at(49, 44).unwrap().label, "moved");
+    assert!(model.cell(0, 0).unwrap().flags_summary().ends_with("red"));
+    assert_eq!(model.apply_diff(&diffs, &database), 0);
+
+    // and the sign is taken down again
+    let diffs = after.diff(&world);
+    model.apply_diff(&diffs, &database);
+    assert_eq!(resolved(&model), resolved(&ViewModel::from_world(&world, &database)));
+
+    // diffs past the cells a short world has are skipped
+    let mut short = world.clone();
+    short.tiles.truncate(100);
+    let mut model = ViewModel::from_world(&short, &database);
+    let diffs = world.diff(&after);
+    assert_eq!(model.apply_diff(&diffs, &database), 1);
+    assert_eq!(model.cells().len(), 100);
+    assert_eq!(model.entity_at(49, 44), None);
+}
@@ -0,0 +1,198 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[repr(u16)]
+pub enum WeatherType {
+    Default,
+    Sunset,
+    Night,
+    Desert,
+    Sunny,
+    RainyCity,
+    Harvest,
+    Mars,
+    Spooky,
+    Maw,
+    Blank,
+    Snowy,
+    Growch,
+    GrowchHappy,
+    Undersea,
+    Warp,
+    Comet,
+    Comet2,
+    Party,
+    Pineapple,
+    SnowyNight,
+    Spring,
+    Wolf,
+    NotInitialized,
+    PurpleHaze,
+    FireHaze,
+    GreenHaze,
+    AquaHaze,
+    CustomHaze,
+    CustomItems,
+    Pagoda,
+    Apocalypse,
+    Jungle,
+    BalloonWarz,
+    Background,
+    Autumn,
+    Hearth,
+    StPatricks,
+    IceAge,
+    Volcano,
+    FloatingIslands,
+    Mascot,
+    DigitalRain,
+    MonoChrome,
+    Treasure,
+    Surgery,
+    Bountiful,
+    Meteor,
+    Stars,
+    Ascended,
+    Destroyed,
+    GrowtopiaSign,
+    Dungeon,
+    LegendaryCity,
+    BloodDragon,
+    PopCity,
+    Anzu,
+    TmntCity,
+    RadCity,
+    Plaze,
+    Nebula,
+    ProtoStar,
+    DarkMountains,
+    Ac15,
+    MountGrowMore,
+    CrackInReality,
+    LnyNian,
+    RaymanLock,
+    Steampunk,
+    RealmOfSpirits,
+    Blackhole,
+    Gems,
+    HolidayHaven,
+    FenyxLock,
+    EnchantedLock,
+    RoyalEnchantedLock,
+    NeptunesAtlantis,
+    PinuskiPetalPerfectHaven,
+    Candyland,
+}
+
+impl From<u16> for WeatherType {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => WeatherType::Default,
+            1 => WeatherType::Sunset,
+            2 => WeatherType::Night,
+            3 => WeatherType::Desert,
+            4 => WeatherType::Sunny,
+            5 => WeatherType::RainyCity,
+            6 => WeatherType::Harvest,
+            7 => WeatherType::Mars,
+            8 => WeatherType::Spooky,
+            9 => WeatherType::Maw,
+            10 => WeatherType::Blank,
+            11 => WeatherType::Snowy,
+            12 => WeatherType::Growch,
+            13 => WeatherType::GrowchHappy,
+            14 => WeatherType::Undersea,
+            15 => WeatherType::Warp,
+            16 => WeatherType::Comet,
+            17 => WeatherType::Comet2,
+            18 => WeatherType::Party,
+            19 => WeatherType::Pineapple,
+            20 => WeatherType::SnowyNight,
+            21 => WeatherType::Spring,
+            22 => WeatherType::Wolf,
+            23 => WeatherType::NotInitialized,
+            24 => WeatherType::PurpleHaze,
+            25 => WeatherType::FireHaze,
+            26 => WeatherType::GreenHaze,
+            27 => WeatherType::AquaHaze,
+            28 => WeatherType::CustomHaze,
+            29 => WeatherType::CustomItems,
+            30 => WeatherType::Pagoda,
+            31 => WeatherType::Apocalypse,
+            32 => WeatherType::Jungle,
+            33 => WeatherType::BalloonWarz,
+            34 => WeatherType::Background,
+            35 => WeatherType::Autumn,
+            36 => WeatherType::Hearth,
+            37 => WeatherType::StPatricks,
+            38 => WeatherType::IceAge,
+            39 => WeatherType::Volcano,
+            40 => WeatherType::FloatingIslands,
+            41 => WeatherType::Mascot,
+            42 => WeatherType::DigitalRain,
+            43 => WeatherType::MonoChrome,
+            44 => WeatherType::Treasure,
+            45 => WeatherType::Surgery,
+            46 => WeatherType::Bountiful,
+            47 => WeatherType::Meteor,
+            48 => WeatherType::Stars,
+            49 => WeatherType::Ascended,
+            50 => WeatherType::Destroyed,
+            51 => WeatherType::GrowtopiaSign,
+            52 => WeatherType::Dungeon,
+            53 => WeatherType::LegendaryCity,
+            54 => WeatherType::BloodDragon,
+            55 => WeatherType::PopCity,
+            56 => WeatherType::Anzu,
+            57 => WeatherType::TmntCity,
+            58 => WeatherType::RadCity,
+            59 => WeatherType::Plaze,
+            60 => WeatherType::Nebula,
+            61 => WeatherType::ProtoStar,
+            62 => WeatherType::DarkMountains,
+            63 => WeatherType::Ac15,
+            64 => WeatherType::MountGrowMore,
+            65 => WeatherType::CrackInReality,
+            66 => WeatherType::LnyNian,
+            67 => WeatherType::RaymanLock,
+            68 => WeatherType::Steampunk,
+            69 => WeatherType::RealmOfSpirits,
+            70 => WeatherType::Blackhole,
+            71 => WeatherType::Gems,
+            72 => WeatherType::HolidayHaven,
+            73 => WeatherType::FenyxLock,
+            74 => WeatherType::EnchantedLock,
+            75 => WeatherType::RoyalEnchantedLock,
+            76 => WeatherType::NeptunesAtlantis,
+            77 => WeatherType::PinuskiPetalPerfectHaven,
+            78 => WeatherType::Candyland,
+            _ => WeatherType::Default,
+        }
+    }
+}
+
+// variants are declared in wire order, so the discriminant is the weather id
+impl From<&WeatherType> for u16 {
+    fn from(value: &WeatherType) -> Self {
+        value.clone() as u16
+    }
+}
+
+impl From<WeatherType> for u16 {
+    fn from(value: WeatherType) -> Self {
+        value as u16
+    }
+}
+
+#[test]
+fn test_weather_round_trip() {
+    // catches a variant moved without its From<u16> arm
+    for id in 0..=78u16 {
+        let weather = WeatherType::from(id);
+        assert_eq!(u16::from(&weather), id, "{:?}", weather);
+        assert_eq!(u16::from(weather), id);
+    }
+    assert_eq!(WeatherType::from(79), WeatherType::Default);
+}
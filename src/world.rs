@@ -0,0 +1,1861 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::dropped::{Dropped, DroppedItem};
+use crate::error::{ParseWarning, WorldParseError};
+use crate::fidelity::LossReason;
+use crate::flags::TileFlags;
+use crate::metrics::{ParseMetrics, SectionTimer};
+use crate::options::{CborDetection, Endian, ParseOptions, UnknownItemPolicy, CBOR_QUIRK_ITEMS};
+use crate::provenance::ProvenanceTag;
+use crate::tile::{Rect, Tile, TileDiff, TileIndex, TileIndexError};
+use crate::tile_extra::TileType;
+use crate::updates::UpdateTracker;
+use crate::version;
+use crate::weather::WeatherType;
+use crate::{limits, writer};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
+use gtitem_r::structs::ItemDatabase;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Cursor, Read};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct World {
+    pub version: u16,
+    pub flags: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_count: u32,
+    pub tiles: Vec<Tile>,
+    pub dropped: Dropped,
+    pub base_weather: WeatherType,
+    pub current_weather: WeatherType,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub item_database: Arc<RwLock<ItemDatabase>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub options: ParseOptions,
+    pub warnings: Vec<ParseWarning>,
+    // everything the last parse read but can't write back, see fidelity()
+    pub losses: Vec<LossReason>,
+    // hash of the raw header, tile, dropped item and weather bytes from the
+    // last parse, equal blobs give equal values
+    pub quick_fingerprint: u64,
+    // only with options.collect_metrics, see metrics()
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub metrics: Option<ParseMetrics>,
+    pub is_error: bool,
+    // tile index to where its content came from, only while tracking, see
+    // track_provenance; never written to the game format
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub provenance: Option<HashMap<u32, ProvenanceTag>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub provenance_source: Option<ProvenanceTag>,
+    // tiles changed since the last drain_pending_updates, only while tracking
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) updates: Option<UpdateTracker>,
+    // bumped by every change the mutation APIs mark, see OccupancyGrid::is_current
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) revision: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct VendingListing {
+    pub x: u32,
+    pub y: u32,
+    pub item_id: u32,
+    pub price: i32,
+}
+
+pub(crate) const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+pub(crate) fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+// Padding that isn't all zeros is lost, the writer fills it with zeros. Missing
+// bytes are left for the reads after it to report.
+fn is_zeroed(data: &[u8], start: usize, len: usize) -> bool {
+    data.iter().skip(start).take(len).all(|byte| *byte == 0)
+}
+
+fn is_eof(err: &WorldParseError) -> bool {
+    match err {
+        WorldParseError::Io(err) => err.kind() == io::ErrorKind::UnexpectedEof,
+        WorldParseError::UnexpectedEof { .. } | WorldParseError::TruncatedExtraData { .. } => true,
+        _ => false,
+    }
+}
+
+// Names what was being read, starting at `offset`, when the data ran out.
+fn unexpected_eof(err: WorldParseError, offset: usize, context: &'static str) -> WorldParseError {
+    match err {
+        WorldParseError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => WorldParseError::UnexpectedEof {
+            offset: offset as u64,
+            context,
+        },
+        err => err,
+    }
+}
+
+// Keeps a copy of everything read through it.
+struct Recorder<'a, R> {
+    reader: R,
+    data: &'a mut Vec<u8>,
+}
+
+impl<R: Read> Read for Recorder<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.reader.read(buf)?;
+        self.data.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+// records that must follow where skip_bad_tiles picks up reading again
+const RESYNC_RECORDS: u32 = 4;
+// id, x, y, count, flags and uid
+const DROPPED_ITEM_LEN: usize = 16;
+
+// Main Door and Starship Main Door
+pub const MAIN_DOOR_IDS: [u16; 2] = [6, 6548];
+
+pub const BEDROCK_ID: u16 = 8;
+
+// version, flags, name length, width, height, tile count and the 5 bytes after it
+pub const MIN_HEADER_SIZE: usize = 25;
+
+pub const MAX_WORLD_NAME_LEN: usize = 24;
+
+// Names the game lets a player create or enter: 1 to 24 ASCII letters and
+// digits. Case doesn't matter, the game shows names uppercased.
+pub fn is_valid_world_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_WORLD_NAME_LEN && name.bytes().all(|byte| byte.is_ascii_alphanumeric())
+}
+
+// Uppercased with trailing NULs and whitespace stripped, the form the game
+// compares names in.
+pub fn normalize_world_name(name: &str) -> String {
+    name.trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+        .to_uppercase()
+}
+
+// Loads items.dat from `items_path` and parses the world at `world_path` with
+// it, for scripts that don't keep the item database around. Use World::new or
+// World::from_file to share one database between worlds.
+pub fn parse_world_file<P: AsRef<Path>, Q: AsRef<Path>>(
+    world_path: P,
+    items_path: Q,
+) -> Result<World, WorldParseError> {
+    let items_path = items_path.as_ref();
+    let items_path = items_path
+        .to_str()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "items.dat path isn't valid UTF-8"))?;
+    let item_database = gtitem_r::load_from_file(items_path)?;
+    World::from_file(world_path, Arc::new(RwLock::new(item_database)))
+}
+
+// Byte ranges each section of the world blob occupied, None for sections the parse never reached.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParseLayout {
+    pub header: Option<Range<usize>>,
+    pub tiles: Option<Range<usize>>,
+    pub post_tile_pad: Option<Range<usize>>,
+    pub dropped: Option<Range<usize>>,
+    pub weather: Option<Range<usize>>,
+    pub trailing: Option<Range<usize>>,
+}
+
+// The fields before the first tile, see World::read_header.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WorldHeader {
+    pub version: u16,
+    pub flags: u32,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub tile_count: u32,
+}
+
+impl World {
+    pub fn new(item_database: Arc<RwLock<ItemDatabase>>) -> World {
+        World {
+            version: version::CURRENT,
+            flags: 0,
+            name: "EXIT".to_string(),
+            width: 0,
+            height: 0,
+            tile_count: 0,
+            tiles: Vec::new(),
+            dropped: Dropped {
+                items_count: 0,
+                last_dropped_item_uid: 0,
+                items: Vec::new(),
+            },
+            base_weather: WeatherType::Default,
+            current_weather: WeatherType::Default,
+            is_error: false,
+            item_database,
+            options: ParseOptions::default(),
+            warnings: Vec::new(),
+            losses: Vec::new(),
+            quick_fingerprint: 0,
+            metrics: None,
+            provenance: None,
+            provenance_source: None,
+            updates: None,
+            revision: 0,
+        }
+    }
+
+    // Reads only the header from `reader`, which is left at the first tile.
+    // The header references no items, so no item database is needed, making
+    // this the cheapest way to tell what a .dat file holds. Little endian, like
+    // every capture; big endian blobs need parse with options.endianness.
+    pub fn read_header<R: Read>(reader: R) -> Result<WorldHeader, WorldParseError> {
+        World::read_header_with::<LittleEndian, R>(reader)
+    }
+
+    fn read_header_with<B: ByteOrder, R: Read>(mut reader: R) -> Result<WorldHeader, WorldParseError> {
+        let version = reader.read_u16::<B>()?;
+        let flags = reader.read_u32::<B>()?;
+        let str_len = reader.read_u16::<B>()?;
+        let mut name = vec![0; str_len as usize];
+        reader.read_exact(&mut name)?;
+        let width = reader.read_u32::<B>()?;
+        let height = reader.read_u32::<B>()?;
+        let tile_count = reader.read_u32::<B>()?;
+        reader.read_exact(&mut [0; 5])?;
+        if tile_count > 0 && width == 0 {
+            return Err(WorldParseError::InvalidDimensions {
+                width,
+                height,
+                tile_count,
+            });
+        }
+        if tile_count as u64 > width as u64 * height as u64 {
+            return Err(WorldParseError::TileCountTooLarge {
+                tile_count,
+                width,
+                height,
+            });
+        }
+        Ok(WorldHeader {
+            version,
+            flags,
+            name: String::from_utf8_lossy(&name).to_string(),
+            width,
+            height,
+            tile_count,
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(
+        path: P,
+        item_database: Arc<RwLock<ItemDatabase>>,
+    ) -> Result<World, WorldParseError> {
+        let data = std::fs::read(path)?;
+        let mut world = World::new(item_database);
+        world.parse(&data)?;
+        Ok(world)
+    }
+
+    pub fn reset(&mut self) {
+        self.version = version::CURRENT;
+        self.flags = 0;
+        self.name = "EXIT".to_string();
+        self.width = 0;
+        self.height = 0;
+        self.tile_count = 0;
+        self.tiles.clear();
+        self.dropped.items_count = 0;
+        self.dropped.last_dropped_item_uid = 0;
+        self.dropped.items.clear();
+        self.base_weather = WeatherType::Default;
+        self.current_weather = WeatherType::Default;
+        self.warnings.clear();
+        self.losses.clear();
+        self.quick_fingerprint = 0;
+        self.metrics = None;
+        if let Some(provenance) = &mut self.provenance {
+            provenance.clear();
+        }
+        self.mark_all_dirty();
+    }
+
+    // name is kept as parsed for display and serialize, use this to key or
+    // compare worlds.
+    pub fn normalized_name(&self) -> String {
+        normalize_world_name(&self.name)
+    }
+
+    pub fn name_matches(&self, other: &str) -> bool {
+        self.normalized_name() == normalize_world_name(other)
+    }
+
+    // False after a failed parse, until the next successful one.
+    pub fn is_valid(&self) -> bool {
+        !self.is_error
+    }
+
+    // Raw access, nothing keeps flags and flags_number in sync. Prefer
+    // edit_tile, which does. The tile counts as changed for
+    // drain_pending_updates either way.
+    pub fn get_tile_mut(&mut self, x: u32, y: u32) -> Option<&mut Tile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = (y * self.width + x) as usize;
+        // a short tile vector has no tile to mark here
+        if index >= self.tiles.len() {
+            return None;
+        }
+        self.mark_dirty(index);
+        self.tiles.get_mut(index)
+    }
+
+    pub fn get_tile(&self, x: u32, y: u32) -> Option<&Tile> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+
+        let index = (y * self.width + x) as usize;
+        self.tiles.get(index)
+    }
+
+    // The index parent_block_index would store for the tile at (x, y), an
+    // error when it's outside the world or past what 16 bits can hold.
+    pub fn tile_index(&self, x: u32, y: u32) -> Result<TileIndex, TileIndexError> {
+        if x >= self.width || y >= self.height {
+            return Err(TileIndexError::OutOfBounds { x, y });
+        }
+        TileIndex::from_position(x, y, self.width)
+    }
+
+    // Points the tile at (x, y) at the tile at (parent_x, parent_y), usually a
+    // lock. Fails, leaving the tile as it was, when the parent can't be stored
+    // instead of wrapping around to some other tile.
+    pub fn set_parent(&mut self, x: u32, y: u32, parent_x: u32, parent_y: u32) -> Result<(), TileIndexError> {
+        let parent = self.tile_index(parent_x, parent_y)?;
+        let tile = self
+            .get_tile_mut(x, y)
+            .ok_or(TileIndexError::OutOfBounds { x, y })?;
+        tile.parent_block_index = parent.get();
+        Ok(())
+    }
+
+    // Every tile with its (x, y), in reading order. The position comes from
+    // the tile's place in `tiles`, not from its own x and y fields.
+    pub fn tiles_iter(&self) -> impl ExactSizeIterator<Item = ((u32, u32), &Tile)> + DoubleEndedIterator + '_ {
+        let width = self.width.max(1);
+        self.tiles
+            .iter()
+            .enumerate()
+            .map(move |(index, tile)| ((index as u32 % width, index as u32 / width), tile))
+    }
+
+    // Like get_tile_mut, raw access that marks every tile as changed for
+    // drain_pending_updates.
+    pub fn tiles_iter_mut(
+        &mut self,
+    ) -> impl ExactSizeIterator<Item = ((u32, u32), &mut Tile)> + DoubleEndedIterator + '_ {
+        self.mark_all_dirty();
+        let width = self.width.max(1);
+        self.tiles
+            .iter_mut()
+            .enumerate()
+            .map(move |(index, tile)| ((index as u32 % width, index as u32 / width), tile))
+    }
+
+    // Every tile with its index into `tiles`, y * width + x.
+    pub fn tiles_with_index(&self) -> impl ExactSizeIterator<Item = (usize, &Tile)> + DoubleEndedIterator + '_ {
+        self.tiles.iter().enumerate()
+    }
+
+    // Tiles inside the rect in reading order, clipped to the world.
+    pub fn tiles_in_rect(&self, rect: Rect) -> impl Iterator<Item = &Tile> + '_ {
+        let x_end = rect.x.saturating_add(rect.width).min(self.width);
+        let y_end = rect.y.saturating_add(rect.height).min(self.height);
+        (rect.y.min(y_end)..y_end)
+            .flat_map(move |y| (rect.x.min(x_end)..x_end).map(move |x| (y * self.width + x) as usize))
+            .filter_map(move |index| self.tiles.get(index))
+    }
+
+    pub fn main_door(&self) -> Option<&Tile> {
+        self.tiles
+            .iter()
+            .find(|tile| MAIN_DOOR_IDS.contains(&tile.foreground_item_id))
+    }
+
+    // Where a player appears: the main door if the world has one, otherwise the
+    // first tile in reading order (top row first, left to right) with a
+    // foreground or background, and (0, 0) for a world with nothing in it.
+    pub fn spawn_point(&self) -> (u32, u32) {
+        self.main_door()
+            .or_else(|| {
+                self.tiles
+                    .iter()
+                    .find(|tile| tile.foreground_item_id != 0 || tile.background_item_id != 0)
+            })
+            .map_or((0, 0), |tile| (tile.x, tile.y))
+    }
+
+    // Tiles are compared by position over the area both worlds share, so a
+    // resized world only reports changes inside the overlapping region.
+    pub fn diff(&self, other: &World) -> Vec<TileDiff> {
+        let mut diff = Vec::new();
+        for y in 0..self.height.min(other.height) {
+            for x in 0..self.width.min(other.width) {
+                if let (Some(before), Some(after)) = (self.get_tile(x, y), other.get_tile(x, y)) {
+                    if !before.same_content(after) {
+                        diff.push(TileDiff {
+                            x,
+                            y,
+                            before: before.clone(),
+                            after: after.clone(),
+                        });
+                    }
+                }
+            }
+        }
+        diff
+    }
+
+    // Whether the base or current weather differs from an earlier poll of the
+    // same world.
+    pub fn weather_changed(&self, previous: &World) -> bool {
+        self.base_weather != previous.base_weather || self.current_weather != previous.current_weather
+    }
+
+    pub fn set_weather(&mut self, base_weather: WeatherType, current_weather: WeatherType) {
+        self.base_weather = base_weather;
+        self.current_weather = current_weather;
+    }
+
+    // Every item id the world references: foregrounds, backgrounds and ids
+    // embedded in extra data (see TileType::embedded_item_ids). Blank (0) is
+    // never included.
+    pub fn item_ids_used(&self) -> HashSet<u32> {
+        let mut item_ids = HashSet::new();
+        for tile in &self.tiles {
+            item_ids.insert(tile.foreground_item_id as u32);
+            item_ids.insert(tile.background_item_id as u32);
+            item_ids.extend(tile.tile_type.embedded_item_ids());
+        }
+        item_ids.remove(&0);
+        item_ids
+    }
+
+    // Every tile whose TileType::name() is `name`, in reading order.
+    pub fn find_by_tile_type(&self, name: &str) -> Vec<(u32, u32, &TileType)> {
+        self.find_by_tile_type_where(|tile_type| tile_type.name() == name)
+    }
+
+    pub fn find_by_tile_type_where(&self, predicate: impl Fn(&TileType) -> bool) -> Vec<(u32, u32, &TileType)> {
+        self.tiles
+            .iter()
+            .filter(|tile| predicate(&tile.tile_type))
+            .map(|tile| (tile.x, tile.y, &tile.tile_type))
+            .collect()
+    }
+
+    // find_by_tile_type_where without collecting, for when existence or a
+    // count is all that's needed.
+    pub fn any_tile_of_type(&self, predicate: impl Fn(&TileType) -> bool) -> bool {
+        self.tiles.iter().any(|tile| predicate(&tile.tile_type))
+    }
+
+    pub fn count_tiles_of_type(&self, predicate: impl Fn(&TileType) -> bool) -> usize {
+        self.tiles.iter().filter(|tile| predicate(&tile.tile_type)).count()
+    }
+
+    // Positions of every tile with `item_id` in the foreground, in reading
+    // order.
+    pub fn find_tiles_by_foreground(&self, item_id: u16) -> Vec<(u32, u32)> {
+        self.tiles
+            .iter()
+            .filter(|tile| tile.foreground_item_id == item_id)
+            .map(|tile| (tile.x, tile.y))
+            .collect()
+    }
+
+    pub fn find_tiles_by_background(&self, item_id: u16) -> Vec<(u32, u32)> {
+        self.tiles
+            .iter()
+            .filter(|tile| tile.background_item_id == item_id)
+            .map(|tile| (tile.x, tile.y))
+            .collect()
+    }
+
+    // find_tiles_by_foreground(item_id).len() without the Vec.
+    pub fn count_tiles_by_foreground(&self, item_id: u16) -> usize {
+        self.tiles.iter().filter(|tile| tile.foreground_item_id == item_id).count()
+    }
+
+    // Positions inside the box with corners (x1, y1) and (x2, y2), both
+    // included and in either order, clipped to the world, in reading order.
+    pub fn find_tiles_in_rect(&self, x1: u32, y1: u32, x2: u32, y2: u32) -> Vec<(u32, u32)> {
+        let (x, y) = (x1.min(x2), y1.min(y2));
+        let rect = Rect::new(x, y, (x1.max(x2) - x).saturating_add(1), (y1.max(y2) - y).saturating_add(1));
+        self.tiles_in_rect(rect).map(|tile| (tile.x, tile.y)).collect()
+    }
+
+    // Positions at most `radius` tiles from (cx, cy), measured between tile
+    // positions, in reading order. The center may lie outside the world; a
+    // negative or NaN radius finds nothing.
+    pub fn find_tiles_in_radius(&self, cx: u32, cy: u32, radius: f32) -> Vec<(u32, u32)> {
+        self.find_tiles_in_radius_where(cx, cy, radius, |_| true)
+    }
+
+    pub fn find_tiles_in_radius_where(
+        &self,
+        cx: u32,
+        cy: u32,
+        radius: f32,
+        predicate: impl Fn(&Tile) -> bool,
+    ) -> Vec<(u32, u32)> {
+        let radius = radius as f64;
+        if radius.is_nan() || radius < 0.0 {
+            return Vec::new();
+        }
+        // the bounding box of the circle, the casts saturate
+        let start = |center: u32| (center as f64 - radius).ceil().max(0.0) as u32;
+        let end = |center: u32| ((center as f64 + radius).floor() as u32).saturating_add(1);
+        let (x, y) = (start(cx), start(cy));
+        let rect = Rect::new(x, y, end(cx).saturating_sub(x), end(cy).saturating_sub(y));
+        self.tiles_in_rect(rect)
+            .filter(|tile| {
+                let dx = tile.x as f64 - cx as f64;
+                let dy = tile.y as f64 - cy as f64;
+                dx * dx + dy * dy <= radius * radius && predicate(tile)
+            })
+            .map(|tile| (tile.x, tile.y))
+            .collect()
+    }
+
+    pub fn vending_listings(&self, for_sale_only: bool) -> Vec<VendingListing> {
+        let mut listings = Vec::new();
+        for tile in &self.tiles {
+            if let TileType::VendingMachine { item_id, price } = tile.tile_type {
+                if for_sale_only && !tile.tile_type.is_for_sale() {
+                    continue;
+                }
+                listings.push(VendingListing {
+                    x: tile.x,
+                    y: tile.y,
+                    item_id,
+                    price,
+                });
+            }
+        }
+        listings
+    }
+
+    pub fn is_tile_harvestable(&self, tile: &Tile) -> bool {
+        tile.harvestable(&self.options)
+    }
+
+    pub fn is_harvestable(&self, x: u32, y: u32) -> bool {
+        if let Some(tile) = self.get_tile(x, y) {
+            return self.is_tile_harvestable(tile);
+        }
+        false
+    }
+
+    pub fn update_tile(&mut self, tile: Tile, data: &mut Cursor<&[u8]>, replace: bool) -> Result<(), WorldParseError> {
+        match self.options.endianness {
+            Endian::Little => self.read_tile::<LittleEndian>(tile, data, replace),
+            Endian::Big => self.read_tile::<BigEndian>(tile, data, replace),
+        }
+    }
+
+    fn read_tile<B: ByteOrder>(
+        &mut self,
+        mut tile: Tile,
+        mut data: &mut Cursor<&[u8]>,
+        replace: bool,
+    ) -> Result<(), WorldParseError> {
+        tile.foreground_item_id = data.read_u16::<B>()?;
+        tile.background_item_id = data.read_u16::<B>()?;
+        tile.parent_block_index = data.read_u16::<B>()?;
+        let flags = data.read_u16::<B>()?;
+        tile.flags = TileFlags::from_u16(flags);
+        tile.flags_number = flags;
+
+        let item_count = {
+            let item_database = self.item_database.read().unwrap();
+            item_database.item_count
+        };
+        let unknown_foreground = tile.foreground_item_id as u32 >= item_count;
+        let unknown_background = tile.background_item_id as u32 >= item_count;
+        if (unknown_foreground || unknown_background)
+            && self.options.unknown_items == UnknownItemPolicy::Error
+        {
+            self.is_error = true;
+            let item_id = if unknown_foreground {
+                tile.foreground_item_id
+            } else {
+                tile.background_item_id
+            };
+            return Err(WorldParseError::UnknownItem {
+                item_id,
+                x: tile.x,
+                y: tile.y,
+            });
+        }
+        if unknown_foreground {
+            self.warnings.push(ParseWarning::UnknownItem {
+                item_id: tile.foreground_item_id,
+                x: tile.x,
+                y: tile.y,
+            });
+        }
+        if unknown_background {
+            self.warnings.push(ParseWarning::UnknownItem {
+                item_id: tile.background_item_id,
+                x: tile.x,
+                y: tile.y,
+            });
+        }
+
+        if tile.flags.has_parent {
+            data.read_u16::<B>()?;
+        }
+
+        if tile.flags.has_extra_data {
+            let offset = data.position();
+            let extra_tile_type = data.read_u8()?;
+            self.get_extra_tile_data::<B>(&mut tile, &mut data, extra_tile_type, &self.item_database)
+                .map_err(|err| match err {
+                    WorldParseError::Io(err) if err.kind() == io::ErrorKind::UnexpectedEof => {
+                        WorldParseError::TruncatedExtraData {
+                            extra_type: extra_tile_type,
+                            item_id: tile.special_item_id(&self.options),
+                            offset,
+                            x: tile.x,
+                            y: tile.y,
+                        }
+                    }
+                    err => err,
+                })?;
+            if let TileType::Lock { settings, .. } = &tile.tile_type {
+                if settings.is_open_to_public != tile.flags.is_open_to_public {
+                    self.warnings.push(ParseWarning::LockPublicMismatch { x: tile.x, y: tile.y });
+                }
+            }
+        }
+
+        if self.has_cbor_data(&tile) {
+            let cbor_len = data.read_u32::<B>()?;
+            limits::check_len("cbor_data", cbor_len as u64, limits::MAX_CBOR_LEN, tile.x, tile.y)?;
+            let mut cbor_data = vec![0; cbor_len as usize];
+            data.read_exact(&mut cbor_data)?;
+            tile.cbor_data = Some(cbor_data);
+        }
+
+        if let UnknownItemPolicy::Remap(item_id) = self.options.unknown_items {
+            if unknown_foreground {
+                self.losses.push(LossReason::RemappedItem {
+                    item_id: tile.foreground_item_id,
+                    x: tile.x,
+                    y: tile.y,
+                });
+                tile.foreground_item_id = item_id;
+            }
+            if unknown_background {
+                self.losses.push(LossReason::RemappedItem {
+                    item_id: tile.background_item_id,
+                    x: tile.x,
+                    y: tile.y,
+                });
+                tile.background_item_id = item_id;
+            }
+        }
+
+        if replace {
+            let index = (tile.y * self.width + tile.x) as usize;
+            self.tiles[index] = tile;
+            self.mark_dirty(index);
+        } else {
+            self.tiles.push(tile);
+        }
+
+        Ok(())
+    }
+
+    fn has_cbor_data(&mut self, tile: &Tile) -> bool {
+        let item_id = tile.special_item_id(&self.options);
+        let detected = self.detects_cbor(item_id);
+        if detected && matches!(self.options.cbor_detection, CborDetection::Heuristic) {
+            self.warnings.push(ParseWarning::CborHeuristic {
+                item_id,
+                x: tile.x,
+                y: tile.y,
+            });
+        }
+        detected
+    }
+
+    // Takes the tile's special item id, usually its foreground.
+    pub(crate) fn detects_cbor(&self, item_id: u16) -> bool {
+        match &self.options.cbor_detection {
+            CborDetection::Heuristic => {
+                // blank never carries CBOR, and most tiles of a typical world
+                // are blank, so don't pay for the lookup on them
+                if CBOR_QUIRK_ITEMS.contains(&item_id) {
+                    return true;
+                }
+                if item_id == 0 {
+                    return false;
+                }
+                let item_database = self.item_database.read().unwrap();
+                item_database
+                    .get_item(&(item_id as u32))
+                    .is_some_and(|item| item.file_name.ends_with(".xml"))
+            }
+            CborDetection::ExplicitList(item_ids) => item_ids.contains(&(item_id as u32)),
+            CborDetection::Callback(detect) => {
+                let item_database = self.item_database.read().unwrap();
+                item_database.get_item(&(item_id as u32)).is_some_and(detect)
+            }
+        }
+    }
+
+    pub fn parse(&mut self, data: &[u8]) -> Result<(), WorldParseError> {
+        self.parse_with_layout(data).map(|_| ())
+    }
+
+    // Reads `reader` to its end into one buffer, then parses that buffer
+    // like parse. This is not incremental: the whole world is held in memory
+    // before the first tile is decoded, because parsing compares records
+    // against the bytes they came from and skip_bad_tiles and
+    // recover_truncated look ahead. What it saves the caller is the
+    // buffering and the size check. The header is read first: a world whose
+    // tiles alone can't fit limits::MAX_WORLD_LEN fails before anything else
+    // is buffered, and no stream is read past that length. A failed read
+    // leaves the world reset with is_error set, like a failed parse.
+    pub fn parse_from_reader<R: Read>(&mut self, reader: R) -> Result<(), WorldParseError> {
+        let data = match self.options.endianness {
+            Endian::Little => World::read_bounded::<LittleEndian, R>(reader),
+            Endian::Big => World::read_bounded::<BigEndian, R>(reader),
+        };
+        match data {
+            Ok(data) => self.parse(&data),
+            Err(err) => {
+                self.reset();
+                self.is_error = true;
+                Err(err)
+            }
+        }
+    }
+
+    fn read_bounded<B: ByteOrder, R: Read>(reader: R) -> Result<Vec<u8>, WorldParseError> {
+        let mut reader = reader.take(limits::MAX_WORLD_LEN as u64 + 1);
+        let mut data = Vec::new();
+        let recorder = Recorder {
+            reader: &mut reader,
+            data: &mut data,
+        };
+        let header = match World::read_header_with::<B, _>(recorder) {
+            Ok(header) => header,
+            Err(WorldParseError::Io(err)) if err.kind() != io::ErrorKind::UnexpectedEof => return Err(err.into()),
+            // parse reports a short or bad header the way it does for a slice
+            Err(_) => return Ok(data),
+        };
+        // every tile record takes at least 8 bytes
+        if data.len() as u64 + header.tile_count as u64 * 8 > limits::MAX_WORLD_LEN as u64 {
+            return Err(WorldParseError::WorldTooLarge {
+                max: limits::MAX_WORLD_LEN,
+            });
+        }
+        reader.read_to_end(&mut data)?;
+        if data.len() > limits::MAX_WORLD_LEN {
+            return Err(WorldParseError::WorldTooLarge {
+                max: limits::MAX_WORLD_LEN,
+            });
+        }
+        Ok(data)
+    }
+
+    // A failed parse always leaves the world reset with is_error set.
+    pub fn parse_with_layout(&mut self, data: &[u8]) -> Result<ParseLayout, WorldParseError> {
+        self.reset();
+        // reset leaves is_error alone, a failed parse before this one must
+        // not stick
+        self.is_error = false;
+        let mut layout = ParseLayout::default();
+        let result = match self.options.endianness {
+            Endian::Little => self.read_world::<LittleEndian>(data, &mut layout),
+            Endian::Big => self.read_world::<BigEndian>(data, &mut layout),
+        };
+        match result {
+            // is_error is set again for a recovered truncated world
+            Ok(()) => Ok(layout),
+            Err(err) => {
+                self.reset();
+                self.is_error = true;
+                Err(err)
+            }
+        }
+    }
+
+    // Leaves `data` at the first tile.
+    pub(crate) fn read_header_fields<B: ByteOrder>(
+        &mut self,
+        data: &mut Cursor<&[u8]>,
+        layout: &mut ParseLayout,
+    ) -> Result<(), WorldParseError> {
+        let data_len = data.get_ref().len();
+        if data_len < MIN_HEADER_SIZE {
+            return Err(WorldParseError::InputTooSmall {
+                len: data_len,
+                minimum: MIN_HEADER_SIZE,
+            });
+        }
+
+        self.version = data.read_u16::<B>()?;
+        if let Some(minimum) = self.options.min_version.filter(|minimum| self.version < *minimum) {
+            return Err(WorldParseError::UnsupportedVersion {
+                found: self.version,
+                minimum,
+            });
+        }
+        self.flags = data.read_u32::<B>()?;
+        let str_len = data.read_u16::<B>()?;
+        let mut name = vec![0; str_len as usize];
+        data.read_exact(&mut name)?;
+        let width = data.read_u32::<B>()?;
+        let height = data.read_u32::<B>()?;
+        let tile_count = data.read_u32::<B>()?;
+        if std::str::from_utf8(&name).is_err() {
+            self.losses.push(LossReason::InvalidName);
+        }
+        if !is_zeroed(data.get_ref(), data.position() as usize, 5) {
+            self.losses.push(LossReason::HeaderPadding);
+        }
+        data.set_position(data.position() + 5);
+        if data.position() > data_len as u64 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        layout.header = Some(0..data.position() as usize);
+        self.name = String::from_utf8_lossy(&name).to_string();
+        self.width = width;
+        self.height = height;
+        self.tile_count = tile_count;
+        if tile_count > 0 && width == 0 {
+            return Err(WorldParseError::InvalidDimensions {
+                width,
+                height,
+                tile_count,
+            });
+        }
+        if tile_count as u64 > width as u64 * height as u64 {
+            return Err(WorldParseError::TileCountTooLarge {
+                tile_count,
+                width,
+                height,
+            });
+        }
+        Ok(())
+    }
+
+    fn read_world<B: ByteOrder>(&mut self, data: &[u8], layout: &mut ParseLayout) -> Result<(), WorldParseError> {
+        let bytes = data;
+        let data_len = data.len();
+        let mut data = Cursor::new(data);
+        let mut timer = SectionTimer::start(self.options.collect_metrics);
+        self.read_header_fields::<B>(&mut data, layout)
+            .map_err(|err| unexpected_eof(err, 0, "header"))?;
+        timer.lap();
+
+        // every id would count as unknown, say why instead of failing on the first tile
+        let item_count = self.item_database.read().unwrap().item_count;
+        if item_count == 0 && self.tile_count > 0 && self.options.unknown_items == UnknownItemPolicy::Error {
+            return Err(WorldParseError::EmptyItemDatabase);
+        }
+
+        // tiles
+        let tiles_start = data.position() as usize;
+        let mut truncated = None;
+        for count in 0..self.tile_count {
+            let x = (count) % self.width;
+            let y = (count) / self.width;
+            let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, Arc::clone(&self.item_database));
+            let start = data.position() as usize;
+            let losses = self.losses.len();
+            if self.options.recover_truncated {
+                // skipping the record first finds where it ends without
+                // decoding past the end of a cut off one
+                match self.read_raw_tile::<B>(&mut data.clone(), x, y) {
+                    Err(err) if is_eof(&err) => {
+                        if self.tile_count as usize > limits::MAX_RECOVERED_TILES {
+                            return Err(err);
+                        }
+                        truncated = Some(count);
+                        break;
+                    }
+                    // left for read_tile to fail on and skip_bad_tiles to skip
+                    Err(err) if !self.options.skip_bad_tiles => return Err(err),
+                    _ => {}
+                }
+            }
+            let warnings = self.warnings.len();
+            if let Err(err) = self.read_tile::<B>(tile, &mut data, false) {
+                let err = unexpected_eof(err, start, "tile");
+                let resynced = self.options.skip_bad_tiles.then(|| self.resync::<B>(bytes, start, count));
+                let Some(next) = resynced.flatten() else {
+                    return Err(err);
+                };
+                self.warnings.truncate(warnings);
+                self.losses.truncate(losses);
+                self.skip_tile::<B>(&bytes[start..next], start, x, y, &err);
+                data.set_position(next as u64);
+                continue;
+            }
+
+            // a remapped id already explains why the record differs
+            let tile = &self.tiles[self.tiles.len() - 1];
+            let raw = &bytes[start..data.position() as usize];
+            if losses == self.losses.len() && !writer::tile_matches::<B>(tile, &self.options, raw) {
+                self.losses.push(LossReason::TileRecord {
+                    x,
+                    y,
+                    extra_type: tile.tile_type.extra_type_id(),
+                });
+            }
+        }
+        layout.tiles = Some(tiles_start..data.position() as usize);
+        timer.lap();
+
+        if truncated.is_none() {
+            match self.read_trailer::<B>(&mut data, layout, &mut timer) {
+                Err(err) if self.options.recover_truncated && is_eof(&err) => {
+                    // keep the dropped items read before the cut
+                    self.dropped.items_count = self.dropped.items.len() as u32;
+                    truncated = Some(self.tile_count);
+                }
+                result => result?,
+            }
+        }
+        if let Some(parsed) = truncated {
+            self.recover_truncated(parsed, bytes, layout);
+        }
+        if self.options.collect_metrics {
+            self.metrics = Some(ParseMetrics::collect(self, data_len, &timer));
+        }
+        Ok(())
+    }
+
+    // Where the record after the bad one at `start` begins: the end of the bad
+    // one if it can still be skipped (an id or a length was rejected, not its
+    // layout), otherwise the first later offset that RESYNC_RECORDS records
+    // skip cleanly from with known item ids. Close to the end, the remaining
+    // records and then the trailer must fit the data exactly.
+    fn resync<B: ByteOrder>(&self, bytes: &[u8], start: usize, count: u32) -> Option<usize> {
+        let mut data = Cursor::new(bytes);
+        data.set_position(start as u64);
+        if self.read_raw_tile::<B>(&mut data, count % self.width, count / self.width).is_ok() {
+            return Some(data.position() as usize);
+        }
+
+        let item_count = self.item_database.read().unwrap().item_count;
+        let checked = (count + 1)..self.tile_count.min(count + 1 + RESYNC_RECORDS);
+        let reaches_trailer = checked.end == self.tile_count;
+        let end = bytes.len().min(start.saturating_add(limits::MAX_RESYNC_BYTES));
+        (start + 1..=end).find(|&next| {
+            let mut data = Cursor::new(bytes);
+            data.set_position(next as u64);
+            for index in checked.clone() {
+                match self.read_raw_tile::<B>(&mut data, index % self.width, index / self.width) {
+                    Ok(tile)
+                        if (tile.foreground_item_id as u32) < item_count
+                            && (tile.background_item_id as u32) < item_count => {}
+                    _ => return false,
+                }
+            }
+            !reaches_trailer || self.trailer_len::<B>(bytes, data.position() as usize) == Some(bytes.len())
+        })
+    }
+
+    // Where a trailer starting at `start` ends, going by its dropped item count.
+    fn trailer_len<B: ByteOrder>(&self, bytes: &[u8], start: usize) -> Option<usize> {
+        let items_count = B::read_u32(bytes.get(start + 12..start + 16)?) as usize;
+        Some(start + 20 + items_count * DROPPED_ITEM_LEN + 6)
+    }
+
+    // Puts a blank tile in place of `record`, which failed with `err`.
+    fn skip_tile<B: ByteOrder>(&mut self, record: &[u8], offset: usize, x: u32, y: u32, err: &WorldParseError) {
+        let id = |at: usize| record.get(at..at + 2).map_or(0, B::read_u16);
+        self.warnings.push(ParseWarning::SkippedTile {
+            x,
+            y,
+            offset: offset as u64,
+            foreground_item_id: id(0),
+            background_item_id: id(2),
+            error: err.to_string(),
+        });
+        self.losses.push(LossReason::SkippedTile { x, y, len: record.len() });
+        let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, Arc::clone(&self.item_database));
+        self.tiles.push(tile);
+        self.is_error = true;
+    }
+
+    // Everything after the tiles: padding, dropped items and weather.
+    fn read_trailer<B: ByteOrder>(
+        &mut self,
+        data: &mut Cursor<&[u8]>,
+        layout: &mut ParseLayout,
+        timer: &mut SectionTimer,
+    ) -> Result<(), WorldParseError> {
+        let bytes = *data.get_ref();
+        let data_len = bytes.len();
+        let pad_start = data.position() as usize;
+        if !is_zeroed(bytes, pad_start, 12) {
+            self.losses.push(LossReason::PostTilePadding);
+        }
+        data.set_position(data.position() + 12); // it exist in the binary, i don't know what it is
+        layout.post_tile_pad = Some(pad_start..data.position() as usize);
+        if data.position() > data_len as u64 {
+            return Err(WorldParseError::UnexpectedEof {
+                offset: pad_start as u64,
+                context: "padding after the tiles",
+            });
+        }
+        let dropped_start = data.position() as usize;
+        self.read_dropped::<B>(data)
+            .map_err(|err| unexpected_eof(err, dropped_start, "dropped items"))?;
+        layout.dropped = Some(dropped_start..data.position() as usize);
+        let uids = self.dropped.duplicates();
+        if !uids.is_empty() {
+            self.warnings.push(ParseWarning::DuplicateDroppedUids { uids });
+        }
+        timer.lap();
+
+        let weather_start = data.position() as usize;
+        let eof = |err: io::Error| unexpected_eof(err.into(), weather_start, "weather");
+        let base_weather = data.read_u16::<B>().map_err(eof)?;
+        // unknown, only ever seen as zero
+        if data.read_u16::<B>().map_err(eof)? != 0 {
+            self.losses.push(LossReason::WeatherPadding);
+        }
+        let current_weather = data.read_u16::<B>().map_err(eof)?;
+        self.base_weather = WeatherType::from(base_weather);
+        self.current_weather = WeatherType::from(current_weather);
+        layout.weather = Some(weather_start..data.position() as usize);
+        layout.trailing = Some(data.position() as usize..data_len);
+        if data_len > data.position() as usize {
+            self.losses.push(LossReason::TrailingBytes {
+                len: data_len - data.position() as usize,
+            });
+        }
+
+        let mut fingerprint = FNV_OFFSET_BASIS;
+        for range in [&layout.header, &layout.tiles, &layout.dropped, &layout.weather]
+            .into_iter()
+            .flatten()
+        {
+            fingerprint = fnv1a(fingerprint, &bytes[range.clone()]);
+        }
+        self.quick_fingerprint = fingerprint;
+        timer.lap();
+        Ok(())
+    }
+
+    fn read_dropped<B: ByteOrder>(&mut self, data: &mut Cursor<&[u8]>) -> Result<(), WorldParseError> {
+        self.dropped.items_count = data.read_u32::<B>()?;
+        self.dropped.last_dropped_item_uid = data.read_u32::<B>()?;
+        for _ in 0..self.dropped.items_count {
+            let id = data.read_u16::<B>()?;
+            let x = data.read_f32::<B>()?;
+            let y = data.read_f32::<B>()?;
+            let count = data.read_u8()?;
+            let flags = data.read_u8()?;
+            let uid = data.read_u32::<B>()?;
+            self.dropped.items.push(DroppedItem {
+                id,
+                x,
+                y,
+                count,
+                flags,
+                uid,
+            });
+        }
+        Ok(())
+    }
+
+    // Keeps a world cut off after `parsed` tiles, or inside what comes after
+    // them, and pads it out to tile_count with blank tiles.
+    fn recover_truncated(&mut self, parsed: u32, bytes: &[u8], layout: &mut ParseLayout) {
+        for count in parsed..self.tile_count {
+            let (x, y) = (count % self.width, count / self.width);
+            let tile = Tile::new(0, 0, 0, TileFlags::default(), 0, x, y, Arc::clone(&self.item_database));
+            self.tiles.push(tile);
+        }
+        self.warnings.push(ParseWarning::Truncated {
+            tiles_parsed: parsed,
+            tile_count: self.tile_count,
+        });
+        self.losses.push(LossReason::Truncated { len: bytes.len() });
+        // whatever section the cut fell in is trailing bytes
+        let sections = [&layout.header, &layout.tiles, &layout.post_tile_pad, &layout.dropped, &layout.weather];
+        let end = sections.into_iter().flatten().map(|range| range.end).max().unwrap_or(0);
+        layout.trailing = Some(end.min(bytes.len())..bytes.len());
+        let mut fingerprint = FNV_OFFSET_BASIS;
+        for range in [&layout.header, &layout.tiles, &layout.dropped, &layout.weather]
+            .into_iter()
+            .flatten()
+        {
+            fingerprint = fnv1a(fingerprint, &bytes[range.clone()]);
+        }
+        self.quick_fingerprint = fingerprint;
+        self.is_error = true;
+    }
+}
+
+#[test]
+fn test_unknown_item_policy() {
+    use crate::test_world_bytes;
+    use byteorder::WriteBytesExt;
+
+    let mut tiles = vec![0; 8];
+    tiles.write_u16::<LittleEndian>(60000).unwrap();
+    tiles.extend_from_slice(&[0; 6]);
+    tiles.extend_from_slice(&[0; 8]);
+    let data = test_world_bytes(version::CURRENT, 3, 1, &tiles);
+
+    let mut world = World::new(crate::test_item_database());
+    assert!(matches!(
+        world.parse(&data),
+        Err(WorldParseError::UnknownItem {
+            item_id: 60000,
+            x: 1,
+            y: 0
+        })
+    ));
+    assert!(world.is_error);
+    assert!(world.tiles.is_empty());
+
+    world.options.unknown_items = UnknownItemPolicy::Placeholder;
+    world.is_error = false;
+    world.parse(&data).unwrap();
+    assert!(!world.is_error);
+    assert_eq!(world.tiles.len(), 3);
+    assert_eq!(world.tiles[1].foreground_item_id, 60000);
+    assert_eq!(
+        world.warnings,
+        vec![ParseWarning::UnknownItem {
+            item_id: 60000,
+            x: 1,
+            y: 0
+        }]
+    );
+    assert!(matches!(world.current_weather, WeatherType::Autumn));
+
+    world.options.unknown_items = UnknownItemPolicy::Remap(2);
+    world.parse(&data).unwrap();
+    assert_eq!(world.tiles[1].foreground_item_id, 2);
+    assert_eq!(world.warnings.len(), 1);
+}
+
+#[test]
+fn test_parse_with_layout() {
+    use crate::test_world_bytes;
+
+    let data = test_world_bytes(version::CURRENT, 2, 1, &[0; 16]);
+    let mut world = World::new(crate::test_item_database());
+    let layout = world.parse_with_layout(&data).unwrap();
+
+    assert_eq!(layout.header, Some(0..29));
+    assert_eq!(layout.tiles, Some(29..45));
+    assert_eq!(layout.post_tile_pad, Some(45..57));
+    assert_eq!(layout.dropped, Some(57..65));
+    assert_eq!(layout.weather, Some(65..71));
+    assert_eq!(layout.trailing, Some(71..71));
+
+    let mut data = test_world_bytes(version::CURRENT, 2, 1, &[0; 16]);
+    data[29] = 0xFF;
+    data[30] = 0xFF;
+    assert!(world.parse_with_layout(&data).is_err());
+    assert!(world.is_error);
+}
+
+#[test]
+fn test_parse_too_small() {
+    use crate::test_world_bytes;
+
+    let item_database = crate::test_item_database();
+    let valid = test_world_bytes(version::CURRENT, 2, 1, &[0; 16]);
+
+    for len in [0, 1] {
+        let mut world = World::new(Arc::clone(&item_database));
+        assert!(matches!(
+            world.parse(&valid[..len]),
+            Err(WorldParseError::InputTooSmall { len: l, minimum: MIN_HEADER_SIZE }) if l == len
+        ));
+        assert!(world.is_error);
+    }
+
+    // ends right after the name, a previously parsed world must be reset
+    let mut world = World::new(item_database);
+    world.parse(&valid).unwrap();
+    assert_eq!(world.name, "TEST");
+    assert!(world.parse(&valid[..12]).is_err());
+    assert!(world.is_error);
+    assert_eq!(world.name, "EXIT");
+    assert_eq!((world.width, world.height, world.tile_count), (0, 0, 0));
+    assert!(world.tiles.is_empty());
+    assert!(world.dropped.items.is_empty());
+
+    // long enough to pass the size check but cut inside the tiles
+    world.is_error = false;
+    assert!(matches!(
+        world.parse(&valid[..valid.len() - 40]),
+        Err(WorldParseError::UnexpectedEof { context: "tile", .. })
+    ));
+    assert!(world.is_error);
+    assert!(world.tiles.is_empty());
+}
+
+#[test]
+fn test_find_by_tile_type() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+
+    let locks = world.find_by_tile_type("Lock");
+    assert_eq!(locks.iter().map(|(x, y, _)| (*x, *y)).collect::<Vec<_>>(), vec![(49, 50)]);
+    assert_eq!(locks[0].2.name(), "Lock");
+    let doors = world.find_by_tile_type("Door");
+    assert!(doors.contains(&(49, 44, &world.get_tile(49, 44).unwrap().tile_type)));
+    assert_eq!(world.find_by_tile_type("Seed").len(), 6);
+    let special = world.find_by_tile_type_where(|tile_type| *tile_type != TileType::Basic);
+    assert_eq!(world.find_by_tile_type("Basic").len(), 6000 - special.len());
+    assert!(world.find_by_tile_type("lock").is_empty());
+
+    let seeds = world.find_by_tile_type_where(|tile_type| matches!(tile_type, TileType::Seed { .. }));
+    assert_eq!(seeds, world.find_by_tile_type("Seed"));
+    assert_eq!(world.count_tiles_of_type(|tile_type| matches!(tile_type, TileType::Seed { .. })), 6);
+    // every lock of one owner
+    let TileType::Lock { owner_uid, .. } = *locks[0].2 else {
+        unreachable!()
+    };
+    let owned_by = |uid: u32| {
+        move |tile_type: &TileType| matches!(tile_type, TileType::Lock { owner_uid, .. } if *owner_uid == uid)
+    };
+    assert!(world.any_tile_of_type(owned_by(owner_uid)));
+    assert_eq!(world.count_tiles_of_type(owned_by(owner_uid)), 1);
+    assert!(!world.any_tile_of_type(owned_by(owner_uid.wrapping_add(1))));
+    assert_eq!(world.count_tiles_of_type(|_| true), 6000);
+    let vending = TileType::VendingMachine { item_id: 0, price: 0 };
+    assert_eq!(vending.name(), "VendingMachine");
+    assert_eq!(TileType::SafeVault.name(), "SafeVault");
+}
+
+#[test]
+fn test_find_tiles_by_item() {
+    use crate::builder::WorldBuilder;
+
+    // 4x3: a dirt column at x = 1, rock on the bottom row, cave background
+    // behind the top row and under one rock
+    let mut world = WorldBuilder::new().size(4, 3).build(crate::test_item_database());
+    for y in 0..3 {
+        world.edit_tile(1, y).unwrap().foreground_item_id = 2;
+    }
+    for x in [0, 2, 3] {
+        world.edit_tile(x, 2).unwrap().foreground_item_id = 10;
+    }
+    for x in 0..4 {
+        world.edit_tile(x, 0).unwrap().background_item_id = 14;
+    }
+    world.edit_tile(3, 2).unwrap().background_item_id = 14;
+
+    assert_eq!(world.find_tiles_by_foreground(2), vec![(1, 0), (1, 1), (1, 2)]);
+    assert_eq!(world.find_tiles_by_foreground(10), vec![(0, 2), (2, 2), (3, 2)]);
+    assert_eq!(world.find_tiles_by_background(14), vec![(0, 0), (1, 0), (2, 0), (3, 0), (3, 2)]);
+    // blank is an item id like any other
+    assert_eq!(world.find_tiles_by_foreground(0), vec![(0, 0), (2, 0), (3, 0), (0, 1), (2, 1), (3, 1)]);
+    assert!(world.find_tiles_by_background(2).is_empty());
+    for item_id in [0, 2, 10, 14] {
+        assert_eq!(world.count_tiles_by_foreground(item_id), world.find_tiles_by_foreground(item_id).len());
+    }
+}
+
+#[test]
+fn test_find_tiles_in_area() {
+    use crate::builder::WorldBuilder;
+
+    let mut world = WorldBuilder::new().size(5, 4).build(crate::test_item_database());
+    for (x, y) in [(2, 0), (0, 1), (2, 2), (4, 3)] {
+        world.edit_tile(x, y).unwrap().foreground_item_id = 2;
+    }
+
+    // corners in either order, clipped to the world
+    assert_eq!(world.find_tiles_in_rect(1, 1, 2, 2), vec![(1, 1), (2, 1), (1, 2), (2, 2)]);
+    assert_eq!(world.find_tiles_in_rect(2, 2, 1, 1), world.find_tiles_in_rect(1, 1, 2, 2));
+    assert_eq!(world.find_tiles_in_rect(3, 2, 100, u32::MAX), vec![(3, 2), (4, 2), (3, 3), (4, 3)]);
+    assert_eq!(world.find_tiles_in_rect(4, 3, 4, 3), vec![(4, 3)]);
+    assert_eq!(world.find_tiles_in_rect(0, 0, u32::MAX, u32::MAX).len(), 20);
+    assert!(world.find_tiles_in_rect(5, 0, 9, 9).is_empty());
+
+    // a plus sign at radius 1, the corners join at sqrt(2)
+    assert_eq!(world.find_tiles_in_radius(2, 1, 1.0), vec![(2, 0), (1, 1), (2, 1), (3, 1), (2, 2)]);
+    assert_eq!(world.find_tiles_in_radius(2, 1, 1.5).len(), 9);
+    assert_eq!(world.find_tiles_in_radius(2, 1, 0.0), vec![(2, 1)]);
+    assert_eq!(world.find_tiles_in_radius(0, 0, 1.0), vec![(0, 0), (1, 0), (0, 1)]);
+    assert_eq!(world.find_tiles_in_radius(0, 0, f32::INFINITY).len(), 20);
+    assert!(world.find_tiles_in_radius(2, 1, -1.0).is_empty());
+    assert!(world.find_tiles_in_radius(2, 1, f32::NAN).is_empty());
+    // a center outside the world still reaches in
+    assert_eq!(world.find_tiles_in_radius(6, 3, 2.0), vec![(4, 3)]);
+    assert!(world.find_tiles_in_radius(u32::MAX, u32::MAX, 10.0).is_empty());
+
+    let dirt = |tile: &Tile| tile.foreground_item_id == 2;
+    assert_eq!(world.find_tiles_in_radius_where(2, 1, 1.0, dirt), vec![(2, 0), (2, 2)]);
+    assert_eq!(world.find_tiles_in_radius_where(2, 1, 2.0, dirt), vec![(2, 0), (0, 1), (2, 2)]);
+}
+
+#[test]
+fn test_vending_listings() {
+    let item_database = crate::test_item_database();
+    let mut world = World::new(Arc::clone(&item_database));
+    world.width = 4;
+    world.height = 1;
+    for (x, (item_id, price)) in [(2, 5), (2, -10), (2, 0), (0, 0)].into_iter().enumerate() {
+        let mut tile = Tile::new(2978, 0, 0, TileFlags::default(), 0, x as u32, 0, Arc::clone(&item_database));
+        tile.tile_type = TileType::VendingMachine { item_id, price };
+        world.tiles.push(tile);
+    }
+
+    assert!(world.tiles[0].tile_type.is_for_sale());
+    assert!(world.tiles[1].tile_type.is_for_sale());
+    assert!(!world.tiles[2].tile_type.is_for_sale());
+    assert!(!world.tiles[3].tile_type.is_for_sale());
+    assert!(!TileType::Basic.is_for_sale());
+
+    assert_eq!(world.vending_listings(false).len(), 4);
+    let for_sale = world.vending_listings(true);
+    assert_eq!(for_sale.len(), 2);
+    assert_eq!(for_sale[1].price, -10);
+}
+
+#[test]
+fn test_quick_fingerprint() {
+    use crate::test_world_bytes;
+
+    let item_database = crate::test_item_database();
+    let data = test_world_bytes(version::CURRENT, 2, 1, &[0; 16]);
+    let mut first = World::new(Arc::clone(&item_database));
+    first.parse(&data).unwrap();
+    let mut second = World::new(Arc::clone(&item_database));
+    second.parse(&data).unwrap();
+    assert_ne!(first.quick_fingerprint, 0);
+    assert_eq!(first.quick_fingerprint, second.quick_fingerprint);
+
+    // the unknown post-tile bytes don't count, the tiles do
+    let mut padded = data.clone();
+    padded[45] = 1;
+    second.parse(&padded).unwrap();
+    assert_eq!(first.quick_fingerprint, second.quick_fingerprint);
+
+    let mut changed = data.clone();
+    changed[37] = 2;
+    second.parse(&changed).unwrap();
+    assert_ne!(first.quick_fingerprint, second.quick_fingerprint);
+}
+
+#[test]
+fn test_cbor_detection() {
+    use crate::test_world_bytes;
+    use byteorder::WriteBytesExt;
+
+    fn tile_with_blob(tiles: &mut Vec<u8>, foreground: u16) {
+        tiles.write_u16::<LittleEndian>(foreground).unwrap();
+        tiles.extend_from_slice(&[0; 6]);
+        tiles.write_u32::<LittleEndian>(3).unwrap();
+        tiles.extend_from_slice(&[0xA1, 0x01, 0x02]);
+    }
+
+    let item_database = crate::test_item_database();
+    let mut tiles = Vec::new();
+    tile_with_blob(&mut tiles, 14666);
+    tiles.extend_from_slice(&[0; 8]);
+    let mut world = World::new(Arc::clone(&item_database));
+    // the default list reads the blob without a warning
+    world.parse(&test_world_bytes(version::CURRENT, 2, 1, &tiles)).unwrap();
+    assert!(world.warnings.is_empty());
+    assert!(world.tiles[0].cbor_data.is_some());
+    world.options.cbor_detection = CborDetection::Heuristic;
+    world.parse(&test_world_bytes(version::CURRENT, 2, 1, &tiles)).unwrap();
+    assert_eq!(
+        world.warnings,
+        vec![ParseWarning::CborHeuristic {
+            item_id: 14666,
+            x: 0,
+            y: 0
+        }]
+    );
+
+    let mut tiles = Vec::new();
+    tile_with_blob(&mut tiles, 2);
+    tiles.extend_from_slice(&[0; 8]);
+    let data = test_world_bytes(version::CURRENT, 2, 1, &tiles);
+
+    world.options.cbor_detection = CborDetection::ExplicitList(HashSet::from([2]));
+    world.parse(&data).unwrap();
+    assert!(world.warnings.is_empty());
+    assert_eq!(world.tiles[1].foreground_item_id, 0);
+
+    world.options.cbor_detection = CborDetection::Callback(|item| item.name == "Dirt");
+    world.parse(&data).unwrap();
+    assert!(world.warnings.is_empty());
+    assert!(matches!(world.current_weather, WeatherType::Autumn));
+}
+
+#[test]
+fn test_big_endian() {
+    use crate::test_world_bytes_with;
+    use byteorder::WriteBytesExt;
+
+    let mut tiles = Vec::new();
+    tiles.write_u16::<BigEndian>(2).unwrap();
+    tiles.write_u16::<BigEndian>(14).unwrap();
+    tiles.extend_from_slice(&[0; 4]);
+    tiles.write_u16::<BigEndian>(20).unwrap();
+    tiles.write_u16::<BigEndian>(0).unwrap();
+    tiles.write_u16::<BigEndian>(0).unwrap();
+    tiles.write_u16::<BigEndian>(0x01).unwrap();
+    tiles.write_u8(2).unwrap();
+    tiles.write_u16::<BigEndian>(2).unwrap();
+    tiles.extend_from_slice(b"hi");
+    tiles.write_u32::<BigEndian>(0).unwrap();
+    let data = test_world_bytes_with::<BigEndian>(version::CURRENT, 2, 1, &tiles);
+
+    let mut world = World::new(crate::test_item_database());
+    assert!(world.parse(&data).is_err());
+
+    world.options.endianness = Endian::Big;
+    world.parse(&data).unwrap();
+    assert_eq!(world.name, "TEST");
+    assert_eq!((world.width, world.height), (2, 1));
+    assert_eq!(world.tiles[0].foreground_item_id, 2);
+    assert_eq!(world.tiles[0].background_item_id, 14);
+    assert_eq!(world.tiles[1].tile_type, TileType::Sign { text: "hi".to_string() });
+    assert!(matches!(world.current_weather, WeatherType::Autumn));
+}
+
+#[test]
+fn test_spawn_point() {
+    use crate::test_world_bytes;
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    assert_eq!(world.main_door().map(|tile| (tile.x, tile.y)), Some((49, 44)));
+    assert_eq!(world.spawn_point(), (49, 44));
+
+    world.get_tile_mut(49, 44).unwrap().foreground_item_id = 0;
+    assert!(world.main_door().is_none());
+    assert_eq!(world.spawn_point(), (93, 24));
+
+    // dirt on the left, a lone background on the right
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&test_world_bytes(version::CURRENT, 3, 2, &[0; 48])).unwrap();
+    assert_eq!(world.spawn_point(), (0, 0));
+    world.get_tile_mut(0, 1).unwrap().foreground_item_id = 2;
+    world.get_tile_mut(2, 0).unwrap().background_item_id = 14;
+    assert_eq!(world.spawn_point(), (2, 0));
+    world.get_tile_mut(1, 1).unwrap().foreground_item_id = 6548;
+    assert_eq!(world.spawn_point(), (1, 1));
+}
+
+#[test]
+fn test_item_ids_used() {
+    use crate::builder::WorldBuilder;
+
+    let mut world = WorldBuilder::new().size(3, 1).build(crate::test_item_database());
+    world.tiles[0].foreground_item_id = 2;
+    world.tiles[0].background_item_id = 14;
+    world.tiles[1].foreground_item_id = 3008;
+    world.tiles[1].tile_type = TileType::Shelf {
+        top_left_item_id: 242,
+        top_right_item_id: 0,
+        bottom_left_item_id: 202,
+        bottom_right_item_id: 242,
+    };
+    let expected: HashSet<u32> = [2, 14, 3008, 242, 202].into_iter().collect();
+    assert_eq!(world.item_ids_used(), expected);
+
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let item_ids = world.item_ids_used();
+    assert!(world.tiles.iter().all(|tile| {
+        tile.foreground_item_id == 0 || item_ids.contains(&(tile.foreground_item_id as u32))
+    }));
+    assert!(!item_ids.contains(&0));
+}
+
+#[test]
+fn test_parse_empty_item_database() {
+    use crate::test_world_bytes;
+
+    let data = std::fs::read("world.dat").unwrap();
+    let mut world = World::new(Arc::new(RwLock::new(ItemDatabase::new())));
+    assert!(matches!(world.parse(&data), Err(WorldParseError::EmptyItemDatabase)));
+    assert!(world.is_error);
+
+    // nothing to look up in a world without tiles
+    world.parse(&test_world_bytes(version::CURRENT, 0, 0, &[])).unwrap();
+
+    // opting into unknown ids skips the check
+    world.options.unknown_items = UnknownItemPolicy::Placeholder;
+    world.parse(&test_world_bytes(version::CURRENT, 1, 1, &[2, 0, 0, 0, 0, 0, 0, 0])).unwrap();
+    assert_eq!(world.tiles[0].foreground_item_id, 2);
+}
+
+#[test]
+fn test_weather_changed() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let previous = world.clone();
+    assert!(!world.weather_changed(&previous));
+
+    world.set_weather(WeatherType::Sunny, WeatherType::Night);
+    assert!(world.weather_changed(&previous));
+    world.set_weather(previous.base_weather.clone(), previous.current_weather.clone());
+    assert!(!world.weather_changed(&previous));
+}
+
+#[test]
+fn test_world_names() {
+    let mut world = World::new(crate::test_item_database());
+    world.name = "BuyDirt \0\0".to_string();
+    assert_eq!(world.normalized_name(), "BUYDIRT");
+    assert!(world.name_matches("buydirt"));
+    assert!(world.name_matches("BUYDIRT\n"));
+    assert!(!world.name_matches("BUYDIRT2"));
+    assert_eq!(world.name, "BuyDirt \0\0");
+
+    assert!(is_valid_world_name("START"));
+    assert!(is_valid_world_name("buy4dirt"));
+    assert!(is_valid_world_name(&"A".repeat(MAX_WORLD_NAME_LEN)));
+    assert!(!is_valid_world_name(&"A".repeat(MAX_WORLD_NAME_LEN + 1)));
+    assert!(!is_valid_world_name(""));
+    assert!(!is_valid_world_name("BUY DIRT"));
+    assert!(!is_valid_world_name("BUY_DIRT"));
+    assert!(!is_valid_world_name("WÖRLD"));
+}
+
+#[test]
+fn test_parse_after_error() {
+    let data = std::fs::read("world.dat").unwrap();
+    let mut world = World::new(crate::test_item_database());
+    assert!(world.parse(&data[..data.len() / 2]).is_err());
+    assert!(!world.is_valid());
+
+    world.parse(&data).unwrap();
+    assert!(world.is_valid());
+    assert!(!world.is_error);
+    assert_eq!(world.name, "PIWD");
+}
+
+#[test]
+fn test_parse_from_reader() {
+    use crate::test_world_bytes;
+    use std::io::{self, BufReader, Read};
+
+    let data = std::fs::read("world.dat").unwrap();
+    let mut expected = World::new(crate::test_item_database());
+    expected.parse(&data).unwrap();
+    let mut world = World::new(crate::test_item_database());
+    world.parse_from_reader(BufReader::new(std::fs::File::open("world.dat").unwrap())).unwrap();
+    assert!(!world.is_error);
+    assert_eq!(world.serialize().unwrap(), expected.serialize().unwrap());
+    assert_eq!((&world.warnings, &world.losses), (&expected.warnings, &expected.losses));
+    assert_eq!(world.quick_fingerprint, expected.quick_fingerprint);
+
+    // a cut off stream fails the same way a cut off slice does
+    let err = world.parse_from_reader(&data[..data.len() / 2]).unwrap_err();
+    assert_eq!(err.to_string(), expected.parse(&data[..data.len() / 2]).unwrap_err().to_string());
+    assert!(world.is_error);
+
+    // and a read error resets the world
+    struct Broken<'a>(&'a [u8]);
+    impl Read for Broken<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::new(io::ErrorKind::ConnectionReset, "socket closed"));
+            }
+            let len = self.0.len().min(buf.len());
+            buf[..len].copy_from_slice(&self.0[..len]);
+            self.0 = &self.0[len..];
+            Ok(len)
+        }
+    }
+    world.parse(&data).unwrap();
+    let err = world.parse_from_reader(Broken(&data[..100])).unwrap_err();
+    assert!(matches!(err, WorldParseError::Io(err) if err.kind() == io::ErrorKind::ConnectionReset));
+    assert!(world.is_error);
+    assert!(world.tiles.is_empty());
+
+    // a header claiming more tiles than the limit holds stops before the
+    // tiles, and an endless stream stops at the limit
+    let header = &test_world_bytes(version::CURRENT, u16::MAX as u32, u16::MAX as u32, &[])[..29];
+    let err = world.parse_from_reader(header.chain(io::repeat(0))).unwrap_err();
+    assert!(matches!(err, WorldParseError::WorldTooLarge { max: limits::MAX_WORLD_LEN }));
+    let header = &test_world_bytes(version::CURRENT, 100, 60, &[])[..29];
+    let err = world.parse_from_reader(header.chain(io::repeat(0))).unwrap_err();
+    assert!(matches!(err, WorldParseError::WorldTooLarge { .. }));
+    assert!(world.is_error);
+
+    // a cut off header fails as it does for a slice
+    let err = world.parse_from_reader(&data[..10]).unwrap_err();
+    assert_eq!(err.to_string(), expected.parse(&data[..10]).unwrap_err().to_string());
+}
+
+#[test]
+fn test_read_header() {
+    let data = std::fs::read("world.dat").unwrap();
+    let mut reader = &data[..];
+    let header = World::read_header(&mut reader).unwrap();
+    let mut world = World::new(crate::test_item_database());
+    let layout = world.parse_with_layout(&data).unwrap();
+    assert_eq!(
+        header,
+        WorldHeader {
+            version: world.version,
+            flags: world.flags,
+            name: "PIWD".to_string(),
+            width: 100,
+            height: 60,
+            tile_count: 6000,
+        }
+    );
+    // the reader is left at the first tile
+    assert_eq!(data.len() - reader.len(), layout.tiles.unwrap().start);
+
+    assert!(World::read_header(&data[..MIN_HEADER_SIZE - 1]).is_err());
+    assert!(World::read_header(std::io::empty()).is_err());
+}
+
+#[test]
+fn test_parse_world_file() {
+    let world = parse_world_file("world.dat", "items.dat").unwrap();
+    assert_eq!((world.name.as_str(), world.width, world.height), ("PIWD", 100, 60));
+    assert!(world.is_valid());
+    assert!(matches!(
+        parse_world_file("world.dat", "missing-items.dat"),
+        Err(WorldParseError::Io(_))
+    ));
+    assert!(parse_world_file("missing-world.dat", "items.dat").is_err());
+}
+
+#[test]
+fn test_recover_truncated() {
+    let data = std::fs::read("world.dat").unwrap();
+    let item_database = crate::test_item_database();
+    let mut full = World::new(Arc::clone(&item_database));
+    let layout = full.parse_with_layout(&data).unwrap();
+    let records = |tiles: &[Tile]| -> Vec<Vec<u8>> {
+        tiles.iter().map(|tile| tile.to_packet_bytes(&full.options).unwrap()).collect()
+    };
+    let tiles = layout.tiles.clone().unwrap();
+    let dropped = layout.dropped.clone().unwrap();
+
+    let mut world = World::new(Arc::clone(&item_database));
+    world.options.recover_truncated = true;
+    // a whole world parses as before
+    world.parse(&data).unwrap();
+    assert!(!world.is_error);
+    assert_eq!(world.serialize().unwrap(), full.serialize().unwrap());
+
+    let cuts = [
+        tiles.start,
+        tiles.start + 7,
+        tiles.start + (tiles.end - tiles.start) / 3,
+        tiles.end - 1,
+        tiles.end + 5,
+        dropped.start + 30,
+        data.len() - 10,
+    ];
+    for cut in cuts {
+        let layout = world.parse_with_layout(&data[..cut]).unwrap();
+        assert!(world.is_error, "cut at {}", cut);
+        assert_eq!(world.tiles.len(), 6000);
+        let Some(ParseWarning::Truncated { tiles_parsed, tile_count }) = world.warnings.last().cloned() else {
+            panic!("no warning for the cut at {}", cut);
+        };
+        assert_eq!(tile_count, 6000);
+        let parsed = tiles_parsed as usize;
+        assert_eq!(records(&world.tiles[..parsed]), records(&full.tiles[..parsed]));
+        assert!(world.tiles[parsed..]
+            .iter()
+            .all(|tile| tile.foreground_item_id == 0 && tile.tile_type == TileType::Basic));
+        assert_eq!(layout.trailing.unwrap().end, cut);
+        assert_eq!(world.dropped.items_count as usize, world.dropped.items.len());
+        assert!(world.dropped.items.iter().all(|item| full.dropped.items.contains(item)));
+        assert!(world.losses.contains(&LossReason::Truncated { len: cut }));
+        // and what was kept makes a whole world again
+        let mut again = World::new(Arc::clone(&item_database));
+        again.parse(&world.serialize().unwrap()).unwrap();
+        assert_eq!(records(&again.tiles), records(&world.tiles));
+    }
+    world.parse(&data[..tiles.start + 7]).unwrap();
+    assert_eq!(world.tiles.iter().filter(|tile| tile.foreground_item_id != 0).count(), 0);
+    world.parse(&data[..dropped.start + 30]).unwrap();
+    assert_eq!(world.dropped.items.len(), 1);
+    assert_eq!(world.base_weather, WeatherType::Default);
+    assert_eq!(records(&world.tiles), records(&full.tiles));
+
+    // without the option, or cut inside the header, it still fails
+    assert!(world.parse(&data[..10]).is_err());
+    let mut strict = World::new(item_database);
+    assert!(strict.parse(&data[..data.len() - 10]).is_err());
+    assert!(strict.is_error);
+}
+
+#[test]
+fn test_skip_bad_tiles() {
+    use crate::test_world_bytes;
+
+    let data = std::fs::read("world.dat").unwrap();
+    let item_database = crate::test_item_database();
+    let mut full = World::new(Arc::clone(&item_database));
+    let layout = full.parse_with_layout(&data).unwrap();
+    let records = |tiles: &[Tile]| -> Vec<Vec<u8>> {
+        tiles.iter().map(|tile| tile.to_packet_bytes(&full.options).unwrap()).collect()
+    };
+
+    // the main door's extra type byte overwritten, so its length is unknown
+    let door = 44 * 100 + 49;
+    let before: usize = full.tiles[..door].iter().map(|tile| tile.serialized_size()).sum();
+    let start = layout.tiles.unwrap().start + before;
+    let mut corrupt = data.clone();
+    corrupt[start + 8] = 200;
+    let mut world = World::new(Arc::clone(&item_database));
+    assert!(matches!(
+        world.parse(&corrupt),
+        Err(WorldParseError::UnknownExtraType { extra_type: 200, x: 49, y: 44, .. })
+    ));
+
+    world.options.skip_bad_tiles = true;
+    world.parse(&data).unwrap();
+    assert!(!world.is_error);
+    world.parse(&corrupt).unwrap();
+    assert!(world.is_error);
+    let skipped: Vec<_> = world
+        .warnings
+        .iter()
+        .filter(|warning| matches!(warning, ParseWarning::SkippedTile { .. }))
+        .collect();
+    assert_eq!(skipped.len(), 1);
+    let ParseWarning::SkippedTile {
+        x,
+        y,
+        offset,
+        foreground_item_id,
+        error,
+        ..
+    } = skipped[0].clone()
+    else {
+        unreachable!()
+    };
+    assert_eq!((x, y, offset, foreground_item_id), (49, 44, start as u64, 6));
+    assert!(error.contains("unknown extra data type 200"));
+    let len = full.tiles[door].serialized_size();
+    assert!(world.losses.contains(&LossReason::SkippedTile { x: 49, y: 44, len }));
+    // every other tile and the trailer read as before
+    assert_eq!(world.tiles.len(), 6000);
+    assert_eq!(world.tiles[door].foreground_item_id, 0);
+    assert_eq!(world.tiles[door].tile_type, TileType::Basic);
+    assert_eq!(records(&world.tiles[..door]), records(&full.tiles[..door]));
+    assert_eq!(records(&world.tiles[door + 1..]), records(&full.tiles[door + 1..]));
+    assert_eq!(world.dropped.items, full.dropped.items);
+
+    // a rejected id leaves the record whole, reading goes on right after it
+    let mut tiles = Vec::new();
+    for value in [2u16, 0, 0, 0, 60000, 14, 0, 0, 2, 0, 0, 0] {
+        tiles.extend(value.to_le_bytes());
+    }
+    let small = test_world_bytes(version::CURRENT, 3, 1, &tiles);
+    world.parse(&small).unwrap();
+    assert_eq!(world.warnings.len(), 1);
+    assert!(matches!(
+        world.warnings[0],
+        ParseWarning::SkippedTile {
+            x: 1,
+            offset: 37,
+            foreground_item_id: 60000,
+            background_item_id: 14,
+            ..
+        }
+    ));
+    let ids: Vec<_> = world.tiles.iter().map(|tile| tile.foreground_item_id).collect();
+    assert_eq!(ids, vec![2, 0, 2]);
+
+    // the last tile is bad, the trailer shows where it ends
+    let mut tiles = Vec::new();
+    for value in [2u16, 0, 0, 0x01] {
+        tiles.extend(value.to_le_bytes());
+    }
+    tiles.extend([200, 1, 2, 3]);
+    world.parse(&test_world_bytes(version::CURRENT, 1, 1, &tiles)).unwrap();
+    assert_eq!(world.tiles.len(), 1);
+    assert!(matches!(world.warnings[0], ParseWarning::SkippedTile { x: 0, .. }));
+
+    // nowhere to pick up again, the records after it hold no known ids
+    tiles.extend([0xff; 32]);
+    let data = test_world_bytes(version::CURRENT, 5, 1, &tiles);
+    assert!(matches!(world.parse(&data), Err(WorldParseError::UnknownExtraType { x: 0, .. })));
+    assert!(world.is_error);
+}
+
+#[test]
+fn test_tiles_iter() {
+    use crate::builder::WorldBuilder;
+
+    let mut world = WorldBuilder::new().size(3, 2).build(crate::test_item_database());
+    let positions: Vec<(u32, u32)> = world.tiles_iter().map(|(position, _)| position).collect();
+    assert_eq!(positions, [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+    assert!(world
+        .tiles_iter()
+        .all(|((x, y), tile)| (tile.x, tile.y) == (x, y)));
+    assert_eq!(world.tiles_iter().len(), 6);
+    assert_eq!(world.tiles_iter().next_back().unwrap().0, (2, 1));
+    {
+        let mut iter = world.tiles_iter();
+        iter.next();
+        iter.next_back();
+        assert_eq!(iter.len(), 4);
+    }
+
+    for ((x, y), tile) in world.tiles_iter_mut() {
+        tile.foreground_item_id = (y * 10 + x) as u16;
+    }
+    assert_eq!(world.get_tile(2, 1).unwrap().foreground_item_id, 12);
+    for (index, tile) in world.tiles_with_index() {
+        let position = world.tile_index(tile.x, tile.y).unwrap().get() as usize;
+        assert_eq!(index, position);
+    }
+    assert_eq!(world.tiles_with_index().next_back().unwrap().0, 5);
+
+    // every tile counts as changed, whether it was written or not
+    world.track_updates();
+    world.tiles_iter_mut().next_back();
+    assert_eq!(world.drain_pending_updates().unwrap().len(), 6);
+
+    // a tile vector shorter than width * height ends the walk early
+    world.tiles.truncate(4);
+    assert_eq!(world.tiles_iter().len(), 4);
+    assert_eq!(world.tiles_iter().next_back().unwrap().0, (0, 1));
+    assert_eq!(world.tiles_iter_mut().count(), 4);
+}
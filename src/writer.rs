@@ -0,0 +1,1333 @@
+use crate::error::SerializeError;
+use crate::options::{Endian, ParseOptions, SerializeOptions};
+use crate::tile::{Rect, Tile};
+use crate::tile_extra::TileType;
+use crate::world::{self, World};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, WriteBytesExt};
+use std::io::{self, Write};
+
+// Bytes the parser skips without keeping (guild lock data, the header and
+// post-tile padding, ...) are written back as zeros, and trailing bytes after
+// the weather are dropped.
+
+// Counts what would be written, so sizes come from the serializer itself
+// without allocating.
+#[derive(Default)]
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Checks what would be written against bytes that were read, without
+// allocating. Extra or missing bytes count as a mismatch.
+struct ByteMatcher<'a> {
+    expected: &'a [u8],
+    matches: bool,
+}
+
+impl Write for ByteMatcher<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.expected.strip_prefix(buf) {
+            Some(rest) => self.expected = rest,
+            None => {
+                self.matches = false;
+                self.expected = &[];
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// FNV-1a over everything written.
+struct FnvHasher(u64);
+
+impl Write for FnvHasher {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 = world::fnv1a(self.0, buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// `len` as the integer type the format stores the length of `field` in.
+fn encode_len<T: TryFrom<usize>>(field: &'static str, len: usize, tile: Option<&Tile>) -> Result<T, SerializeError> {
+    T::try_from(len).map_err(|_| SerializeError::TooLong {
+        field,
+        len,
+        tile: tile.map(|tile| (tile.x, tile.y)),
+    })
+}
+
+fn write_string<B: ByteOrder, W: Write>(
+    out: &mut W,
+    field: &'static str,
+    value: &str,
+    tile: Option<&Tile>,
+) -> Result<(), SerializeError> {
+    out.write_u16::<B>(encode_len(field, value.len(), tile)?).unwrap();
+    out.write_all(value.as_bytes()).unwrap();
+    Ok(())
+}
+
+impl TileType {
+    pub fn extra_type_id(&self) -> Option<u8> {
+        let id = match self {
+            TileType::Basic => return None,
+            TileType::Door { .. } => 1,
+            TileType::Sign { .. } => 2,
+            TileType::Lock { .. } => 3,
+            TileType::Seed { .. } => 4,
+            TileType::Mailbox { .. } => 6,
+            TileType::Bulletin { .. } => 7,
+            TileType::Dice { .. } => 8,
+            TileType::ChemicalSource { .. } => 9,
+            TileType::AchievementBlock { .. } => 10,
+            TileType::HearthMonitor { .. } => 11,
+            TileType::DonationBox { .. } => 12,
+            TileType::Mannequin { .. } => 14,
+            TileType::BunnyEgg { .. } => 15,
+            TileType::GamePack { .. } => 16,
+            TileType::GameGenerator {} => 17,
+            TileType::XenoniteCrystal { .. } => 18,
+            TileType::PhoneBooth { .. } => 19,
+            TileType::Crystal { .. } => 20,
+            TileType::CrimeInProgress { .. } => 21,
+            TileType::Spotlight => 22,
+            TileType::DisplayBlock { .. } => 23,
+            TileType::VendingMachine { .. } => 24,
+            TileType::FishTankPort { .. } => 25,
+            TileType::SolarCollector { .. } => 26,
+            TileType::Forge { .. } => 27,
+            TileType::GivingTree { .. } => 28,
+            TileType::SteamOrgan { .. } => 30,
+            TileType::SilkWorm { .. } => 31,
+            TileType::SewingMachine { .. } => 32,
+            TileType::CountryFlag { .. } => 33,
+            TileType::LobsterTrap => 34,
+            TileType::PaintingEasel { .. } => 35,
+            TileType::PetBattleCage { .. } => 36,
+            TileType::PetTrainer { .. } => 37,
+            TileType::SteamEngine { .. } => 38,
+            TileType::LockBot { .. } => 39,
+            TileType::WeatherMachine { .. } => 40,
+            TileType::SpiritStorageUnit { .. } => 41,
+            TileType::DataBedrock { .. } => 42,
+            TileType::Shelf { .. } => 43,
+            TileType::VipEntrance { .. } => 44,
+            TileType::ChallangeTimer => 45,
+            TileType::FishWallMount { .. } => 47,
+            TileType::Portrait { .. } => 48,
+            TileType::GuildWeatherMachine { .. } => 49,
+            TileType::FossilPrepStation { .. } => 50,
+            TileType::DnaExtractor => 51,
+            TileType::Howler => 52,
+            TileType::ChemsynthTank { .. } => 53,
+            TileType::StorageBlock { .. } => 54,
+            TileType::CookingOven { .. } => 55,
+            TileType::AudioRack { .. } => 56,
+            TileType::GeigerCharger { .. } => 57,
+            TileType::AdventureBegins => 58,
+            TileType::TombRobber => 59,
+            TileType::BalloonOMatic { .. } => 60,
+            TileType::TrainingPort { .. } => 61,
+            TileType::ItemSucker { .. } => 62,
+            TileType::CyBot { .. } => 63,
+            TileType::GuildItem => 65,
+            TileType::Growscan { .. } => 66,
+            TileType::ContainmentFieldPowerNode { .. } => 67,
+            TileType::SpiritBoard { .. } => 68,
+            TileType::StormyCloud { .. } => 72,
+            TileType::TemporaryPlatform { .. } => 73,
+            TileType::SafeVault => 74,
+            TileType::AngelicCountingCloud { .. } => 75,
+            TileType::InfinityWeatherMachine { .. } => 77,
+            TileType::PineappleGuzzler => 79,
+            TileType::KrakenGalaticBlock { .. } => 80,
+            TileType::FriendsEntrance { .. } => 81,
+        };
+        Some(id)
+    }
+}
+
+// Writes the extra data of a tile (type byte included) the way the parser
+// reads it. Writes nothing for TileType::Basic.
+pub(crate) fn write_extra_tile_data<B: ByteOrder, W: Write>(
+    tile: &Tile,
+    options: &ParseOptions,
+    out: &mut W,
+) -> Result<(), SerializeError> {
+    let extra_type = match tile.tile_type.extra_type_id() {
+        Some(extra_type) => extra_type,
+        None => return Ok(()),
+    };
+    out.write_u8(extra_type).unwrap();
+
+    match &tile.tile_type {
+        TileType::Basic
+        | TileType::GameGenerator {}
+        | TileType::Spotlight
+        | TileType::LobsterTrap
+        | TileType::ChallangeTimer
+        | TileType::DnaExtractor
+        | TileType::Howler
+        | TileType::AdventureBegins
+        | TileType::TombRobber
+        | TileType::SafeVault
+        | TileType::PineappleGuzzler => {}
+        TileType::Door { text, unknown_1 } => {
+            write_string::<B, W>(out, "text", text, Some(tile))?;
+            out.write_u8(*unknown_1).unwrap();
+        }
+        TileType::Sign { text } => {
+            write_string::<B, W>(out, "text", text, Some(tile))?;
+            // every captured sign ends in 0xFFFFFFFF
+            out.write_u32::<B>(u32::MAX).unwrap();
+        }
+        TileType::Lock {
+            settings,
+            owner_uid,
+            access_uids,
+            minimum_level,
+            unknown_1,
+            ..
+        } => {
+            out.write_u8(settings.to_u8()).unwrap();
+            out.write_u32::<B>(*owner_uid).unwrap();
+            out.write_u32::<B>(encode_len("access_uids", access_uids.len(), Some(tile))?).unwrap();
+            for uid in access_uids {
+                out.write_u32::<B>(*uid).unwrap();
+            }
+            out.write_u8(*minimum_level).unwrap();
+            out.write_all(unknown_1).unwrap();
+            if tile.special_item_id(options) == 5814 {
+                out.write_all(&[0; 16]).unwrap();
+            }
+        }
+        TileType::Seed {
+            time_passed,
+            item_on_tree,
+            ..
+        } => {
+            out.write_u32::<B>(*time_passed).unwrap();
+            out.write_u8(*item_on_tree).unwrap();
+        }
+        TileType::Mailbox {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+            unknown_4,
+        }
+        | TileType::Bulletin {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+            unknown_4,
+        }
+        | TileType::DonationBox {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+            unknown_4,
+        } => {
+            write_string::<B, W>(out, "unknown_1", unknown_1, Some(tile))?;
+            write_string::<B, W>(out, "unknown_2", unknown_2, Some(tile))?;
+            write_string::<B, W>(out, "unknown_3", unknown_3, Some(tile))?;
+            out.write_u8(*unknown_4).unwrap();
+        }
+        TileType::Dice { symbol } => out.write_u8(*symbol).unwrap(),
+        TileType::ChemicalSource { time_passed, .. } => out.write_u32::<B>(*time_passed).unwrap(),
+        TileType::AchievementBlock { unknown_1, tile_type } => {
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u8(*tile_type).unwrap();
+        }
+        TileType::HearthMonitor { unknown_1, player_name } => {
+            out.write_u32::<B>(*unknown_1).unwrap();
+            write_string::<B, W>(out, "player_name", player_name, Some(tile))?;
+        }
+        TileType::Mannequin {
+            text,
+            unknown_1,
+            clothing_1,
+            clothing_2,
+            clothing_3,
+            clothing_4,
+            clothing_5,
+            clothing_6,
+            clothing_7,
+            clothing_8,
+            clothing_9,
+            clothing_10,
+        } => {
+            write_string::<B, W>(out, "text", text, Some(tile))?;
+            out.write_u8(*unknown_1).unwrap();
+            out.write_u32::<B>(*clothing_1).unwrap();
+            for clothing in [
+                clothing_2,
+                clothing_3,
+                clothing_4,
+                clothing_5,
+                clothing_6,
+                clothing_7,
+                clothing_8,
+                clothing_9,
+                clothing_10,
+            ] {
+                out.write_u16::<B>(*clothing).unwrap();
+            }
+        }
+        TileType::BunnyEgg { egg_placed } => out.write_u32::<B>(*egg_placed).unwrap(),
+        TileType::GamePack { team } => out.write_u8(*team).unwrap(),
+        TileType::XenoniteCrystal { unknown_1, unknown_2 } => {
+            out.write_u8(*unknown_1).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+        }
+        TileType::PhoneBooth {
+            clothing_1,
+            clothing_2,
+            clothing_3,
+            clothing_4,
+            clothing_5,
+            clothing_6,
+            clothing_7,
+            clothing_8,
+            clothing_9,
+        } => {
+            for clothing in [
+                clothing_1, clothing_2, clothing_3, clothing_4, clothing_5, clothing_6, clothing_7, clothing_8,
+                clothing_9,
+            ] {
+                out.write_u16::<B>(*clothing).unwrap();
+            }
+        }
+        TileType::Crystal { unknown_1 } => write_string::<B, W>(out, "unknown_1", unknown_1, Some(tile))?,
+        TileType::CrimeInProgress {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+        } => {
+            write_string::<B, W>(out, "unknown_1", unknown_1, Some(tile))?;
+            out.write_u32::<B>(*unknown_2).unwrap();
+            out.write_u8(*unknown_3).unwrap();
+        }
+        TileType::DisplayBlock { item_id } => out.write_u32::<B>(*item_id).unwrap(),
+        TileType::VendingMachine { item_id, price } => {
+            out.write_u32::<B>(*item_id).unwrap();
+            out.write_i32::<B>(*price).unwrap();
+        }
+        TileType::FishTankPort { flags, fishes } => {
+            out.write_u8(*flags).unwrap();
+            out.write_u32::<B>(encode_len("fishes", fishes.len().saturating_mul(2), Some(tile))?).unwrap();
+            for fish in fishes {
+                out.write_u32::<B>(fish.fish_item_id).unwrap();
+                out.write_u32::<B>(fish.lbs).unwrap();
+            }
+        }
+        TileType::SolarCollector { unknown_1 } => out.write_all(unknown_1).unwrap(),
+        TileType::Forge { temperature } | TileType::SteamEngine { temperature } => {
+            out.write_u32::<B>(*temperature).unwrap()
+        }
+        TileType::GivingTree { unknown_1, unknown_2 } => {
+            out.write_u16::<B>(*unknown_1).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+        }
+        TileType::SteamOrgan { instrument_type, note } => {
+            out.write_u8(*instrument_type).unwrap();
+            out.write_u32::<B>(*note).unwrap();
+        }
+        TileType::SilkWorm {
+            type_,
+            name,
+            age,
+            unknown_1,
+            unknown_2,
+            can_be_fed,
+            color,
+            sick_duration,
+        } => {
+            out.write_u8(*type_).unwrap();
+            write_string::<B, W>(out, "name", name, Some(tile))?;
+            out.write_u32::<B>(*age).unwrap();
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+            out.write_u8(*can_be_fed).unwrap();
+            let color = (color.a as u32) << 24 | (color.r as u32) << 16 | (color.g as u32) << 8 | color.b as u32;
+            out.write_u32::<B>(color).unwrap();
+            out.write_u32::<B>(*sick_duration).unwrap();
+        }
+        TileType::SewingMachine { bolt_id_list } => {
+            out.write_u16::<B>(encode_len("bolt_id_list", bolt_id_list.len(), Some(tile))?).unwrap();
+            for bolt_id in bolt_id_list {
+                out.write_u32::<B>(*bolt_id).unwrap();
+            }
+        }
+        TileType::CountryFlag { country } => write_string::<B, W>(out, "country", country, Some(tile))?,
+        TileType::PaintingEasel { item_id, label } => {
+            out.write_u32::<B>(*item_id).unwrap();
+            write_string::<B, W>(out, "label", label, Some(tile))?;
+        }
+        TileType::PetBattleCage {
+            label,
+            base_pet,
+            combined_pet_1,
+            combined_pet_2,
+        } => {
+            write_string::<B, W>(out, "label", label, Some(tile))?;
+            out.write_u32::<B>(*base_pet).unwrap();
+            out.write_u32::<B>(*combined_pet_1).unwrap();
+            out.write_u32::<B>(*combined_pet_2).unwrap();
+        }
+        TileType::PetTrainer {
+            name,
+            unknown_1,
+            pets_id,
+            ..
+        } => {
+            write_string::<B, W>(out, "name", name, Some(tile))?;
+            out.write_u32::<B>(encode_len("pets_id", pets_id.len(), Some(tile))?).unwrap();
+            out.write_u32::<B>(*unknown_1).unwrap();
+            for pet_id in pets_id {
+                out.write_u32::<B>(*pet_id).unwrap();
+            }
+        }
+        TileType::LockBot { time_passed } => out.write_u32::<B>(*time_passed).unwrap(),
+        TileType::WeatherMachine { settings } => out.write_u32::<B>(*settings).unwrap(),
+        TileType::SpiritStorageUnit { ghost_jar_count } => out.write_u32::<B>(*ghost_jar_count).unwrap(),
+        TileType::DataBedrock {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+            unknown_4,
+            unknown_5,
+            unknown_6,
+        } => {
+            out.write_u8(*unknown_1).unwrap();
+            for field in [unknown_2, unknown_3, unknown_4, unknown_5, unknown_6] {
+                out.write_u32::<B>(*field).unwrap();
+            }
+        }
+        TileType::Shelf {
+            top_left_item_id,
+            top_right_item_id,
+            bottom_left_item_id,
+            bottom_right_item_id,
+        } => {
+            out.write_u32::<B>(*top_left_item_id).unwrap();
+            out.write_u32::<B>(*top_right_item_id).unwrap();
+            out.write_u32::<B>(*bottom_left_item_id).unwrap();
+            out.write_u32::<B>(*bottom_right_item_id).unwrap();
+        }
+        TileType::VipEntrance {
+            unknown_1,
+            owner_uid,
+            access_uids,
+        } => {
+            out.write_u8(*unknown_1).unwrap();
+            out.write_u32::<B>(*owner_uid).unwrap();
+            out.write_u32::<B>(encode_len("access_uids", access_uids.len(), Some(tile))?).unwrap();
+            for uid in access_uids {
+                out.write_u32::<B>(*uid).unwrap();
+            }
+        }
+        TileType::FishWallMount { label, item_id, lb } => {
+            write_string::<B, W>(out, "label", label, Some(tile))?;
+            out.write_u32::<B>(*item_id).unwrap();
+            out.write_u8(*lb).unwrap();
+        }
+        TileType::Portrait {
+            label,
+            unknown_1,
+            unknown_2,
+            unknown_3,
+            unknown_4,
+            face,
+            hat,
+            hair,
+            unknown_5,
+            unknown_6,
+        } => {
+            write_string::<B, W>(out, "label", label, Some(tile))?;
+            for value in [unknown_1, unknown_2, unknown_3, unknown_4, face, hat, hair] {
+                out.write_u32::<B>(*value).unwrap();
+            }
+            out.write_u16::<B>(*unknown_5).unwrap();
+            out.write_u16::<B>(*unknown_6).unwrap();
+        }
+        TileType::GuildWeatherMachine {
+            unknown_1,
+            gravity,
+            flags,
+        } => {
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u32::<B>(*gravity).unwrap();
+            out.write_u8(*flags).unwrap();
+        }
+        TileType::FossilPrepStation { unknown_1 }
+        | TileType::GeigerCharger { unknown_1 }
+        | TileType::TemporaryPlatform { unknown_1 } => out.write_u32::<B>(*unknown_1).unwrap(),
+        TileType::ChemsynthTank {
+            current_chem,
+            target_chem,
+        } => {
+            out.write_u32::<B>(*current_chem).unwrap();
+            out.write_u32::<B>(*target_chem).unwrap();
+        }
+        TileType::StorageBlock { items } => {
+            out.write_u16::<B>(encode_len("items", items.len().saturating_mul(13), Some(tile))?).unwrap();
+            for item in items {
+                out.write_all(&[0; 3]).unwrap();
+                out.write_u32::<B>(item.id).unwrap();
+                out.write_all(&[0; 2]).unwrap();
+                out.write_u32::<B>(item.amount).unwrap();
+            }
+        }
+        TileType::CookingOven {
+            temperature_level,
+            ingredients,
+            unknown_1,
+            unknown_2,
+            unknown_3,
+        } => {
+            out.write_u32::<B>(*temperature_level).unwrap();
+            out.write_u32::<B>(encode_len("ingredients", ingredients.len(), Some(tile))?).unwrap();
+            for ingredient in ingredients {
+                out.write_u32::<B>(ingredient.item_id).unwrap();
+                out.write_u32::<B>(ingredient.time_added).unwrap();
+            }
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+            out.write_u32::<B>(*unknown_3).unwrap();
+        }
+        TileType::AudioRack { note, volume } => {
+            write_string::<B, W>(out, "note", note, Some(tile))?;
+            out.write_u32::<B>(*volume).unwrap();
+        }
+        TileType::BalloonOMatic {
+            total_rarity,
+            team_type,
+        } => {
+            out.write_u32::<B>(*total_rarity).unwrap();
+            out.write_u8(*team_type).unwrap();
+        }
+        TileType::TrainingPort {
+            fish_lb,
+            fish_status,
+            fish_id,
+            fish_total_exp,
+            fish_level,
+            unknown_2,
+        } => {
+            out.write_u32::<B>(*fish_lb).unwrap();
+            out.write_u16::<B>(*fish_status).unwrap();
+            out.write_u32::<B>(*fish_id).unwrap();
+            out.write_u32::<B>(*fish_total_exp).unwrap();
+            out.write_u32::<B>(*fish_level).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+        }
+        TileType::ItemSucker {
+            item_id_to_suck,
+            item_amount,
+            flags,
+            limit,
+        } => {
+            out.write_u32::<B>(*item_id_to_suck).unwrap();
+            out.write_u32::<B>(*item_amount).unwrap();
+            out.write_u16::<B>(*flags).unwrap();
+            out.write_u32::<B>(*limit).unwrap();
+        }
+        TileType::CyBot {
+            sync_timer,
+            activated,
+            command_datas,
+        } => {
+            out.write_u32::<B>(*sync_timer).unwrap();
+            out.write_u32::<B>(*activated).unwrap();
+            out.write_u32::<B>(encode_len("command_datas", command_datas.len(), Some(tile))?).unwrap();
+            for command in command_datas {
+                out.write_u32::<B>(command.command_id).unwrap();
+                out.write_u32::<B>(command.is_command_used).unwrap();
+                out.write_all(&command.args).unwrap();
+            }
+        }
+        TileType::GuildItem => out.write_all(&[0; 17]).unwrap(),
+        TileType::Growscan { unknown_1 } => out.write_u8(*unknown_1).unwrap(),
+        TileType::ContainmentFieldPowerNode {
+            ghost_jar_count,
+            unknown_1,
+        } => {
+            out.write_u32::<B>(*ghost_jar_count).unwrap();
+            out.write_u32::<B>(encode_len("unknown_1", unknown_1.len(), Some(tile))?).unwrap();
+            for value in unknown_1 {
+                out.write_u32::<B>(*value).unwrap();
+            }
+        }
+        TileType::SpiritBoard {
+            unknown_1,
+            unknown_2,
+            unknown_3,
+        } => {
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u32::<B>(*unknown_2).unwrap();
+            out.write_u32::<B>(*unknown_3).unwrap();
+        }
+        TileType::StormyCloud {
+            sting_duration,
+            is_solid,
+            non_solid_duration,
+        } => {
+            out.write_u32::<B>(*sting_duration).unwrap();
+            out.write_u32::<B>(*is_solid).unwrap();
+            out.write_u32::<B>(*non_solid_duration).unwrap();
+        }
+        TileType::AngelicCountingCloud {
+            is_raffling,
+            unknown_1,
+            ascii_code,
+        } => {
+            out.write_u32::<B>(*is_raffling).unwrap();
+            out.write_u16::<B>(*unknown_1).unwrap();
+            out.write_u8(*ascii_code).unwrap();
+        }
+        TileType::InfinityWeatherMachine {
+            interval_minutes,
+            weather_machine_list,
+        } => {
+            out.write_u32::<B>(*interval_minutes).unwrap();
+            out.write_u32::<B>(encode_len("weather_machine_list", weather_machine_list.len(), Some(tile))?).unwrap();
+            for weather_machine in weather_machine_list {
+                out.write_u32::<B>(*weather_machine).unwrap();
+            }
+        }
+        TileType::KrakenGalaticBlock {
+            pattern_index,
+            unknown_1,
+            r,
+            g,
+            b,
+        } => {
+            out.write_u8(*pattern_index).unwrap();
+            out.write_u32::<B>(*unknown_1).unwrap();
+            out.write_u8(*r).unwrap();
+            out.write_u8(*g).unwrap();
+            out.write_u8(*b).unwrap();
+        }
+        TileType::FriendsEntrance {
+            owner_user_id,
+            unknown_1,
+            unknown_2,
+        } => {
+            out.write_u32::<B>(*owner_user_id).unwrap();
+            out.write_u16::<B>(*unknown_1).unwrap();
+            out.write_u16::<B>(*unknown_2).unwrap();
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn write_tile<B: ByteOrder, W: Write>(
+    tile: &Tile,
+    options: &ParseOptions,
+    out: &mut W,
+) -> Result<(), SerializeError> {
+    out.write_u16::<B>(tile.foreground_item_id).unwrap();
+    out.write_u16::<B>(tile.background_item_id).unwrap();
+    out.write_u16::<B>(tile.parent_block_index).unwrap();
+    out.write_u16::<B>(tile.flags_number).unwrap();
+    if tile.flags.has_parent {
+        out.write_u16::<B>(0).unwrap();
+    }
+    if tile.flags.has_extra_data {
+        write_extra_tile_data::<B, W>(tile, options, out)?;
+    }
+    if let Some(cbor_data) = &tile.cbor_data {
+        out.write_u32::<B>(encode_len("cbor_data", cbor_data.len(), Some(tile))?).unwrap();
+        out.write_all(cbor_data).unwrap();
+    }
+    Ok(())
+}
+
+fn write_world<B: ByteOrder, W: Write>(world: &World, out: &mut W) -> Result<(), SerializeError> {
+    out.write_u16::<B>(world.version).unwrap();
+    out.write_u32::<B>(world.flags).unwrap();
+    write_string::<B, W>(out, "name", &world.name, None)?;
+    out.write_u32::<B>(world.width).unwrap();
+    out.write_u32::<B>(world.height).unwrap();
+    out.write_u32::<B>(encode_len("tiles", world.tiles.len(), None)?).unwrap();
+    out.write_all(&[0; 5]).unwrap();
+    for tile in &world.tiles {
+        write_tile::<B, W>(tile, &world.options, out)?;
+    }
+    out.write_all(&[0; 12]).unwrap();
+
+    out.write_u32::<B>(encode_len("dropped items", world.dropped.items.len(), None)?).unwrap();
+    out.write_u32::<B>(world.dropped.last_dropped_item_uid).unwrap();
+    for item in &world.dropped.items {
+        out.write_u16::<B>(item.id).unwrap();
+        out.write_f32::<B>(item.x).unwrap();
+        out.write_f32::<B>(item.y).unwrap();
+        out.write_u8(item.count).unwrap();
+        out.write_u8(item.flags).unwrap();
+        out.write_u32::<B>(item.uid).unwrap();
+    }
+
+    out.write_u16::<B>(u16::from(&world.base_weather)).unwrap();
+    out.write_u16::<B>(0).unwrap();
+    out.write_u16::<B>(u16::from(&world.current_weather)).unwrap();
+    Ok(())
+}
+
+pub(crate) fn tile_matches<B: ByteOrder>(tile: &Tile, options: &ParseOptions, raw: &[u8]) -> bool {
+    let mut matcher = ByteMatcher {
+        expected: raw,
+        matches: true,
+    };
+    write_tile::<B, _>(tile, options, &mut matcher).is_ok() && matcher.matches && matcher.expected.is_empty()
+}
+
+// The little endian record of a tile in a world parsed with `options`.
+pub(crate) fn tile_record(tile: &Tile, options: &ParseOptions) -> Result<Vec<u8>, SerializeError> {
+    let mut out = Vec::new();
+    write_tile::<LittleEndian, _>(tile, options, &mut out)?;
+    Ok(out)
+}
+
+// Tile level encoding knows no world, so extra data is keyed the default way,
+// by the foreground item. Sizes and hashes of a tile serialize refuses cover
+// the record up to the field that is too long.
+impl Tile {
+    // Tile record as it appears in a world file, little endian.
+    pub fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
+        let mut out = Vec::with_capacity(self.serialized_size());
+        write_tile::<LittleEndian, _>(self, &ParseOptions::default(), &mut out)?;
+        Ok(out)
+    }
+
+    pub fn serialized_size(&self) -> usize {
+        let mut counter = ByteCounter::default();
+        let _ = write_tile::<LittleEndian, _>(self, &ParseOptions::default(), &mut counter);
+        counter.0
+    }
+
+    // Hash of the encoded record (ids, flags, extra data and CBOR, not the
+    // position), equal for tiles with the same content. Cheaper to keep around
+    // than the tile itself when comparing snapshots.
+    pub fn content_hash(&self) -> u64 {
+        let mut hasher = FnvHasher(world::FNV_OFFSET_BASIS);
+        let _ = write_tile::<LittleEndian, _>(self, &ParseOptions::default(), &mut hasher);
+        hasher.0
+    }
+}
+
+impl World {
+    // Encodes the world in options.endianness, in the layout parse reads. The
+    // version is written as recorded, every version uses the same layout.
+    // Fails on a string or list too long for the length field it is stored
+    // with, which only an edited world can have.
+    pub fn serialize(&self) -> Result<Vec<u8>, SerializeError> {
+        let mut out = Vec::with_capacity(self.serialized_size());
+        match self.options.endianness {
+            Endian::Little => write_world::<LittleEndian, _>(self, &mut out)?,
+            Endian::Big => write_world::<BigEndian, _>(self, &mut out)?,
+        }
+        Ok(out)
+    }
+
+    // With require_exact set, a world whose last parse lost data (see
+    // World::fidelity) is refused instead of written.
+    pub fn serialize_with(&self, options: &SerializeOptions) -> Result<Vec<u8>, SerializeError> {
+        if options.require_exact && !self.losses.is_empty() {
+            return Err(SerializeError::Lossy(self.losses.clone()));
+        }
+        self.serialize()
+    }
+
+    // Hash of the tiles inside the rect (clipped to the world): their content
+    // hashes and where in the rect they sit. Equal regions hash equal across
+    // polls, so a chunked renderer can redraw only chunks whose hash moved.
+    // Dropped items aren't bound to tiles and are ignored.
+    pub fn region_hash(&self, x0: u32, y0: u32, width: u32, height: u32) -> u64 {
+        let rect = Rect::new(x0, y0, width, height);
+        let mut hasher = FnvHasher(world::FNV_OFFSET_BASIS);
+        for tile in self.tiles_in_rect(rect) {
+            hasher.write_u32::<LittleEndian>(tile.x - x0).unwrap();
+            hasher.write_u32::<LittleEndian>(tile.y - y0).unwrap();
+            hasher.write_u64::<LittleEndian>(tile.content_hash()).unwrap();
+        }
+        hasher.0
+    }
+
+    // Size of what serialize writes. For a world serialize refuses, the size
+    // up to the field that is too long.
+    pub fn serialized_size(&self) -> usize {
+        // sizes don't depend on byte order
+        let mut counter = ByteCounter::default();
+        let _ = write_world::<LittleEndian, _>(self, &mut counter);
+        counter.0
+    }
+}
+
+// One of every extra tile type, with non-empty strings and lists.
+#[cfg(test)]
+pub(crate) fn test_sample_tile_types() -> Vec<TileType> {
+    use crate::tile_extra::{CookingOvenIngredientInfo, CyBotCommandData, FishInfo, SilkWormColor, StorageBlockItemInfo};
+    use std::time::Duration;
+
+    let text = || "sample text".to_string();
+    vec![
+        TileType::Door {
+            text: text(),
+            unknown_1: 1,
+        },
+        TileType::Sign { text: text() },
+        TileType::Lock {
+            settings: crate::flags::LockSettings::from_u8(1),
+            owner_uid: 2,
+            access_count: 2,
+            access_uids: vec![3, 4],
+            minimum_level: 5,
+            unknown_1: [6, 7, 8, 9, 10, 11, 12],
+        },
+        TileType::Seed {
+            time_passed: 100,
+            item_on_tree: 3,
+            ready_to_harvest: false,
+            elapsed: Duration::from_secs(100),
+        },
+        TileType::Mailbox {
+            unknown_1: text(),
+            unknown_2: "b".to_string(),
+            unknown_3: String::new(),
+            unknown_4: 4,
+        },
+        TileType::Bulletin {
+            unknown_1: text(),
+            unknown_2: "b".to_string(),
+            unknown_3: String::new(),
+            unknown_4: 4,
+        },
+        TileType::Dice { symbol: 6 },
+        TileType::ChemicalSource {
+            time_passed: 7,
+            ready_to_harvest: false,
+            elapsed: Duration::from_secs(7),
+        },
+        TileType::AchievementBlock {
+            unknown_1: 8,
+            tile_type: 9,
+        },
+        TileType::HearthMonitor {
+            unknown_1: 10,
+            player_name: text(),
+        },
+        TileType::DonationBox {
+            unknown_1: text(),
+            unknown_2: "b".to_string(),
+            unknown_3: String::new(),
+            unknown_4: 4,
+        },
+        TileType::Mannequin {
+            text: text(),
+            unknown_1: 1,
+            clothing_1: 2,
+            clothing_2: 3,
+            clothing_3: 4,
+            clothing_4: 5,
+            clothing_5: 6,
+            clothing_6: 7,
+            clothing_7: 8,
+            clothing_8: 9,
+            clothing_9: 10,
+            clothing_10: 11,
+        },
+        TileType::BunnyEgg { egg_placed: 12 },
+        TileType::GamePack { team: 2 },
+        TileType::GameGenerator {},
+        TileType::XenoniteCrystal {
+            unknown_1: 1,
+            unknown_2: 2,
+        },
+        TileType::PhoneBooth {
+            clothing_1: 1,
+            clothing_2: 2,
+            clothing_3: 3,
+            clothing_4: 4,
+            clothing_5: 5,
+            clothing_6: 6,
+            clothing_7: 7,
+            clothing_8: 8,
+            clothing_9: 9,
+        },
+        TileType::Crystal { unknown_1: text() },
+        TileType::CrimeInProgress {
+            unknown_1: text(),
+            unknown_2: 2,
+            unknown_3: 3,
+        },
+        TileType::Spotlight,
+        TileType::DisplayBlock { item_id: 242 },
+        TileType::VendingMachine {
+            item_id: 242,
+            price: -5,
+        },
+        TileType::FishTankPort {
+            flags: 1,
+            fishes: vec![
+                FishInfo {
+                    fish_item_id: 3,
+                    lbs: 4,
+                },
+                FishInfo {
+                    fish_item_id: 5,
+                    lbs: 6,
+                },
+            ],
+        },
+        TileType::SolarCollector {
+            unknown_1: [1, 2, 3, 4, 5],
+        },
+        TileType::Forge { temperature: 100 },
+        TileType::GivingTree {
+            unknown_1: 1,
+            unknown_2: 2,
+        },
+        TileType::SteamOrgan {
+            instrument_type: 1,
+            note: 2,
+        },
+        TileType::SilkWorm {
+            type_: 1,
+            name: text(),
+            age: 2,
+            unknown_1: 3,
+            unknown_2: 4,
+            can_be_fed: 1,
+            color: SilkWormColor {
+                a: 255,
+                r: 1,
+                g: 2,
+                b: 3,
+            },
+            sick_duration: 5,
+        },
+        TileType::SewingMachine {
+            bolt_id_list: vec![1, 2, 3],
+        },
+        TileType::CountryFlag {
+            country: "id".to_string(),
+        },
+        TileType::LobsterTrap,
+        TileType::PaintingEasel {
+            item_id: 1,
+            label: text(),
+        },
+        TileType::PetBattleCage {
+            label: text(),
+            base_pet: 1,
+            combined_pet_1: 2,
+            combined_pet_2: 3,
+        },
+        TileType::PetTrainer {
+            name: text(),
+            pet_total_count: 2,
+            unknown_1: 1,
+            pets_id: vec![5, 6],
+        },
+        TileType::SteamEngine { temperature: 1 },
+        TileType::LockBot { time_passed: 1 },
+        TileType::WeatherMachine { settings: 1 },
+        TileType::SpiritStorageUnit { ghost_jar_count: 3 },
+        TileType::DataBedrock {
+            unknown_1: 0,
+            unknown_2: 1,
+            unknown_3: 1,
+            unknown_4: 1,
+            unknown_5: 0,
+            unknown_6: u32::MAX,
+        },
+        TileType::Shelf {
+            top_left_item_id: 1,
+            top_right_item_id: 2,
+            bottom_left_item_id: 3,
+            bottom_right_item_id: 4,
+        },
+        TileType::VipEntrance {
+            unknown_1: 1,
+            owner_uid: 2,
+            access_uids: vec![3],
+        },
+        TileType::ChallangeTimer,
+        TileType::FishWallMount {
+            label: text(),
+            item_id: 1,
+            lb: 2,
+        },
+        TileType::Portrait {
+            label: text(),
+            unknown_1: 1,
+            unknown_2: 2,
+            unknown_3: 3,
+            unknown_4: 4,
+            face: 5,
+            hat: 6,
+            hair: 7,
+            unknown_5: 8,
+            unknown_6: 9,
+        },
+        TileType::GuildWeatherMachine {
+            unknown_1: 1,
+            gravity: 2,
+            flags: 3,
+        },
+        TileType::FossilPrepStation { unknown_1: 1 },
+        TileType::DnaExtractor,
+        TileType::Howler,
+        TileType::ChemsynthTank {
+            current_chem: 1,
+            target_chem: 2,
+        },
+        TileType::StorageBlock {
+            items: vec![
+                StorageBlockItemInfo { id: 1, amount: 2 },
+                StorageBlockItemInfo { id: 3, amount: 4 },
+            ],
+        },
+        TileType::CookingOven {
+            temperature_level: 1,
+            ingredients: vec![CookingOvenIngredientInfo {
+                item_id: 2,
+                time_added: 3,
+            }],
+            unknown_1: 4,
+            unknown_2: 5,
+            unknown_3: 6,
+        },
+        TileType::AudioRack {
+            note: text(),
+            volume: 1,
+        },
+        TileType::GeigerCharger { unknown_1: 1 },
+        TileType::AdventureBegins,
+        TileType::TombRobber,
+        TileType::BalloonOMatic {
+            total_rarity: 1,
+            team_type: 2,
+        },
+        TileType::TrainingPort {
+            fish_lb: 1,
+            fish_status: 2,
+            fish_id: 3,
+            fish_total_exp: 4,
+            fish_level: 5,
+            unknown_2: 6,
+        },
+        TileType::ItemSucker {
+            item_id_to_suck: 1,
+            item_amount: 2,
+            flags: 3,
+            limit: 4,
+        },
+        TileType::CyBot {
+            sync_timer: 1,
+            activated: 1,
+            command_datas: vec![CyBotCommandData {
+                command_id: 2,
+                is_command_used: 1,
+                args: [1, 2, 3, 4, 5, 6, 7],
+            }],
+        },
+        TileType::GuildItem,
+        TileType::Growscan { unknown_1: 1 },
+        TileType::ContainmentFieldPowerNode {
+            ghost_jar_count: 1,
+            unknown_1: vec![2, 3],
+        },
+        TileType::SpiritBoard {
+            unknown_1: 1,
+            unknown_2: 2,
+            unknown_3: 3,
+        },
+        TileType::StormyCloud {
+            sting_duration: 1,
+            is_solid: 2,
+            non_solid_duration: 3,
+        },
+        TileType::TemporaryPlatform { unknown_1: 1 },
+        TileType::SafeVault,
+        TileType::AngelicCountingCloud {
+            is_raffling: 1,
+            unknown_1: 2,
+            ascii_code: 3,
+        },
+        TileType::InfinityWeatherMachine {
+            interval_minutes: 1,
+            weather_machine_list: vec![2, 3],
+        },
+        TileType::PineappleGuzzler,
+        TileType::KrakenGalaticBlock {
+            pattern_index: 1,
+            unknown_1: 2,
+            r: 3,
+            g: 4,
+            b: 5,
+        },
+        TileType::FriendsEntrance {
+            owner_user_id: 1,
+            unknown_1: 2,
+            unknown_2: 3,
+        },
+    ]
+}
+
+// A world with one tile per sample tile type plus a few blank and CBOR tiles.
+// Item 14 is the one carrying CBOR data, so parse it back with
+// CborDetection::ExplicitList([14]).
+#[cfg(test)]
+pub(crate) fn test_sample_world() -> World {
+    use crate::builder::WorldBuilder;
+    use crate::dropped::DroppedItem;
+    use crate::flags::TileFlags;
+    use crate::weather::WeatherType;
+
+    let tile_types = test_sample_tile_types();
+    let width = 10;
+    let height = (tile_types.len() as u32 + 3) / width + 1;
+    let mut world = WorldBuilder::new()
+        .version(crate::version::CURRENT)
+        .name("SAMPLE")
+        .size(width, height)
+        .weather(WeatherType::Sunny, WeatherType::Autumn)
+        .build(crate::test_item_database());
+    for (tile, tile_type) in world.tiles.iter_mut().zip(tile_types) {
+        // guild lock, its record carries 16 more bytes
+        tile.foreground_item_id = if matches!(tile_type, TileType::Lock { .. }) {
+            5814
+        } else {
+            2
+        };
+        tile.flags_number = 0x01;
+        tile.flags = TileFlags::from_u16(0x01);
+        tile.tile_type = tile_type;
+    }
+    for tile in world.tiles.iter_mut().rev().take(3) {
+        tile.foreground_item_id = 14;
+        tile.cbor_data = Some(vec![0xA1, 0x61, 0x61, tile.x as u8]);
+    }
+    world.dropped.last_dropped_item_uid = 9;
+    world.dropped.items.push(DroppedItem {
+        id: 242,
+        x: 32.0,
+        y: 64.5,
+        count: 3,
+        flags: 0,
+        uid: 9,
+    });
+    world.dropped.items_count = 1;
+    world
+}
+
+#[test]
+fn test_serialized_size() {
+    let mut world = World::new(crate::test_item_database());
+    let data = std::fs::read("world.dat").unwrap();
+    world.parse(&data).unwrap();
+    // the trailing bytes after the weather aren't kept
+    assert_eq!(world.serialized_size(), data.len() - crate::fidelity::trailing_len(&world));
+    assert_eq!(world.serialize().unwrap().len(), world.serialized_size());
+
+    let mut world = test_sample_world();
+    for endianness in [Endian::Little, Endian::Big] {
+        world.options.endianness = endianness;
+        assert_eq!(world.serialize().unwrap().len(), world.serialized_size());
+    }
+    for tile in &world.tiles {
+        assert_eq!(tile.serialize().unwrap().len(), tile.serialized_size());
+        let mut out = Vec::new();
+        write_tile::<BigEndian, _>(tile, &world.options, &mut out).unwrap();
+        assert_eq!(out.len(), tile.serialized_size());
+    }
+    let sizes: usize = world.tiles.iter().map(|tile| tile.serialized_size()).sum();
+    assert!(world.serialized_size() > sizes);
+}
+
+#[test]
+fn test_serialize_parse_back() {
+    use crate::options::CborDetection;
+
+    for endianness in [Endian::Little, Endian::Big] {
+        let mut world = test_sample_world();
+        world.options.endianness = endianness;
+        world.options.cbor_detection = CborDetection::ExplicitList([14].into_iter().collect());
+        let data = world.serialize().unwrap();
+
+        let mut parsed = World::new(crate::test_item_database());
+        parsed.options = world.options.clone();
+        parsed.parse(&data).unwrap();
+        assert_eq!(parsed.serialize().unwrap(), data);
+        assert_eq!(
+            (parsed.name.as_str(), parsed.width, parsed.height),
+            ("SAMPLE", world.width, world.height)
+        );
+        assert_eq!(parsed.dropped, world.dropped);
+        assert_eq!(parsed.current_weather, world.current_weather);
+        for (before, after) in world.tiles.iter().zip(&parsed.tiles) {
+            assert_eq!(before.cbor_data, after.cbor_data);
+            match (&before.tile_type, &after.tile_type) {
+                (TileType::Seed { time_passed: a, .. }, TileType::Seed { time_passed: b, .. }) => assert_eq!(a, b),
+                (
+                    TileType::ChemicalSource { time_passed: a, .. },
+                    TileType::ChemicalSource { time_passed: b, .. },
+                ) => {
+                    assert_eq!(a, b)
+                }
+                (before, after) => assert_eq!(before, after),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_round_trip_edge_cases() {
+    use crate::builder::WorldBuilder;
+    use crate::flags::{LockSettings, TileFlags};
+
+    // with the default options: a tile pointing at its parent, a lock with
+    // access uids and CBOR on 15376, which only CBOR_QUIRK_ITEMS finds
+    let item_database = crate::test_item_database();
+    let mut world = WorldBuilder::new()
+        .name("EDGES")
+        .size(3, 1)
+        .build(std::sync::Arc::clone(&item_database));
+    world.tiles[0].foreground_item_id = 242;
+    world.tiles[0].flags_number = 0x01;
+    world.tiles[0].flags = TileFlags::from_u16(0x01);
+    world.tiles[0].tile_type = TileType::Lock {
+        settings: LockSettings::from_u8(0),
+        owner_uid: 77,
+        access_count: 3,
+        access_uids: vec![11, 12, 13],
+        minimum_level: 0,
+        unknown_1: [0; 7],
+    };
+    world.tiles[1].foreground_item_id = 2;
+    world.tiles[1].flags_number = 0x02;
+    world.tiles[1].flags = TileFlags::from_u16(0x02);
+    world.set_parent(1, 0, 2, 0).unwrap();
+    world.tiles[2].foreground_item_id = 15376;
+    world.tiles[2].cbor_data = Some(vec![0xA1, 0x61, 0x6B, 0x02]);
+    let data = world.serialize().unwrap();
+
+    let mut parsed = World::new(std::sync::Arc::clone(&item_database));
+    // the test items.dat is older than 15376
+    parsed.options.unknown_items = crate::options::UnknownItemPolicy::Placeholder;
+    parsed.parse(&data).unwrap();
+    assert_eq!(parsed.serialize().unwrap(), data);
+    assert_eq!(parsed.tiles[0].tile_type, world.tiles[0].tile_type);
+    assert!(parsed.tiles[1].flags.has_parent);
+    assert_eq!(parsed.tiles[1].parent_block_index, 2);
+    assert_eq!(parsed.tiles[2].cbor_data, world.tiles[2].cbor_data);
+
+    // and a real world comes back the same after a second trip
+    let mut first = World::new(std::sync::Arc::clone(&item_database));
+    first.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let mut second = World::new(item_database);
+    second.parse(&first.serialize().unwrap()).unwrap();
+    assert_eq!(second.serialize().unwrap(), first.serialize().unwrap());
+    assert_eq!(second.dropped, first.dropped);
+    for (before, after) in first.tiles.iter().zip(&second.tiles) {
+        assert_eq!(before.tile_type, after.tile_type);
+        assert_eq!(before.cbor_data, after.cbor_data);
+    }
+}
+
+#[test]
+fn test_serialize_too_long() {
+    use crate::builder::WorldBuilder;
+    use crate::flags::TileFlags;
+    use crate::tile_extra::StorageBlockItemInfo;
+
+    let mut world = WorldBuilder::new()
+        .name("LONG")
+        .size(2, 1)
+        .build(crate::test_item_database());
+    for tile in &mut world.tiles {
+        tile.flags_number = 0x01;
+        tile.flags = TileFlags::from_u16(0x01);
+    }
+    world.tiles[0].tile_type = TileType::Sign {
+        text: "a".repeat(u16::MAX as usize),
+    };
+    // 13 bytes per item, 5041 items is the most a u16 length holds
+    let item = StorageBlockItemInfo { id: 2, amount: 1 };
+    world.tiles[1].tile_type = TileType::StorageBlock {
+        items: vec![item.clone(); 5041],
+    };
+    world.serialize().unwrap();
+
+    let TileType::StorageBlock { items } = &mut world.tiles[1].tile_type else {
+        unreachable!()
+    };
+    items.push(item);
+    assert_eq!(
+        world.serialize(),
+        Err(SerializeError::TooLong {
+            field: "items",
+            len: 5042 * 13,
+            tile: Some((1, 0)),
+        })
+    );
+    assert!(world.tiles[1].serialize().is_err());
+
+    world.tiles[0].tile_type = TileType::Sign {
+        text: "a".repeat(u16::MAX as usize + 1),
+    };
+    assert_eq!(
+        world.serialize(),
+        Err(SerializeError::TooLong {
+            field: "text",
+            len: u16::MAX as usize + 1,
+            tile: Some((0, 0)),
+        })
+    );
+    assert!(world.serialize_with(&SerializeOptions::default()).is_err());
+}
+
+#[test]
+fn test_region_hash() {
+    let mut world = World::new(crate::test_item_database());
+    world.parse(&std::fs::read("world.dat").unwrap()).unwrap();
+    let polled = world.clone();
+    let chunks = |world: &World| -> Vec<u64> {
+        (0..world.height)
+            .step_by(16)
+            .flat_map(|y| (0..world.width).step_by(16).map(move |x| (x, y)))
+            .map(|(x, y)| world.region_hash(x, y, 16, 16))
+            .collect()
+    };
+    let before = chunks(&world);
+    assert_eq!(before, chunks(&polled));
+
+    // only the chunk holding (40, 20) moves, drops don't count
+    world.get_tile_mut(40, 20).unwrap().foreground_item_id = 554;
+    world.dropped.items.clear();
+    let after = chunks(&world);
+    let changed: Vec<usize> = (0..before.len()).filter(|&i| before[i] != after[i]).collect();
+    assert_eq!(changed, vec![7 + 2]);
+
+    assert_eq!(world.tiles_in_rect(Rect::new(98, 58, 5, 5)).count(), 4);
+    assert_eq!(world.tiles_in_rect(Rect::new(200, 0, 5, 5)).count(), 0);
+    assert_eq!(world.region_hash(200, 0, 5, 5), World::new(crate::test_item_database()).region_hash(0, 0, 0, 0));
+}